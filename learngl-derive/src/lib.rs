@@ -0,0 +1,177 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Implements `learngl::uniform::Uniforms` by uploading every field to the
+/// uniform of the same name.
+///
+/// Fields can be renamed with `#[uniform(name = "u_view")]` and left out
+/// with `#[uniform(skip)]`.
+#[proc_macro_derive(Uniforms, attributes(uniform))]
+pub fn derive_uniforms(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_uniforms(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_uniforms(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input,
+                    "Uniforms can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "Uniforms can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut uploads = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let mut name = LitStr::new(&ident.to_string(), ident.span());
+        let mut skip = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("uniform"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = meta.value()?.parse()?;
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `name = \"...\"` or `skip`"))
+                }
+            })?;
+        }
+
+        if !skip {
+            uploads.push(quote! {
+                ::learngl::uniform::UniformValue::set_uniform(&self.#ident, program, #name)?;
+            });
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::learngl::uniform::Uniforms for #ident #ty_generics #where_clause {
+            fn set_uniforms(
+                &self,
+                program: &::learngl::shader::ShaderProgram,
+            ) -> ::std::result::Result<(), ::learngl::errors::GLWError> {
+                #(#uploads)*
+                ::std::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+/// Implements `learngl::vertex::Vertex` for a `#[repr(C)]` struct, with one
+/// attribute per field at locations `0, 1, ...` in declaration order
+/// (matrices take one location per column).
+///
+/// Integer fields are read as floats in `[0, 1]` with `#[vertex(normalized)]`.
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_vertex(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_vertex(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input,
+                    "Vertex can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "Vertex can only be derived for structs",
+            ))
+        }
+    };
+
+    // field offsets are only meaningful with a defined layout
+    let mut repr_c = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            repr_c |= meta.path.is_ident("C");
+            // skip the arguments of e.g. `align(16)` or `packed(2)`
+            if meta.input.peek(syn::token::Paren) {
+                let arguments;
+                syn::parenthesized!(arguments in meta.input);
+                arguments.parse::<proc_macro2::TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+    if !repr_c {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "Vertex can only be derived for #[repr(C)] structs",
+        ));
+    }
+
+    let mut pushes = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let mut normalized = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("vertex"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("normalized") {
+                    normalized = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `normalized`"))
+                }
+            })?;
+        }
+
+        pushes.push(quote! {
+            .push_field::<#ty>(::std::mem::offset_of!(Self, #ident), #normalized)
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::learngl::vertex::Vertex for #ident #ty_generics #where_clause {
+            fn layout() -> ::learngl::vertex::VertexLayout {
+                ::learngl::vertex::VertexLayout::new()
+                    #(#pushes)*
+                    .with_stride(::std::mem::size_of::<Self>())
+            }
+        }
+    })
+}
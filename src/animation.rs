@@ -0,0 +1,407 @@
+use std::{
+    ops::{Add, Mul},
+    rc::Rc,
+};
+
+use cgmath::{InnerSpace, Quaternion, Vector3};
+
+use crate::transform::Transform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Holds each keyframe until the next.
+    Step,
+    Linear,
+    /// Hermite spline through the keyframes, with an in and out tangent per
+    /// keyframe.
+    CubicSpline,
+}
+
+/// Values of one transform property, one per keyframe.
+///
+/// Cubic splines store three per keyframe instead, the in-tangent, the
+/// value and the out-tangent, as glTF does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Keyframes {
+    Translation(Vec<Vector3<f32>>),
+    Rotation(Vec<Quaternion<f32>>),
+    Scale(Vec<Vector3<f32>>),
+}
+
+/// Keyframes of one property of one target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationChannel {
+    /// Index of the transform in the pose the clip is sampled into, e.g. a
+    /// node index.
+    pub target: usize,
+    pub interpolation: Interpolation,
+    /// Seconds of each keyframe, ascending.
+    pub times: Vec<f32>,
+    pub keyframes: Keyframes,
+}
+
+impl AnimationChannel {
+    /// Writes the channel's value at `time` into `transform`, holding the
+    /// first and last keyframes outside their range.
+    pub fn sample(&self, time: f32, transform: &mut Transform) {
+        let Some(&last) = self.times.last() else {
+            return;
+        };
+        let next = self.times.partition_point(|&t| t <= time);
+        let (from, to, t, dt) = match next {
+            0 => (0, 0, 0.0, 0.0),
+            _ if time >= last => (next - 1, next - 1, 0.0, 0.0),
+            _ => {
+                let (start, end) = (self.times[next - 1], self.times[next]);
+                (next - 1, next, (time - start) / (end - start), end - start)
+            }
+        };
+        match &self.keyframes {
+            Keyframes::Translation(values) => {
+                transform.translation = self.interpolate(values, from, to, t, dt, Vector3::lerp);
+            }
+            Keyframes::Rotation(values) => {
+                transform.rotation = self.interpolate(values, from, to, t, dt, nlerp).normalize();
+            }
+            Keyframes::Scale(values) => {
+                transform.scale = self.interpolate(values, from, to, t, dt, Vector3::lerp);
+            }
+        }
+    }
+
+    /// Value `t` of the way from keyframe `from` to `to`, `dt` seconds
+    /// apart.
+    fn interpolate<T>(
+        &self,
+        values: &[T],
+        from: usize,
+        to: usize,
+        t: f32,
+        dt: f32,
+        lerp: impl Fn(T, T, f32) -> T,
+    ) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+    {
+        match self.interpolation {
+            Interpolation::Step => values[from],
+            Interpolation::Linear => lerp(values[from], values[to], t),
+            Interpolation::CubicSpline => {
+                let (t2, t3) = (t * t, t * t * t);
+                let (start, out_tangent) = (values[from * 3 + 1], values[from * 3 + 2]);
+                let (in_tangent, end) = (values[to * 3], values[to * 3 + 1]);
+                start * (2.0 * t3 - 3.0 * t2 + 1.0)
+                    + out_tangent * ((t3 - 2.0 * t2 + t) * dt)
+                    + end * (-2.0 * t3 + 3.0 * t2)
+                    + in_tangent * ((t3 - t2) * dt)
+            }
+        }
+    }
+}
+
+/// Keyframed transforms of a set of targets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip {
+    pub name: Option<String>,
+    pub channels: Vec<AnimationChannel>,
+}
+
+impl AnimationClip {
+    pub fn new(channels: Vec<AnimationChannel>) -> Self {
+        Self {
+            name: None,
+            channels,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Time of the last keyframe, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.channels
+            .iter()
+            .filter_map(|channel| channel.times.last().copied())
+            .fold(0.0, f32::max)
+    }
+
+    /// Writes the animated properties at `time` seconds into `pose`,
+    /// skipping targets out of its range.
+    pub fn sample(&self, time: f32, pose: &mut [Transform]) {
+        for channel in &self.channels {
+            if let Some(transform) = pose.get_mut(channel.target) {
+                channel.sample(time, transform);
+            }
+        }
+    }
+}
+
+/// A clip being played.
+#[derive(Debug, Clone)]
+struct Playback {
+    clip: Rc<AnimationClip>,
+    time: f32,
+    looping: bool,
+}
+
+impl Playback {
+    fn advance(&mut self, dt: f32) {
+        let duration = self.clip.duration();
+        self.time = match self.looping && duration > 0.0 {
+            true => (self.time + dt).rem_euclid(duration),
+            false => (self.time + dt).clamp(0.0, duration),
+        };
+    }
+}
+
+/// Plays [`AnimationClip`]s, cross-fading from one to the next.
+/// ```ignore
+/// animator.play(idle, true);
+/// // later
+/// animator.cross_fade(walk, 0.3, true);
+/// // every frame
+/// animator.update(dt);
+/// animator.apply(&mut pose);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Animator {
+    current: Option<Playback>,
+    /// Clip faded out, with the seconds the fade has run and lasts.
+    fading: Option<(Playback, f32, f32)>,
+    /// Playback rate, negative plays backwards.
+    pub speed: f32,
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            fading: None,
+            speed: 1.0,
+        }
+    }
+
+    /// Switches to `clip` from its start, at once.
+    pub fn play(&mut self, clip: Rc<AnimationClip>, looping: bool) {
+        self.fading = None;
+        self.current = Some(Playback {
+            clip,
+            time: 0.0,
+            looping,
+        });
+    }
+
+    /// Switches to `clip` from its start, blending over from the current
+    /// clip for `duration` seconds. Plays it at once without a current clip.
+    pub fn cross_fade(&mut self, clip: Rc<AnimationClip>, duration: f32, looping: bool) {
+        let previous = self.current.take();
+        self.play(clip, looping);
+        if duration > 0.0 {
+            self.fading = previous.map(|previous| (previous, 0.0, duration));
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.fading = None;
+    }
+
+    /// Advances the clips by `dt` seconds scaled by [`speed`](Self::speed).
+    pub fn update(&mut self, dt: f32) {
+        let dt = dt * self.speed;
+        if let Some(current) = &mut self.current {
+            current.advance(dt);
+        }
+        if let Some((previous, elapsed, duration)) = &mut self.fading {
+            previous.advance(dt);
+            *elapsed += dt.abs();
+            if *elapsed >= *duration {
+                self.fading = None;
+            }
+        }
+    }
+
+    pub fn clip(&self) -> Option<&Rc<AnimationClip>> {
+        self.current.as_ref().map(|current| &current.clip)
+    }
+
+    /// Seconds into the current clip.
+    pub fn time(&self) -> f32 {
+        self.current.as_ref().map_or(0.0, |current| current.time)
+    }
+
+    /// Jumps to `time` seconds into the current clip.
+    pub fn seek(&mut self, time: f32) {
+        if let Some(current) = &mut self.current {
+            current.time = 0.0;
+            current.advance(time);
+        }
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fading.is_some()
+    }
+
+    /// Whether the current clip doesn't loop and has reached its end, or
+    /// there is none.
+    pub fn is_finished(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_none_or(|current| !current.looping && current.time >= current.clip.duration())
+    }
+
+    /// Poses `pose` as the clips have it, leaving unanimated targets as they
+    /// are.
+    pub fn apply(&self, pose: &mut [Transform]) {
+        let Some(current) = &self.current else {
+            return;
+        };
+        let Some((previous, elapsed, duration)) = &self.fading else {
+            current.clip.sample(current.time, pose);
+            return;
+        };
+        let mut from = pose.to_vec();
+        previous.clip.sample(previous.time, &mut from);
+        current.clip.sample(current.time, pose);
+        let weight = (elapsed / duration).clamp(0.0, 1.0);
+        for (to, from) in pose.iter_mut().zip(from) {
+            *to = blend(&from, to, weight);
+        }
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Normalized lerp along the shorter arc, `q` and `-q` being the same
+/// rotation.
+fn nlerp(from: Quaternion<f32>, to: Quaternion<f32>, t: f32) -> Quaternion<f32> {
+    let to = match from.dot(to) < 0.0 {
+        true => -to,
+        false => to,
+    };
+    from.nlerp(to, t)
+}
+
+/// `weight` of the way from `from` to `to`.
+pub fn blend(from: &Transform, to: &Transform, weight: f32) -> Transform {
+    Transform {
+        translation: from.translation.lerp(to.translation, weight),
+        rotation: nlerp(from.rotation, to.rotation, weight),
+        scale: from.scale.lerp(to.scale, weight),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, Rotation3};
+
+    use super::*;
+
+    fn translation(
+        interpolation: Interpolation,
+        times: &[f32],
+        values: &[f32],
+    ) -> AnimationChannel {
+        AnimationChannel {
+            target: 0,
+            interpolation,
+            times: times.to_vec(),
+            keyframes: Keyframes::Translation(
+                values.iter().map(|&x| Vector3::new(x, 0.0, 0.0)).collect(),
+            ),
+        }
+    }
+
+    fn sample_x(channel: &AnimationChannel, time: f32) -> f32 {
+        let mut transform = Transform::identity();
+        channel.sample(time, &mut transform);
+        transform.translation.x
+    }
+
+    #[test]
+    fn step_holds_each_keyframe() {
+        let channel = translation(Interpolation::Step, &[0.0, 1.0], &[2.0, 4.0]);
+        assert_eq!(sample_x(&channel, 0.0), 2.0);
+        assert_eq!(sample_x(&channel, 0.99), 2.0);
+        assert_eq!(sample_x(&channel, 1.0), 4.0);
+    }
+
+    #[test]
+    fn linear_interpolates_and_clamps() {
+        let channel = translation(Interpolation::Linear, &[1.0, 3.0], &[2.0, 4.0]);
+        assert_eq!(sample_x(&channel, 0.0), 2.0);
+        assert_eq!(sample_x(&channel, 2.0), 3.0);
+        assert_eq!(sample_x(&channel, 2.5), 3.5);
+        assert_eq!(sample_x(&channel, 5.0), 4.0);
+    }
+
+    #[test]
+    fn cubic_spline_follows_the_tangents() {
+        // in-tangent, value, out-tangent per keyframe
+        let flat = translation(
+            Interpolation::CubicSpline,
+            &[0.0, 2.0],
+            &[0.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+        );
+        assert_eq!(sample_x(&flat, 1.0), 0.5);
+        assert_eq!(sample_x(&flat, 2.0), 1.0);
+
+        // tangents of the straight line through the values
+        let straight = translation(
+            Interpolation::CubicSpline,
+            &[0.0, 2.0],
+            &[0.5, 0.0, 0.5, 0.5, 1.0, 0.5],
+        );
+        assert!((sample_x(&straight, 0.5) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rotations_take_the_shorter_arc() {
+        let from = Quaternion::from_angle_z(Deg(10.0));
+        // the same rotation as 20 degrees, on the other hemisphere
+        let to = -Quaternion::from_angle_z(Deg(20.0));
+        let channel = AnimationChannel {
+            target: 0,
+            interpolation: Interpolation::Linear,
+            times: vec![0.0, 1.0],
+            keyframes: Keyframes::Rotation(vec![from, to]),
+        };
+        let mut transform = Transform::identity();
+        channel.sample(0.5, &mut transform);
+        let expected = Quaternion::from_angle_z(Deg(15.0));
+        assert!(transform.rotation.dot(expected).abs() > 0.9999);
+    }
+
+    #[test]
+    fn blending_opposite_quaternions_stays_finite() {
+        let from = Transform::identity();
+        let to = Transform {
+            rotation: -from.rotation,
+            ..Transform::identity()
+        };
+        let blended = blend(&from, &to, 0.5);
+        assert!(blended.rotation.dot(from.rotation).abs() > 0.9999);
+    }
+
+    #[test]
+    fn clips_sample_their_targets() {
+        let clip = AnimationClip::new(vec![
+            translation(Interpolation::Linear, &[0.0, 2.0], &[0.0, 2.0]),
+            AnimationChannel {
+                target: 5,
+                ..translation(Interpolation::Linear, &[0.0, 4.0], &[0.0, 1.0])
+            },
+        ]);
+        assert_eq!(clip.duration(), 4.0);
+        let mut pose = [Transform::identity(); 2];
+        clip.sample(1.0, &mut pose);
+        assert_eq!(pose[0].translation.x, 1.0);
+        assert_eq!(pose[1].translation.x, 0.0);
+    }
+}
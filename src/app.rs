@@ -0,0 +1,470 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::mpsc::Receiver,
+};
+
+use glfw::Context;
+
+#[cfg(feature = "renderdoc")]
+use crate::frame_capture::FrameCapture;
+use crate::{
+    context::{self, GlApi, GlProfile},
+    debug::{self, DebugOutput, DebugSeverity, DebugSink},
+    display::{self, DisplayMode, WindowGeometry},
+    errors::{GLWError, GLWErrorKind},
+    events::EventDispatcher,
+    input::Input,
+    timing::{FrameClock, FrameStats},
+    trace,
+};
+
+/// Window and context settings for [`run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub api: GlApi,
+    /// Major and minor version of the context, see
+    /// [`GlApi::default_version`].
+    pub gl_version: (u32, u32),
+    /// Ignored for GLES.
+    pub profile: GlProfile,
+    /// Requests a debug context, which reports more errors and warnings
+    /// through `KHR_debug`. [`run`] logs them through
+    /// [`AppContext::debug_output`].
+    pub debug: bool,
+    /// Samples of the default framebuffer, `None` for no multisampling.
+    pub samples: Option<u32>,
+    pub vsync: bool,
+    pub resizable: bool,
+    pub display_mode: DisplayMode,
+    /// Image file for the window icon.
+    pub icon: Option<PathBuf>,
+    /// Key capturing the next frame with RenderDoc, when the app was
+    /// launched from it, F9 by default. RenderDoc's own hotkeys, F12 among
+    /// them, are turned off.
+    #[cfg(feature = "renderdoc")]
+    pub capture_key: Option<glfw::Key>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            title: "LearnOpenGl".to_string(),
+            width: 800,
+            height: 600,
+            api: GlApi::OpenGl,
+            gl_version: (3, 3),
+            profile: GlProfile::Core,
+            debug: false,
+            samples: None,
+            vsync: true,
+            resizable: true,
+            display_mode: DisplayMode::Windowed,
+            icon: None,
+            #[cfg(feature = "renderdoc")]
+            capture_key: Some(glfw::Key::F9),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn builder() -> WindowBuilder {
+        WindowBuilder {
+            config: Self::default(),
+        }
+    }
+}
+
+/// Builds an [`AppConfig`] to [`run`] an app with, or opens a window with a
+/// loaded context directly.
+#[derive(Debug, Clone)]
+pub struct WindowBuilder {
+    config: AppConfig,
+}
+
+impl WindowBuilder {
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.config.title = title.into();
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.config.width = width;
+        self.config.height = height;
+        self
+    }
+
+    /// Switches the client API, with its default version.
+    pub fn api(mut self, api: GlApi) -> Self {
+        self.config.api = api;
+        self.config.gl_version = api.default_version();
+        self
+    }
+
+    pub fn gl_version(mut self, major: u32, minor: u32) -> Self {
+        self.config.gl_version = (major, minor);
+        self
+    }
+
+    pub fn profile(mut self, profile: GlProfile) -> Self {
+        self.config.profile = profile;
+        self
+    }
+
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.config.debug = debug;
+        self
+    }
+
+    /// Multisamples the default framebuffer, `None` for no multisampling.
+    pub fn samples(mut self, samples: Option<u32>) -> Self {
+        self.config.samples = samples;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.config.vsync = vsync;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.config.resizable = resizable;
+        self
+    }
+
+    pub fn display_mode(mut self, display_mode: DisplayMode) -> Self {
+        self.config.display_mode = display_mode;
+        self
+    }
+
+    pub fn icon(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.icon = Some(path.into());
+        self
+    }
+
+    #[cfg(feature = "renderdoc")]
+    pub fn capture_key(mut self, key: Option<glfw::Key>) -> Self {
+        self.config.capture_key = key;
+        self
+    }
+
+    pub fn config(self) -> AppConfig {
+        self.config
+    }
+
+    /// Opens the window, makes its context current and loads GL, for
+    /// driving the event loop without [`run`]. The display mode is applied
+    /// too, but not remembered as it is by [`AppContext`].
+    pub fn build(self) -> Result<WindowBundle, GLWError> {
+        let mut bundle = open_window(&self.config)?;
+        if self.config.display_mode != DisplayMode::Windowed {
+            let geometry = WindowGeometry::of(&bundle.window);
+            display::apply(
+                &mut bundle.glfw,
+                &mut bundle.window,
+                self.config.display_mode,
+                geometry,
+            );
+        }
+        Ok(bundle)
+    }
+}
+
+/// A window with a current context GL is loaded with, see
+/// [`WindowBuilder::build`].
+pub struct WindowBundle {
+    pub window: glfw::Window,
+    pub events: Receiver<(f64, glfw::WindowEvent)>,
+    pub glfw: glfw::Glfw,
+}
+
+/// The window and GLFW handles an [`App`] is run with.
+pub struct AppContext {
+    /// Debug message callback, installed when [`AppConfig::debug`] is set
+    /// and the context supports it. Replace it to change the sink.
+    // dropped first, while the context lives
+    pub debug_output: Option<DebugOutput>,
+    pub window: glfw::Window,
+    pub glfw: glfw::Glfw,
+    /// Input state, up to date with the frame's events in `update`.
+    pub input: Input,
+    /// Timing of the current frame, ticked before `update`.
+    pub clock: FrameClock,
+    /// Frame time statistics, recorded with every tick.
+    pub stats: FrameStats,
+    /// Handlers registered for window and app defined events.
+    pub events: EventDispatcher,
+    /// RenderDoc's API, when the app was launched from RenderDoc.
+    #[cfg(feature = "renderdoc")]
+    pub frame_capture: Option<FrameCapture>,
+    #[cfg(feature = "renderdoc")]
+    capture_key: Option<glfw::Key>,
+    display_mode: DisplayMode,
+    /// Where the window goes back to when leaving fullscreen.
+    windowed_geometry: WindowGeometry,
+    /// When [`set_title_fmt`](Self::set_title_fmt) last changed the title.
+    title_updated_at: Option<f64>,
+}
+
+impl AppContext {
+    /// Ends the run loop after the current frame.
+    pub fn close(&mut self) {
+        self.window.set_should_close(true);
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    /// Sets the title to `args` at most every quarter second, so it can be
+    /// called every frame, e.g. with
+    /// `ctx.set_title_fmt(format_args!("{} fps", fps))`.
+    pub fn set_title_fmt(&mut self, args: fmt::Arguments) {
+        const INTERVAL: f64 = 0.25;
+        let now = self.glfw.get_time();
+        if self
+            .title_updated_at
+            .is_some_and(|updated_at| now - updated_at < INTERVAL)
+        {
+            return;
+        }
+        self.title_updated_at = Some(now);
+        self.window.set_title(&args.to_string());
+    }
+
+    /// Sets the window icon to the image at `path`.
+    pub fn set_icon(&mut self, path: impl AsRef<Path>) -> Result<(), GLWError> {
+        display::set_icon(&mut self.window, path)
+    }
+
+    pub fn display_mode(&self) -> DisplayMode {
+        self.display_mode
+    }
+
+    /// Switches between windowed and fullscreen modes, remembering the
+    /// window's position and size to restore.
+    pub fn set_display_mode(&mut self, mode: DisplayMode) {
+        if self.display_mode == DisplayMode::Windowed {
+            self.windowed_geometry = WindowGeometry::of(&self.window);
+        }
+        display::apply(
+            &mut self.glfw,
+            &mut self.window,
+            mode,
+            self.windowed_geometry,
+        );
+        self.display_mode = mode;
+    }
+
+    /// Toggles between windowed and borderless fullscreen.
+    pub fn toggle_fullscreen(&mut self) {
+        self.set_display_mode(match self.display_mode {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            _ => DisplayMode::Windowed,
+        });
+    }
+
+    /// Captures the cursor for mouse look, see [`Input::capture_cursor`].
+    pub fn capture_cursor(&mut self, captured: bool) {
+        self.input.capture_cursor(&mut self.window, captured);
+    }
+
+    /// Starts a RenderDoc capture if the capture key went down this frame,
+    /// returning whether it did.
+    #[cfg(feature = "renderdoc")]
+    fn start_requested_capture(&mut self) -> bool {
+        let (Some(capture), Some(key)) = (&mut self.frame_capture, self.capture_key) else {
+            return false;
+        };
+        if !self.input.just_pressed(key) || capture.is_capturing() {
+            return false;
+        }
+        capture.start();
+        true
+    }
+}
+
+/// An example driven by [`run`]. Each frame runs `on_event` for pending
+/// events, then `update` and `render`, then swaps buffers.
+pub trait App: Sized {
+    /// Creates the app once the context is current and GL is loaded.
+    fn init(ctx: &mut AppContext) -> Result<Self, GLWError>;
+
+    /// Advances the app by `dt` seconds.
+    fn update(&mut self, _ctx: &mut AppContext, _dt: f32) {}
+
+    fn render(&mut self, ctx: &mut AppContext);
+
+    /// Called with the new framebuffer size, after the viewport is updated.
+    fn resize(&mut self, _ctx: &mut AppContext, _width: i32, _height: i32) {}
+
+    fn on_event(&mut self, _ctx: &mut AppContext, _event: &glfw::WindowEvent) {}
+}
+
+/// Opens a window as described by `config` and runs `A` until it's closed.
+pub fn run<A: App>(config: AppConfig) -> Result<(), GLWError> {
+    let (mut ctx, events) = create_context(&config)?;
+    let mut app = A::init(&mut ctx)?;
+
+    // loading in `init` shouldn't count as the first frame
+    ctx.clock = FrameClock::new(ctx.glfw.get_time());
+    while !ctx.window.should_close() {
+        let _frame = trace::span!("frame", frame = ctx.clock.frame_count());
+        {
+            let _span = trace::span!("events");
+            ctx.glfw.poll_events();
+            ctx.input.begin_frame();
+            ctx.input.poll_gamepads(&ctx.glfw);
+            for (_, event) in glfw::flush_messages(&events) {
+                ctx.input.handle_event(&event);
+                if let glfw::WindowEvent::FramebufferSize(width, height) = event {
+                    unsafe {
+                        gl::Viewport(0, 0, width, height);
+                    }
+                    app.resize(&mut ctx, width, height);
+                }
+                app.on_event(&mut ctx, &event);
+                EventDispatcher::dispatch(&mut ctx, &event);
+            }
+            EventDispatcher::dispatch_custom(&mut ctx);
+        }
+
+        #[cfg(feature = "renderdoc")]
+        let capturing = ctx.start_requested_capture();
+
+        let dt = ctx.clock.tick(ctx.glfw.get_time());
+        ctx.stats.record(dt);
+        {
+            let _span = trace::span!("update", dt);
+            app.update(&mut ctx, dt);
+        }
+        {
+            let _span = trace::span!("render");
+            app.render(&mut ctx);
+        }
+        {
+            let _span = trace::span!("swap_buffers");
+            ctx.window.swap_buffers();
+        }
+        #[cfg(feature = "renderdoc")]
+        if capturing {
+            if let Some(capture) = &mut ctx.frame_capture {
+                capture.end();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn create_context(
+    config: &AppConfig,
+) -> Result<(AppContext, Receiver<(f64, glfw::WindowEvent)>), GLWError> {
+    let WindowBundle {
+        window,
+        events,
+        glfw,
+    } = open_window(config)?;
+
+    let debug_output = match config.debug && debug::supported() {
+        true => Some(DebugOutput::new(DebugSink::Log, DebugSeverity::Low)?),
+        false => None,
+    };
+    #[cfg(feature = "renderdoc")]
+    let frame_capture = FrameCapture::new().ok().map(|mut capture| {
+        if config.capture_key.is_some() {
+            capture.disable_capture_keys();
+        }
+        capture
+    });
+    let clock = FrameClock::new(glfw.get_time());
+    let mut ctx = AppContext {
+        debug_output,
+        windowed_geometry: WindowGeometry::of(&window),
+        window,
+        glfw,
+        input: Input::new(),
+        clock,
+        stats: FrameStats::default(),
+        events: EventDispatcher::default(),
+        #[cfg(feature = "renderdoc")]
+        frame_capture,
+        #[cfg(feature = "renderdoc")]
+        capture_key: config.capture_key,
+        display_mode: DisplayMode::Windowed,
+        title_updated_at: None,
+    };
+    if config.display_mode != DisplayMode::Windowed {
+        ctx.set_display_mode(config.display_mode);
+    }
+    Ok((ctx, events))
+}
+
+/// Opens a windowed window with `config`'s icon and a current context.
+fn open_window(config: &AppConfig) -> Result<WindowBundle, GLWError> {
+    let mut glfw = glfw::init(glfw::LOG_ERRORS)?;
+    context_hints(&mut glfw, config);
+
+    let (mut window, events) = glfw
+        .create_window(
+            config.width,
+            config.height,
+            &config.title,
+            glfw::WindowMode::Windowed,
+        )
+        .ok_or(GLWErrorKind::WindowCreationFailed)?;
+    window.make_current();
+    window.set_all_polling(true);
+    glfw.set_swap_interval(match config.vsync {
+        true => glfw::SwapInterval::Sync(1),
+        false => glfw::SwapInterval::None,
+    });
+
+    context::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+    let (width, height) = window.get_framebuffer_size();
+    unsafe {
+        gl::Viewport(0, 0, width, height);
+    }
+    if config.samples.is_some() {
+        context::enable_multisample();
+    }
+    if let Some(icon) = &config.icon {
+        display::set_icon(&mut window, icon)?;
+    }
+
+    Ok(WindowBundle {
+        window,
+        events,
+        glfw,
+    })
+}
+
+/// Sets the window hints for the context and window `config` describes.
+pub(crate) fn context_hints(glfw: &mut glfw::Glfw, config: &AppConfig) {
+    let (major, minor) = config.gl_version;
+    glfw.window_hint(glfw::WindowHint::ContextVersion(major, minor));
+    match config.api {
+        GlApi::OpenGl => match config.profile {
+            GlProfile::Core => {
+                glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+                    glfw::OpenGlProfileHint::Core,
+                ));
+                #[cfg(target_os = "macos")]
+                glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+            }
+            GlProfile::Compatibility => {
+                glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+                    glfw::OpenGlProfileHint::Compat,
+                ));
+            }
+        },
+        GlApi::OpenGlEs => {
+            glfw.window_hint(glfw::WindowHint::ClientApi(glfw::ClientApiHint::OpenGlEs));
+        }
+    }
+    glfw.window_hint(glfw::WindowHint::OpenGlDebugContext(config.debug));
+    glfw.window_hint(glfw::WindowHint::Samples(config.samples));
+    glfw.window_hint(glfw::WindowHint::Resizable(config.resizable));
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use russimp::{
+    material::{Material as AiMaterial, PropertyTypeInfo, TextureType},
+    mesh::Mesh as AiMesh,
+    scene::{PostProcess, Scene},
+};
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    material::Material,
+    mesh::Mesh,
+    model::{cached_texture, Model, ModelVertex},
+    normals, optimize,
+    texture::Texture2D,
+};
+
+impl Model {
+    /// Loads any file Assimp reads, e.g. FBX, COLLADA or 3DS, like the
+    /// LearnOpenGL model chapter does. Materials are read as
+    /// [`from_obj`](Self::from_obj) reads MTL ones, with external textures
+    /// resolved relative to the file; embedded ones are skipped.
+    ///
+    /// Node transforms are baked into the vertices, and the meshes are
+    /// [optimized](optimize::optimize) for drawing.
+    pub fn from_assimp(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        let path = path.as_ref();
+        let flags = vec![
+            PostProcess::Triangulate,
+            PostProcess::GenerateSmoothNormals,
+            PostProcess::PreTransformVertices,
+            PostProcess::SortByPrimitiveType,
+        ];
+        let scene = Scene::from_file(&path.to_string_lossy(), flags).map_err(|source| {
+            GLWErrorKind::AssimpLoadFailed {
+                path: path.to_path_buf(),
+                source,
+            }
+        })?;
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut textures = HashMap::new();
+        let materials: Vec<_> = scene
+            .materials
+            .iter()
+            .map(|material| load_material(material, directory, &mut textures).map(Rc::new))
+            .collect::<Result<_, _>>()?;
+
+        let meshes = scene
+            .meshes
+            .iter()
+            .filter_map(|source| {
+                let (mut vertices, mut indices) = vertices_of(source);
+                if indices.is_empty() {
+                    return None;
+                }
+                optimize::optimize(
+                    &mut vertices,
+                    &mut indices,
+                    ModelVertex::to_bits,
+                    |vertex| vertex.position,
+                );
+                let mesh = Mesh::from_vertices(&vertices, Some(&indices))
+                    .with_bounds_of(vertices.iter().map(|vertex| vertex.position));
+                Some(match materials.get(source.material_index as usize) {
+                    Some(material) => mesh.with_material(Rc::clone(material)),
+                    None => mesh,
+                })
+            })
+            .collect();
+        Ok(Self::from_meshes(meshes, materials))
+    }
+}
+
+fn load_material(
+    material: &AiMaterial,
+    directory: &Path,
+    textures: &mut HashMap<PathBuf, Rc<Texture2D>>,
+) -> Result<Material, GLWError> {
+    let mut model_material = Material::without_program();
+    for property in &material.properties {
+        let param = match property.key.as_str() {
+            "$clr.ambient" => "material.ambientColor",
+            "$clr.diffuse" => "material.diffuseColor",
+            "$clr.specular" => "material.specularColor",
+            "$mat.shininess" => "material.shininess",
+            _ => "",
+        };
+        match &property.data {
+            PropertyTypeInfo::String(name) if property.key == "?mat.name" => {
+                model_material.name = Some(name.clone());
+            }
+            PropertyTypeInfo::FloatArray(values) if !param.is_empty() => match values[..] {
+                [shininess] => model_material.set_param(param, shininess),
+                [r, g, b, ..] => model_material.set_param(param, [r, g, b]),
+                _ => {}
+            },
+            // embedded textures are named `*` and their index
+            PropertyTypeInfo::String(file)
+                if property.key == "$tex.file" && property.index == 0 && !file.starts_with('*') =>
+            {
+                let slot = match property.semantic {
+                    TextureType::Diffuse => Some(("material.diffuse", true)),
+                    TextureType::Specular => Some(("material.specular", false)),
+                    // OBJ normal maps are usually under `map_Bump`, which
+                    // Assimp reads as a height map
+                    TextureType::Normals | TextureType::Height => Some(("material.normal", false)),
+                    _ => None,
+                };
+                if let Some((slot, srgb)) = slot {
+                    let texture = cached_texture(&directory.join(file), srgb, textures)?;
+                    model_material.set_texture(slot, texture);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(model_material)
+}
+
+fn vertices_of(mesh: &AiMesh) -> (Vec<ModelVertex>, Vec<u32>) {
+    let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|p| [p.x, p.y, p.z]).collect();
+    // points and lines are sorted into meshes of their own, skip them
+    let indices: Vec<u32> = mesh
+        .faces
+        .iter()
+        .filter(|face| face.0.len() == 3)
+        .flat_map(|face| face.0.iter().copied())
+        .collect();
+    let normals = match mesh.normals.len() == positions.len() {
+        true => mesh.normals.iter().map(|n| [n.x, n.y, n.z]).collect(),
+        false => normals::smooth_normals(&positions, &indices),
+    };
+    let tex_coords: Vec<[f32; 2]> = match mesh.texture_coords.first() {
+        Some(Some(tex_coords)) => tex_coords.iter().map(|uv| [uv.x, uv.y]).collect(),
+        _ => vec![[0.0, 0.0]; positions.len()],
+    };
+    let tangents = normals::tangents(&positions, &normals, &tex_coords, &indices);
+    let vertices = (0..positions.len())
+        .map(|i| ModelVertex {
+            position: positions[i],
+            normal: normals[i],
+            tex_coords: tex_coords.get(i).copied().unwrap_or_default(),
+            tangent: tangents[i],
+        })
+        .collect();
+    (vertices, indices)
+}
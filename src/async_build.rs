@@ -0,0 +1,231 @@
+use std::{
+    task::Poll,
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    binary_cache::ProgramBinaryCache,
+    errors::{GLWError, GLWErrorKind},
+    preprocessor::Source,
+    shader::{
+        self, LinkOptions, PreparedSources, Shader, ShaderProgram, ShaderProgramBuilder, ShaderType,
+    },
+    utils,
+};
+
+/// `GL_COMPLETION_STATUS_KHR`, not part of the core bindings.
+const COMPLETION_STATUS: gl::types::GLenum = 0x91B1;
+
+type PreprocessHandle = JoinHandle<Result<(Source, ShaderType), GLWError>>;
+
+enum State {
+    Reading(Vec<PreprocessHandle>),
+    Compiling {
+        prepared: PreparedSources,
+        cache: Option<ProgramBinaryCache>,
+        link_options: LinkOptions,
+        shaders: Vec<Shader>,
+    },
+    Linking {
+        prepared: PreparedSources,
+        cache: Option<ProgramBinaryCache>,
+        link_options: LinkOptions,
+        shaders: Vec<Shader>,
+        shader_program_id: u32,
+    },
+    Done,
+}
+
+/// A program being built by [`ShaderProgramBuilder::build_async`].
+///
+/// Files are read and preprocessed on worker threads. When the driver
+/// supports `KHR_parallel_shader_compile`, compiling and linking also happen
+/// in the background and [`poll`](Self::poll) only checks for completion,
+/// otherwise they block the poll that issues them.
+pub struct PendingProgram<'a> {
+    builder: Option<ShaderProgramBuilder<'a>>,
+    state: State,
+    parallel: bool,
+}
+
+impl<'a> ShaderProgramBuilder<'a> {
+    pub fn build_async(self) -> PendingProgram<'a> {
+        let handles = self
+            .shader_paths
+            .iter()
+            .map(|(path, shader_type)| {
+                let (path, shader_type, defines, target) = (
+                    path.clone(),
+                    *shader_type,
+                    self.defines.clone(),
+                    self.glsl_target,
+                );
+                thread::spawn(move || shader::preprocess_path(&path, shader_type, &defines, target))
+            })
+            .collect();
+
+        PendingProgram {
+            builder: Some(self),
+            state: State::Reading(handles),
+            parallel: utils::has_extension("GL_KHR_parallel_shader_compile")
+                || utils::has_extension("GL_ARB_parallel_shader_compile"),
+        }
+    }
+}
+
+impl PendingProgram<'_> {
+    /// Advances the build as far as possible without blocking. Must be called
+    /// from the thread the GL context is current on. Polling again once the
+    /// result is ready fails with
+    /// [`ProgramAlreadyBuilt`](GLWErrorKind::ProgramAlreadyBuilt).
+    pub fn poll(&mut self) -> Poll<Result<ShaderProgram, GLWError>> {
+        match self.step(false) {
+            Ok(Some(program)) => Poll::Ready(Ok(program)),
+            Ok(None) => Poll::Pending,
+            Err(err) => {
+                self.state = State::Done;
+                self.builder = None;
+                Poll::Ready(Err(err))
+            }
+        }
+    }
+
+    /// Blocks until the program is built.
+    pub fn wait(mut self) -> Result<ShaderProgram, GLWError> {
+        loop {
+            if let Some(program) = self.step(true)? {
+                return Ok(program);
+            }
+        }
+    }
+
+    fn step(&mut self, block: bool) -> Result<Option<ShaderProgram>, GLWError> {
+        let Some(builder) = self.builder.as_ref() else {
+            Err(GLWErrorKind::ProgramAlreadyBuilt)?
+        };
+
+        match std::mem::replace(&mut self.state, State::Done) {
+            State::Reading(handles) => {
+                if !block && !handles.iter().all(JoinHandle::is_finished) {
+                    self.state = State::Reading(handles);
+                    return Ok(None);
+                }
+
+                let path_sources = handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("shader preprocessing thread panicked"))
+                    .collect::<Result<_, _>>()?;
+                let prepared = builder.prepare(path_sources)?;
+                let (cache, link_options) = builder.link_setup(&prepared)?;
+
+                if let Some(shader_program_id) = cache.as_ref().and_then(ProgramBinaryCache::load) {
+                    let embedded = prepared.compile_embedded().inspect_err(|_| unsafe {
+                        gl::DeleteProgram(shader_program_id);
+                    })?;
+                    builder.attach_to_binary(shader_program_id, &embedded);
+                    let builder = self.builder.take().unwrap();
+                    return builder
+                        .finish(shader_program_id, &prepared, embedded, link_options)
+                        .map(Some);
+                }
+
+                let shaders = prepared.compile_unchecked()?;
+                self.state = State::Compiling {
+                    prepared,
+                    cache,
+                    link_options,
+                    shaders,
+                };
+            }
+            State::Compiling {
+                prepared,
+                cache,
+                link_options,
+                shaders,
+            } => {
+                let compiled = shaders.iter().all(|shader| {
+                    self.complete(|status| unsafe {
+                        gl::GetShaderiv(shader.shader_id, COMPLETION_STATUS, status)
+                    })
+                });
+                if !block && !compiled {
+                    self.state = State::Compiling {
+                        prepared,
+                        cache,
+                        link_options,
+                        shaders,
+                    };
+                    return Ok(None);
+                }
+
+                prepared.check_compiled(&shaders)?;
+                let shader_program_id =
+                    unsafe { shader::link_unchecked(builder.shader_ids(&shaders), &link_options) };
+                self.state = State::Linking {
+                    prepared,
+                    cache,
+                    link_options,
+                    shaders,
+                    shader_program_id,
+                };
+            }
+            State::Linking {
+                prepared,
+                cache,
+                link_options,
+                shaders,
+                shader_program_id,
+            } => {
+                let linked = self.complete(|status| unsafe {
+                    gl::GetProgramiv(shader_program_id, COMPLETION_STATUS, status)
+                });
+                if !block && !linked {
+                    self.state = State::Linking {
+                        prepared,
+                        cache,
+                        link_options,
+                        shaders,
+                        shader_program_id,
+                    };
+                    return Ok(None);
+                }
+
+                let shader_program_id = unsafe { shader::check_linked(shader_program_id)? };
+                if let Some(cache) = &cache {
+                    let _ = cache.store(shader_program_id);
+                }
+                let builder = self.builder.take().unwrap();
+                return builder
+                    .finish(shader_program_id, &prepared, shaders, link_options)
+                    .map(Some);
+            }
+            State::Done => Err(GLWErrorKind::ProgramAlreadyBuilt)?,
+        }
+
+        Ok(None)
+    }
+
+    /// Queries a completion status, treating everything as complete when
+    /// the driver compiles synchronously anyway.
+    fn complete(&self, query: impl FnOnce(&mut i32)) -> bool {
+        if !self.parallel {
+            return true;
+        }
+        let mut status = 0;
+        query(&mut status);
+        status == gl::TRUE as i32
+    }
+}
+
+impl Drop for PendingProgram<'_> {
+    fn drop(&mut self) {
+        if let State::Linking {
+            shader_program_id, ..
+        } = self.state
+        {
+            unsafe {
+                gl::DeleteProgram(shader_program_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use std::{collections::HashMap, hash::Hash, path::Path};
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    texture::{self, Filter, MinFilter, Texture2D, TextureFormat, WrapMode},
+};
+
+/// Where an entry ended up in the atlas, in texels and normalized texture
+/// coordinates. The first row of the entry's pixels is at `uv_min[1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+}
+
+/// Packs rectangles into rows ("shelves") as tall as their tallest entry.
+/// Simple and fast, and wastes little space when entries have similar
+/// heights, as glyphs of one font or sprites of one sheet do.
+#[derive(Debug, Clone)]
+pub struct ShelfPacker {
+    width: u32,
+    height: u32,
+    padding: u32,
+    /// y, height and the x the next entry goes at, of every shelf.
+    shelves: Vec<(u32, u32, u32)>,
+}
+
+impl ShelfPacker {
+    /// Packer for a `width` by `height` area, keeping `padding` texels
+    /// between entries so filtering doesn't bleed between them.
+    pub fn new(width: u32, height: u32, padding: u32) -> Self {
+        Self {
+            width,
+            height,
+            padding,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Finds space for a `width` by `height` rectangle, returning its
+    /// position, or `None` if the area is full.
+    pub fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let padded_width = width + self.padding;
+        let padded_height = height + self.padding;
+
+        // the lowest shelf the entry fits on, wasting the least height
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|(_, shelf_height, x)| {
+                *shelf_height >= padded_height && x + padded_width <= self.width
+            })
+            .min_by_key(|(_, shelf_height, _)| *shelf_height);
+        if let Some((y, _, x)) = shelf {
+            let position = (*x, *y);
+            *x += padded_width;
+            return Some(position);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map_or(0, |(y, shelf_height, _)| y + shelf_height);
+        if padded_width > self.width || y + padded_height > self.height {
+            return None;
+        }
+        self.shelves.push((y, padded_height, padded_width));
+        Some((0, y))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// One texture holding many small images, looked up by key.
+pub struct TextureAtlas<K> {
+    texture: Texture2D,
+    packer: ShelfPacker,
+    entries: HashMap<K, AtlasRect>,
+}
+
+impl<K: Eq + Hash> TextureAtlas<K> {
+    /// Creates an empty, transparent atlas. `format` is `R8` for glyph
+    /// bitmaps, or one of the 4 channel 8 bit formats for images.
+    ///
+    /// # Panics
+    /// If `format` isn't one of those.
+    pub fn new(width: u32, height: u32, format: TextureFormat) -> Self {
+        assert!(
+            matches!(
+                format,
+                TextureFormat::R8 | TextureFormat::Rgba8 | TextureFormat::Srgb8Alpha8
+            ),
+            "atlases are R8 or RGBA8"
+        );
+        let clear = vec![0; width as usize * height as usize * format.pixel_size()];
+        let texture = Texture2D::builder()
+            .internal_format(format)
+            .wrap(WrapMode::ClampToEdge)
+            .min_filter(MinFilter::Linear)
+            .mag_filter(Filter::Linear)
+            .mipmaps(false)
+            .build_from_data(width, height, format.pixel_format().0, &clear);
+
+        Self {
+            texture,
+            packer: ShelfPacker::new(width, height, 1),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Packs and uploads tightly packed `pixels` with the atlas' channel
+    /// count. Inserting an existing key replaces its rect, without
+    /// reclaiming the old space.
+    ///
+    /// # Panics
+    /// If `pixels` doesn't match the dimensions.
+    pub fn insert(
+        &mut self,
+        key: K,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<AtlasRect, GLWError> {
+        let format = self.texture.format();
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * format.pixel_size(),
+            "pixel data doesn't match the entry size"
+        );
+        let (x, y) = self
+            .packer
+            .pack(width, height)
+            .ok_or(GLWErrorKind::AtlasFull { width, height })?;
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.texture.id());
+        }
+        texture::with_unpack_alignment_1(|| unsafe {
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                format.pixel_format().0,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr().cast(),
+            );
+        });
+
+        let (atlas_width, atlas_height) = (self.packer.width() as f32, self.packer.height() as f32);
+        let rect = AtlasRect {
+            x,
+            y,
+            width,
+            height,
+            uv_min: [x as f32 / atlas_width, y as f32 / atlas_height],
+            uv_max: [
+                (x + width) as f32 / atlas_width,
+                (y + height) as f32 / atlas_height,
+            ],
+        };
+        self.entries.insert(key, rect);
+        Ok(rect)
+    }
+
+    /// Loads the image at `path`, converted to the atlas' channel count and
+    /// flipped so its top is at `uv_max[1]`, and inserts it.
+    pub fn insert_path(&mut self, key: K, path: impl AsRef<Path>) -> Result<AtlasRect, GLWError> {
+        let path = path.as_ref();
+        let image = texture::load_image(path)?.flipv();
+        let (width, height, pixels) = match self.texture.format() {
+            TextureFormat::R8 => {
+                let image = image.to_luma();
+                (image.width(), image.height(), image.into_raw())
+            }
+            _ => {
+                let image = image.to_rgba();
+                (image.width(), image.height(), image.into_raw())
+            }
+        };
+        self.insert(key, width, height, &pixels)
+    }
+
+    pub fn get(&self, key: &K) -> Option<AtlasRect> {
+        self.entries.get(key).copied()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn bind(&self, unit: u32) {
+        self.texture.bind(unit);
+    }
+
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+}
+
+/// Collects entries to pack all at once, tallest first, which packs much
+/// tighter than inserting them one by one in arbitrary order.
+pub struct TextureAtlasBuilder<K> {
+    entries: Vec<(K, u32, u32, Vec<u8>)>,
+    format: TextureFormat,
+}
+
+impl<K: Eq + Hash> TextureAtlasBuilder<K> {
+    pub fn new(format: TextureFormat) -> Self {
+        Self {
+            entries: Vec::new(),
+            format,
+        }
+    }
+
+    /// Adds tightly packed `pixels` with the atlas' channel count.
+    pub fn add(mut self, key: K, width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        self.entries.push((key, width, height, pixels));
+        self
+    }
+
+    /// Packs every entry into a `width` by `height` atlas, which can still be
+    /// appended to afterwards.
+    pub fn build(mut self, width: u32, height: u32) -> Result<TextureAtlas<K>, GLWError> {
+        self.entries
+            .sort_by_key(|(_, _, entry_height, _)| std::cmp::Reverse(*entry_height));
+
+        let mut atlas = TextureAtlas::new(width, height, self.format);
+        for (key, entry_width, entry_height, pixels) in self.entries {
+            atlas.insert(key, entry_width, entry_height, &pixels)?;
+        }
+        Ok(atlas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_along_a_shelf_then_below_it() {
+        let mut packer = ShelfPacker::new(16, 16, 0);
+        assert_eq!(packer.pack(8, 4), Some((0, 0)));
+        assert_eq!(packer.pack(8, 4), Some((8, 0)));
+        assert_eq!(packer.pack(8, 4), Some((0, 4)));
+    }
+
+    #[test]
+    fn keeps_padding_between_entries() {
+        let mut packer = ShelfPacker::new(16, 16, 1);
+        assert_eq!(packer.pack(4, 4), Some((0, 0)));
+        assert_eq!(packer.pack(4, 4), Some((5, 0)));
+        assert_eq!(packer.pack(4, 8), Some((0, 5)));
+    }
+
+    #[test]
+    fn picks_the_shelf_wasting_the_least_height() {
+        let mut packer = ShelfPacker::new(16, 16, 0);
+        packer.pack(12, 8);
+        assert_eq!(packer.pack(8, 2), Some((0, 8)));
+        assert_eq!(packer.pack(4, 2), Some((8, 8)));
+        assert_eq!(packer.pack(4, 6), Some((12, 0)));
+    }
+
+    #[test]
+    fn fails_when_full() {
+        let mut packer = ShelfPacker::new(8, 8, 0);
+        assert_eq!(packer.pack(9, 1), None);
+        assert_eq!(packer.pack(8, 8), Some((0, 0)));
+        assert_eq!(packer.pack(1, 1), None);
+    }
+
+    #[test]
+    fn entries_never_overlap() {
+        let mut packer = ShelfPacker::new(64, 64, 1);
+        let mut rects = Vec::new();
+        for i in 0..200u32 {
+            let (width, height) = (1 + i * 7 % 9, 1 + i * 5 % 11);
+            if let Some((x, y)) = packer.pack(width, height) {
+                assert!(x + width <= 64 && y + height <= 64);
+                rects.push((x, y, width, height));
+            }
+        }
+        for (i, &(ax, ay, aw, ah)) in rects.iter().enumerate() {
+            for &(bx, by, bw, bh) in &rects[i + 1..] {
+                let apart = ax + aw <= bx || bx + bw <= ax || ay + ah <= by || by + bh <= ay;
+                assert!(
+                    apart,
+                    "{:?} overlaps {:?}",
+                    (ax, ay, aw, ah),
+                    (bx, by, bw, bh)
+                );
+            }
+        }
+    }
+}
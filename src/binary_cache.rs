@@ -0,0 +1,111 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    ffi::c_void,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    shader::{LinkOptions, ShaderType},
+    utils,
+};
+
+/// On-disk cache of linked program binaries.
+pub struct ProgramBinaryCache {
+    path: PathBuf,
+}
+
+impl ProgramBinaryCache {
+    /// Whether the driver can hand out program binaries at all.
+    pub fn supported() -> bool {
+        let mut formats = 0;
+        unsafe {
+            gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut formats);
+        }
+        formats > 0
+    }
+
+    pub fn new<'s>(
+        dir: impl AsRef<Path>,
+        stages: impl IntoIterator<Item = (ShaderType, &'s str)>,
+    ) -> Self {
+        Self::with_link_options(dir, stages, &LinkOptions::default())
+    }
+
+    /// Entry of a program linked with `options`, which change the binary
+    /// as much as the sources do.
+    pub(crate) fn with_link_options<'s>(
+        dir: impl AsRef<Path>,
+        stages: impl IntoIterator<Item = (ShaderType, &'s str)>,
+        options: &LinkOptions,
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        // binaries are only valid for the driver that produced them
+        for name in [gl::VENDOR, gl::RENDERER, gl::VERSION] {
+            utils::gl_string(name).hash(&mut hasher);
+        }
+        for (shader_type, source) in stages {
+            u32::from(shader_type).hash(&mut hasher);
+            source.hash(&mut hasher);
+        }
+        options.separable.hash(&mut hasher);
+        options.transform_feedback.hash(&mut hasher);
+
+        Self {
+            path: dir.as_ref().join(format!("{:016x}.bin", hasher.finish())),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Creates a program from the cached binary, returning `None` if there is
+    /// no entry or the driver rejects it.
+    pub fn load(&self) -> Option<u32> {
+        let data = fs::read(&self.path).ok()?;
+        let (format, binary) = data.split_first_chunk::<4>()?;
+
+        unsafe {
+            let shader_program_id = gl::CreateProgram();
+            gl::ProgramBinary(
+                shader_program_id,
+                u32::from_le_bytes(*format),
+                binary.as_ptr() as *const c_void,
+                binary.len() as i32,
+            );
+
+            if utils::check_program_success(shader_program_id, gl::LINK_STATUS).is_err() {
+                gl::DeleteProgram(shader_program_id);
+                return None;
+            }
+            Some(shader_program_id)
+        }
+    }
+
+    /// Writes the binary of a linked program to the cache.
+    pub fn store(&self, shader_program_id: u32) -> std::io::Result<()> {
+        let (format, binary) = unsafe {
+            let mut length = 0;
+            gl::GetProgramiv(shader_program_id, gl::PROGRAM_BINARY_LENGTH, &mut length);
+
+            let mut binary = vec![0u8; length as usize];
+            let mut format = 0;
+            gl::GetProgramBinary(
+                shader_program_id,
+                length,
+                &mut length,
+                &mut format,
+                binary.as_mut_ptr() as *mut c_void,
+            );
+            binary.truncate(length as usize);
+            (format, binary)
+        };
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, [&format.to_le_bytes()[..], &binary].concat())
+    }
+}
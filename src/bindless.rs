@@ -0,0 +1,111 @@
+use std::{ffi::c_void, marker::PhantomData, sync::OnceLock};
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    ssbo::SsboBuffer,
+    texture_units::BindTexture,
+    utils,
+};
+
+type GetTextureHandle = unsafe extern "system" fn(u32) -> u64;
+type MakeTextureHandleResident = unsafe extern "system" fn(u64);
+
+/// `ARB_bindless_texture` entry points, which the `gl` crate doesn't
+/// generate.
+struct BindlessFunctions {
+    get_texture_handle: GetTextureHandle,
+    make_resident: MakeTextureHandleResident,
+    make_non_resident: MakeTextureHandleResident,
+}
+
+static FUNCTIONS: OnceLock<BindlessFunctions> = OnceLock::new();
+
+/// Loads the bindless texture functions, like `gl::load_with`. Without this
+/// [`supported`] is always false.
+pub fn load_with(mut loader: impl FnMut(&'static str) -> *const c_void) {
+    let get_texture_handle = loader("glGetTextureHandleARB");
+    let make_resident = loader("glMakeTextureHandleResidentARB");
+    let make_non_resident = loader("glMakeTextureHandleNonResidentARB");
+    if [get_texture_handle, make_resident, make_non_resident]
+        .iter()
+        .any(|function| function.is_null())
+    {
+        return;
+    }
+
+    // SAFETY: the pointers are non-null and the signatures are the ones from
+    // the extension spec
+    let _ = FUNCTIONS.set(unsafe {
+        BindlessFunctions {
+            get_texture_handle: std::mem::transmute::<*const c_void, GetTextureHandle>(
+                get_texture_handle,
+            ),
+            make_resident: std::mem::transmute::<*const c_void, MakeTextureHandleResident>(
+                make_resident,
+            ),
+            make_non_resident: std::mem::transmute::<*const c_void, MakeTextureHandleResident>(
+                make_non_resident,
+            ),
+        }
+    });
+}
+
+/// Whether the functions are loaded and the context exposes
+/// `ARB_bindless_texture`.
+pub fn supported() -> bool {
+    FUNCTIONS.get().is_some() && utils::has_extension("GL_ARB_bindless_texture")
+}
+
+/// Handle of a texture made resident, so shaders can sample it without
+/// binding it to a unit. The handle is made non-resident again on drop.
+///
+/// The texture's sampling parameters are frozen once it has a handle, and
+/// it must stay alive as long as the handle does.
+pub struct ResidentHandle<'t> {
+    handle: u64,
+    _texture: PhantomData<&'t ()>,
+}
+
+impl<'t> ResidentHandle<'t> {
+    pub fn new(texture: &'t impl BindTexture) -> Result<Self, GLWError> {
+        let functions = match FUNCTIONS.get() {
+            Some(functions) if supported() => functions,
+            _ => Err(GLWErrorKind::ExtensionNotSupported(
+                "GL_ARB_bindless_texture".to_string(),
+            ))?,
+        };
+        let handle = unsafe {
+            let handle = (functions.get_texture_handle)(texture.id());
+            (functions.make_resident)(handle);
+            handle
+        };
+
+        Ok(Self {
+            handle,
+            _texture: PhantomData,
+        })
+    }
+
+    /// The 64 bit handle, which a `sampler2D` (or `uvec2`) in a uniform or
+    /// storage block is set to.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+}
+
+impl Drop for ResidentHandle<'_> {
+    fn drop(&mut self) {
+        if let Some(functions) = FUNCTIONS.get() {
+            unsafe {
+                (functions.make_non_resident)(self.handle);
+            }
+        }
+    }
+}
+
+/// Storage buffer of `handles`, read in the shader as
+/// `layout(std430) buffer Textures { sampler2D textures[]; };`.
+pub fn handle_buffer(handles: &[ResidentHandle]) -> SsboBuffer<u64> {
+    let handles: Vec<u64> = handles.iter().map(ResidentHandle::handle).collect();
+    SsboBuffer::new(&handles)
+}
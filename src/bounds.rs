@@ -0,0 +1,138 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, MetricSpace, Point3, Vector3};
+
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Point3<f32>, max: Point3<f32>) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest box around `points`, `None` if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = Point3<f32>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, point| {
+            aabb.union(&Self::new(point, point))
+        }))
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    /// Half the size along each axis.
+    pub fn extents(&self) -> Vector3<f32> {
+        (self.max - self.min) / 2.0
+    }
+
+    pub fn contains(&self, point: Point3<f32>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    /// Box around this one after `transform`, e.g. a model matrix to go
+    /// from object to world space.
+    pub fn transformed(&self, transform: &Matrix4<f32>) -> Aabb {
+        let center = transform * self.center().to_homogeneous();
+        let extents = self.extents();
+        // each axis of the new box spans the absolute projections of the
+        // transformed old axes
+        let column = |i: usize| transform[i].truncate().map(f32::abs) * extents[i];
+        let extents = column(0) + column(1) + column(2);
+        let center = Point3::from_homogeneous(center);
+        Aabb::new(center - extents, center + extents)
+    }
+
+    /// Bounding sphere around the box.
+    pub fn bounding_sphere(&self) -> Sphere {
+        Sphere::new(self.center(), self.extents().magnitude())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub fn new(center: Point3<f32>, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Sphere around `points`, `None` if there are none. Found with Ritter's
+    /// algorithm, so it's at most a few percent larger than the smallest.
+    pub fn from_points(points: impl IntoIterator<Item = Point3<f32>>) -> Option<Self> {
+        let points: Vec<_> = points.into_iter().collect();
+        let farthest_from = |from: Point3<f32>| {
+            points
+                .iter()
+                .copied()
+                .max_by(|a, b| a.distance2(from).total_cmp(&b.distance2(from)))
+        };
+        // start from two points roughly farthest apart, then grow the
+        // sphere to reach any left outside
+        let a = farthest_from(*points.first()?)?;
+        let b = farthest_from(a)?;
+        let mut sphere = Sphere::new(a.midpoint(b), a.distance(b) / 2.0);
+        for &point in &points {
+            let distance = point.distance(sphere.center);
+            if distance > sphere.radius {
+                let radius = (sphere.radius + distance) / 2.0;
+                sphere.center += (point - sphere.center) * ((radius - sphere.radius) / distance);
+                sphere.radius = radius;
+            }
+        }
+        Some(sphere)
+    }
+
+    /// Smallest sphere around both.
+    pub fn union(&self, other: &Sphere) -> Sphere {
+        let offset = other.center - self.center;
+        let distance = offset.magnitude();
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+        let radius = (distance + self.radius + other.radius) / 2.0;
+        let center = self.center + offset * ((radius - self.radius) / distance);
+        Sphere::new(center, radius)
+    }
+
+    pub fn contains(&self, point: Point3<f32>) -> bool {
+        point.distance2(self.center) <= self.radius * self.radius
+    }
+
+    /// Sphere around this one after `transform`, scaled by its largest
+    /// axis when the scale isn't uniform.
+    pub fn transformed(&self, transform: &Matrix4<f32>) -> Sphere {
+        let center = Point3::from_homogeneous(transform * self.center.to_homogeneous());
+        let scale = (0..3)
+            .map(|i| transform[i].truncate().magnitude())
+            .fold(0.0, f32::max);
+        Sphere::new(center, self.radius * scale)
+    }
+}
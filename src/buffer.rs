@@ -0,0 +1,383 @@
+use std::marker::PhantomData;
+
+use gl::types::{GLenum, GLintptr, GLsizeiptr};
+
+use cgmath::Matrix4;
+
+use crate::{
+    draw::Primitive,
+    trace,
+    vertex::{Vertex, VertexLayout},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferTarget {
+    Array,
+    ElementArray,
+    Uniform,
+    ShaderStorage,
+    DrawIndirect,
+    /// Destination of pixel reads, e.g. `glReadPixels`.
+    PixelPack,
+    /// Source of texture uploads.
+    PixelUnpack,
+}
+
+impl From<BufferTarget> for u32 {
+    fn from(val: BufferTarget) -> Self {
+        match val {
+            BufferTarget::Array => gl::ARRAY_BUFFER,
+            BufferTarget::ElementArray => gl::ELEMENT_ARRAY_BUFFER,
+            BufferTarget::Uniform => gl::UNIFORM_BUFFER,
+            BufferTarget::ShaderStorage => gl::SHADER_STORAGE_BUFFER,
+            BufferTarget::DrawIndirect => gl::DRAW_INDIRECT_BUFFER,
+            BufferTarget::PixelPack => gl::PIXEL_PACK_BUFFER,
+            BufferTarget::PixelUnpack => gl::PIXEL_UNPACK_BUFFER,
+        }
+    }
+}
+
+/// How often the contents of a buffer are expected to change, a hint for
+/// where the driver places the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage {
+    /// Written once, drawn many times.
+    Static,
+    /// Updated repeatedly, drawn many times.
+    Dynamic,
+    /// Rewritten about every time it is drawn.
+    Stream,
+    /// Written by the GPU and read back once, e.g. pixel readbacks.
+    StreamRead,
+    /// Written and read by the GPU repeatedly, e.g. compute shader storage.
+    DynamicCopy,
+}
+
+impl From<BufferUsage> for u32 {
+    fn from(val: BufferUsage) -> Self {
+        match val {
+            BufferUsage::Static => gl::STATIC_DRAW,
+            BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+            BufferUsage::Stream => gl::STREAM_DRAW,
+            BufferUsage::StreamRead => gl::STREAM_READ,
+            BufferUsage::DynamicCopy => gl::DYNAMIC_COPY,
+        }
+    }
+}
+
+/// GL buffer object holding `len` elements of `T`.
+pub struct Buffer<T> {
+    buffer_id: u32,
+    target: BufferTarget,
+    usage: BufferUsage,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> Buffer<T> {
+    pub fn new(target: BufferTarget, data: &[T], usage: BufferUsage) -> Self {
+        Self::allocate(target, data.len(), data.as_ptr(), usage)
+    }
+
+    /// Buffer of `len` elements with undefined contents.
+    pub fn with_len(target: BufferTarget, len: usize, usage: BufferUsage) -> Self {
+        Self::allocate(target, len, std::ptr::null(), usage)
+    }
+
+    fn allocate(target: BufferTarget, len: usize, data: *const T, usage: BufferUsage) -> Self {
+        let mut buffer_id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut buffer_id);
+            gl::BindBuffer(target.into(), buffer_id);
+            gl::BufferData(
+                target.into(),
+                (len * std::mem::size_of::<T>()) as GLsizeiptr,
+                data.cast(),
+                usage.into(),
+            );
+        }
+
+        trace::event!(TRACE, buffer_id, ?target, len, "created buffer");
+        Self {
+            buffer_id,
+            target,
+            usage,
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Overwrites the elements starting at `offset`.
+    ///
+    /// # Panics
+    /// If the data doesn't fit in the buffer.
+    pub fn update(&mut self, offset: usize, data: &[T]) {
+        assert!(
+            offset + data.len() <= self.len,
+            "buffer update out of range"
+        );
+        self.bind();
+        unsafe {
+            gl::BufferSubData(
+                self.target.into(),
+                (offset * std::mem::size_of::<T>()) as GLintptr,
+                std::mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr().cast(),
+            );
+        }
+    }
+
+    /// Replaces the whole contents, resizing the buffer to `data.len()`.
+    pub fn set_data(&mut self, data: &[T]) {
+        self.bind();
+        unsafe {
+            gl::BufferData(
+                self.target.into(),
+                std::mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr().cast(),
+                self.usage.into(),
+            );
+        }
+        self.len = data.len();
+    }
+}
+
+impl<V: Vertex> Buffer<V> {
+    /// `ARRAY_BUFFER` of vertices, attached to a vertex array with
+    /// [`VertexArray::attach_vertices`](crate::vertex_array::VertexArray::attach_vertices).
+    pub fn vertices(vertices: &[V], usage: BufferUsage) -> Self {
+        Self::new(BufferTarget::Array, vertices, usage)
+    }
+}
+
+impl<T> Buffer<T> {
+    /// Re-specifies the storage without data, so the driver can hand out
+    /// fresh memory instead of waiting for draw calls still reading the old
+    /// contents. The contents are undefined until the next
+    /// [`update`](Self::update).
+    pub fn orphan(&mut self) {
+        self.bind();
+        unsafe {
+            gl::BufferData(
+                self.target.into(),
+                self.size_in_bytes() as GLsizeiptr,
+                std::ptr::null(),
+                self.usage.into(),
+            );
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindBuffer(self.target.into(), self.buffer_id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindBuffer(self.target.into(), 0);
+        }
+    }
+
+    /// Binds the buffer to an indexed binding point of its target, e.g. the
+    /// one a uniform block reads from.
+    pub fn bind_base(&self, binding_point: u32) {
+        unsafe {
+            gl::BindBufferBase(self.target.into(), binding_point, self.buffer_id);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.len * std::mem::size_of::<T>()
+    }
+
+    pub fn target(&self) -> BufferTarget {
+        self.target
+    }
+
+    pub fn usage(&self) -> BufferUsage {
+        self.usage
+    }
+
+    pub fn id(&self) -> u32 {
+        self.buffer_id
+    }
+}
+
+impl<T> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        trace::event!(TRACE, buffer_id = self.buffer_id, "deleting buffer");
+        unsafe {
+            gl::DeleteBuffers(1, &self.buffer_id);
+        }
+    }
+}
+
+/// Integer types usable as vertex indices.
+pub trait Index: Copy + private::Sealed {
+    const GL_TYPE: GLenum;
+}
+
+impl Index for u8 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_BYTE;
+}
+
+impl Index for u16 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_SHORT;
+}
+
+impl Index for u32 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_INT;
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// `ELEMENT_ARRAY_BUFFER` of `u8`, `u16` or `u32` indices. The binding is
+/// recorded by the vertex array bound at the time of [`bind`](Self::bind).
+pub struct IndexBuffer<I> {
+    buffer: Buffer<I>,
+}
+
+impl<I: Index> IndexBuffer<I> {
+    pub fn new(indices: &[I], usage: BufferUsage) -> Self {
+        Self {
+            buffer: Buffer::new(BufferTarget::ElementArray, indices, usage),
+        }
+    }
+
+    pub fn bind(&self) {
+        self.buffer.bind();
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn buffer(&self) -> &Buffer<I> {
+        &self.buffer
+    }
+
+    /// Draws `count` indices starting at index `offset`, using the index
+    /// buffer bound to the current vertex array.
+    ///
+    /// # Panics
+    /// If the range is out of bounds of the buffer.
+    pub fn draw_elements(&self, primitive: Primitive, count: usize, offset: usize) {
+        assert!(offset + count <= self.len(), "index range out of bounds");
+        unsafe {
+            gl::DrawElements(
+                primitive.into(),
+                count as i32,
+                I::GL_TYPE,
+                (offset * std::mem::size_of::<I>()) as *const _,
+            );
+        }
+    }
+
+    /// Draws `instances` instances of the `count` indices starting at index
+    /// `offset`.
+    ///
+    /// # Panics
+    /// If the range is out of bounds of the buffer.
+    pub fn draw_elements_instanced(
+        &self,
+        primitive: Primitive,
+        count: usize,
+        offset: usize,
+        instances: usize,
+    ) {
+        assert!(offset + count <= self.len(), "index range out of bounds");
+        unsafe {
+            gl::DrawElementsInstanced(
+                primitive.into(),
+                count as i32,
+                I::GL_TYPE,
+                (offset * std::mem::size_of::<I>()) as *const _,
+                instances as i32,
+            );
+        }
+    }
+
+    /// Draws every index in the buffer.
+    pub fn draw(&self, primitive: Primitive) {
+        self.draw_elements(primitive, self.len(), 0);
+    }
+}
+
+/// Per-instance vertex data, e.g. a model matrix per instance, attached to a
+/// vertex array with
+/// [`VertexArray::attach_instance_buffer`](crate::vertex_array::VertexArray::attach_instance_buffer).
+pub struct InstanceBuffer<T> {
+    buffer: Buffer<T>,
+    layout: VertexLayout,
+}
+
+impl<T: Copy> InstanceBuffer<T> {
+    /// The attributes of `layout` advance once per instance unless it
+    /// already has a divisor set.
+    pub fn new(data: &[T], layout: VertexLayout, usage: BufferUsage) -> Self {
+        let layout = match layout.divisor() {
+            0 => layout.per_instance(1),
+            _ => layout,
+        };
+        Self {
+            buffer: Buffer::new(BufferTarget::Array, data, usage),
+            layout,
+        }
+    }
+
+    pub fn update(&mut self, offset: usize, data: &[T]) {
+        self.buffer.update(offset, data);
+    }
+
+    pub fn set_data(&mut self, data: &[T]) {
+        self.buffer.set_data(data);
+    }
+}
+
+impl InstanceBuffer<Matrix4<f32>> {
+    /// One matrix per instance, taking the four locations starting at
+    /// `location` (a `mat4` attribute in the shader).
+    pub fn matrices(data: &[Matrix4<f32>], location: u32, usage: BufferUsage) -> Self {
+        let layout = VertexLayout::new()
+            .starting_at(location)
+            .push_field::<Matrix4<f32>>(0, false);
+        Self::new(data, layout, usage)
+    }
+}
+
+impl<T> InstanceBuffer<T> {
+    pub fn buffer(&self) -> &Buffer<T> {
+        &self.buffer
+    }
+
+    pub fn layout(&self) -> &VertexLayout {
+        &self.layout
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
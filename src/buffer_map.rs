@@ -0,0 +1,264 @@
+use std::{
+    mem::MaybeUninit,
+    ops::{BitOr, Deref, DerefMut, Range},
+};
+
+use gl::types::{GLbitfield, GLintptr, GLsizeiptr};
+
+use crate::{
+    buffer::Buffer,
+    errors::{GLWError, GLWErrorKind},
+};
+
+/// Extra `glMapBufferRange` access bits for write-only mappings, combinable
+/// with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapFlags(GLbitfield);
+
+impl MapFlags {
+    pub const NONE: Self = Self(0);
+    /// Don't wait for pending draw calls using the buffer.
+    pub const UNSYNCHRONIZED: Self = Self(gl::MAP_UNSYNCHRONIZED_BIT);
+
+    pub fn bits(self) -> GLbitfield {
+        self.0
+    }
+}
+
+impl BitOr for MapFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Read-only view of a mapped buffer range, unmapped on drop.
+pub struct MappedRead<'b, T> {
+    buffer: &'b Buffer<T>,
+    data: &'b [T],
+}
+
+/// Writable view of a mapped buffer range, unmapped on drop.
+pub struct MappedWrite<'b, T> {
+    buffer: &'b Buffer<T>,
+    data: &'b mut [T],
+}
+
+/// Write-only view of a mapped buffer range whose previous contents were
+/// discarded, unmapped on drop. Elements are uninitialized until written.
+pub struct MappedUninit<'b, T> {
+    buffer: &'b Buffer<T>,
+    data: &'b mut [MaybeUninit<T>],
+}
+
+impl<T: Copy> Buffer<T> {
+    pub fn map_read(&self) -> Result<MappedRead<'_, T>, GLWError> {
+        self.map_range_read(0..self.len())
+    }
+
+    pub fn map_range_read(&self, range: Range<usize>) -> Result<MappedRead<'_, T>, GLWError> {
+        let len = range.len();
+        let ptr = self.map_range(range, gl::MAP_READ_BIT)?;
+        Ok(MappedRead {
+            buffer: self,
+            // SAFETY: the mapping stays valid until the guard unmaps it
+            data: unsafe { std::slice::from_raw_parts(ptr, len) },
+        })
+    }
+
+    pub fn map_write(&mut self) -> Result<MappedWrite<'_, T>, GLWError> {
+        self.map_range_write(0..self.len())
+    }
+
+    /// Maps `range` for reading and writing, so the view starts out with
+    /// the current contents. Reading rules out
+    /// [`UNSYNCHRONIZED`](MapFlags::UNSYNCHRONIZED), which
+    /// [`map_range_invalidate`](Self::map_range_invalidate) takes.
+    pub fn map_range_write(&mut self, range: Range<usize>) -> Result<MappedWrite<'_, T>, GLWError> {
+        let len = range.len();
+        let ptr = self.map_range(range, gl::MAP_READ_BIT | gl::MAP_WRITE_BIT)?;
+        Ok(MappedWrite {
+            buffer: self,
+            // SAFETY: the mapping stays valid until the guard unmaps it and
+            // the guard borrows the buffer mutably
+            data: unsafe { std::slice::from_raw_parts_mut(ptr, len) },
+        })
+    }
+
+    /// Maps the whole buffer for writing only, discarding its contents so
+    /// the driver doesn't have to preserve them.
+    pub fn map_invalidate(&mut self) -> Result<MappedUninit<'_, T>, GLWError> {
+        self.map_uninit(0..self.len(), gl::MAP_INVALIDATE_BUFFER_BIT)
+    }
+
+    /// Like [`map_invalidate`](Self::map_invalidate), for `range`.
+    pub fn map_range_invalidate(
+        &mut self,
+        range: Range<usize>,
+        flags: MapFlags,
+    ) -> Result<MappedUninit<'_, T>, GLWError> {
+        self.map_uninit(range, gl::MAP_INVALIDATE_RANGE_BIT | flags.bits())
+    }
+
+    /// Overwrites the elements starting at `offset` through a mapping that
+    /// discards them first, the mapped counterpart of
+    /// [`update`](Self::update).
+    pub fn write(&self, offset: usize, data: &[T]) -> Result<(), GLWError> {
+        let end = offset
+            .checked_add(data.len())
+            .filter(|&end| end <= self.len())
+            .ok_or(GLWErrorKind::BufferMapFailed)?;
+        // mapping nothing is an error in GL
+        if data.is_empty() {
+            return Ok(());
+        }
+        let range = offset..end;
+        let ptr = self.map_range(range, gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_RANGE_BIT)?;
+        // SAFETY: the mapping holds `data.len()` elements until unmapped
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len()) };
+        unmap(self)
+    }
+
+    fn map_uninit(
+        &mut self,
+        range: Range<usize>,
+        access: GLbitfield,
+    ) -> Result<MappedUninit<'_, T>, GLWError> {
+        let len = range.len();
+        let ptr = self.map_range(range, gl::MAP_WRITE_BIT | access)?;
+        Ok(MappedUninit {
+            buffer: self,
+            // SAFETY: as for `map_range_write`, and `MaybeUninit` makes no
+            // claim about the discarded contents
+            data: unsafe { std::slice::from_raw_parts_mut(ptr.cast(), len) },
+        })
+    }
+
+    fn map_range(&self, range: Range<usize>, access: GLbitfield) -> Result<*mut T, GLWError> {
+        if range.start > range.end || range.end > self.len() {
+            Err(GLWErrorKind::BufferMapFailed)?;
+        }
+
+        self.bind();
+        let ptr = unsafe {
+            gl::MapBufferRange(
+                self.target().into(),
+                (range.start * std::mem::size_of::<T>()) as GLintptr,
+                (range.len() * std::mem::size_of::<T>()) as GLsizeiptr,
+                access,
+            ) as *mut T
+        };
+        if ptr.is_null() {
+            Err(GLWErrorKind::BufferMapFailed)?;
+        }
+        Ok(ptr)
+    }
+}
+
+/// Unmaps `buffer`, failing if its contents got corrupted while mapped.
+fn unmap<T>(buffer: &Buffer<T>) -> Result<(), GLWError> {
+    buffer.bind();
+    let unmapped = unsafe { gl::UnmapBuffer(buffer.target().into()) };
+    if unmapped != gl::TRUE {
+        Err(GLWErrorKind::BufferMapFailed)?;
+    }
+    Ok(())
+}
+
+impl<T> MappedRead<'_, T> {
+    /// Unmaps the buffer. Unlike dropping the guard, this reports whether the
+    /// data read through it can be trusted.
+    pub fn unmap(self) -> Result<(), GLWError> {
+        let buffer = self.buffer;
+        std::mem::forget(self);
+        unmap(buffer)
+    }
+}
+
+impl<T> MappedWrite<'_, T> {
+    /// Unmaps the buffer, failing if the written data got lost and has to be
+    /// written again.
+    pub fn unmap(self) -> Result<(), GLWError> {
+        let buffer = self.buffer;
+        std::mem::forget(self);
+        unmap(buffer)
+    }
+}
+
+impl<T: Copy> MappedUninit<'_, T> {
+    /// Writes `data` over the whole view.
+    ///
+    /// # Panics
+    /// If `data` isn't as long as the view.
+    pub fn copy_from_slice(&mut self, data: &[T]) {
+        assert_eq!(data.len(), self.data.len(), "mapped range length mismatch");
+        for (element, value) in self.data.iter_mut().zip(data) {
+            element.write(*value);
+        }
+    }
+}
+
+impl<T> MappedUninit<'_, T> {
+    /// Unmaps the buffer, failing if the written data got lost and has to be
+    /// written again.
+    pub fn unmap(self) -> Result<(), GLWError> {
+        let buffer = self.buffer;
+        std::mem::forget(self);
+        unmap(buffer)
+    }
+}
+
+impl<T> Deref for MappedRead<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<T> Deref for MappedWrite<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<T> DerefMut for MappedWrite<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.data
+    }
+}
+
+impl<T> Deref for MappedUninit<'_, T> {
+    type Target = [MaybeUninit<T>];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<T> DerefMut for MappedUninit<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.data
+    }
+}
+
+impl<T> Drop for MappedRead<'_, T> {
+    fn drop(&mut self) {
+        let _ = unmap(self.buffer);
+    }
+}
+
+impl<T> Drop for MappedWrite<'_, T> {
+    fn drop(&mut self) {
+        let _ = unmap(self.buffer);
+    }
+}
+
+impl<T> Drop for MappedUninit<'_, T> {
+    fn drop(&mut self) {
+        let _ = unmap(self.buffer);
+    }
+}
@@ -0,0 +1,314 @@
+use cgmath::{Deg, InnerSpace, Matrix4, Point3, Vector3};
+use glfw::{Key, MouseButton};
+
+use crate::{context::DepthMode, input::Input, projection, ray::Ray};
+
+/// First-person camera flying with WASD (Space and Left Shift for up and
+/// down), looking around with the mouse and zooming with the scroll wheel.
+/// Mouse look wants a [captured](crate::app::AppContext::capture_cursor)
+/// cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FpsCamera {
+    pub position: Point3<f32>,
+    /// Degrees, 0 facing `+X` and -90 facing `-Z`.
+    yaw: f32,
+    /// Degrees, kept within ±89 so the view never flips.
+    pitch: f32,
+    /// Vertical field of view in degrees, within `fov_range`.
+    pub fov: f32,
+    pub fov_range: (f32, f32),
+    /// Units per second.
+    pub speed: f32,
+    /// Degrees per screen coordinate of mouse movement.
+    pub sensitivity: f32,
+    pub near: f32,
+    pub far: f32,
+    /// Depth mapping the projection is made for.
+    pub depth_mode: DepthMode,
+    pub world_up: Vector3<f32>,
+}
+
+impl FpsCamera {
+    const MAX_PITCH: f32 = 89.0;
+
+    /// Camera at `position` facing `-Z`.
+    pub fn new(position: Point3<f32>) -> Self {
+        Self {
+            position,
+            yaw: -90.0,
+            pitch: 0.0,
+            fov: 45.0,
+            fov_range: (1.0, 45.0),
+            speed: 2.5,
+            sensitivity: 0.1,
+            near: 0.1,
+            far: 100.0,
+            depth_mode: DepthMode::Standard,
+            world_up: Vector3::unit_y(),
+        }
+    }
+
+    /// Moves and turns from the frame's input.
+    pub fn update(&mut self, input: &Input, dt: f32) {
+        let step = self.speed * dt;
+        let (front, right) = (self.front(), self.right());
+        let moves = [
+            (Key::W, front),
+            (Key::S, -front),
+            (Key::D, right),
+            (Key::A, -right),
+            (Key::Space, self.world_up),
+            (Key::LeftShift, -self.world_up),
+        ];
+        for (key, direction) in moves {
+            if input.is_pressed(key) {
+                self.position += direction * step;
+            }
+        }
+
+        let (dx, dy) = input.mouse_delta();
+        self.turn(dx as f32 * self.sensitivity, -dy as f32 * self.sensitivity);
+
+        let (_, scroll) = input.scroll_delta();
+        self.zoom(scroll as f32);
+    }
+
+    /// Turns by `yaw` and `pitch` degrees, clamping the pitch.
+    pub fn turn(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw) % 360.0;
+        self.pitch = (self.pitch + pitch).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// Narrows the field of view by `degrees`, widening it when negative.
+    pub fn zoom(&mut self, degrees: f32) {
+        self.fov = (self.fov - degrees).clamp(self.fov_range.0, self.fov_range.1);
+    }
+
+    /// Turns towards `target`.
+    pub fn look_at(&mut self, target: Point3<f32>) {
+        let direction = (target - self.position).normalize();
+        self.yaw = direction.z.atan2(direction.x).to_degrees();
+        self.pitch = direction
+            .y
+            .asin()
+            .to_degrees()
+            .clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    pub fn front(&self) -> Vector3<f32> {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        Vector3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        )
+        .normalize()
+    }
+
+    pub fn right(&self) -> Vector3<f32> {
+        self.front().cross(self.world_up).normalize()
+    }
+
+    pub fn up(&self) -> Vector3<f32> {
+        self.right().cross(self.front())
+    }
+
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at_dir(self.position, self.front(), self.world_up)
+    }
+
+    /// Perspective projection for a viewport of `aspect` width over height.
+    pub fn projection_matrix(&self, aspect: f32) -> Matrix4<f32> {
+        match self.depth_mode {
+            DepthMode::Standard => {
+                projection::perspective(Deg(self.fov), aspect, self.near, self.far)
+            }
+            DepthMode::ReverseZ => {
+                projection::perspective_reverse_z(Deg(self.fov), aspect, self.near, self.far)
+            }
+        }
+    }
+
+    /// World space ray through `mouse_pos` on a `viewport` sized window,
+    /// e.g. [`Input::cursor_position`] and the window size, to pick objects.
+    pub fn screen_to_ray(&self, mouse_pos: (f64, f64), viewport: (f64, f64)) -> Ray {
+        let projection = self.projection_matrix((viewport.0 / viewport.1) as f32);
+        Ray::from_screen_with_depth(
+            mouse_pos,
+            viewport,
+            &self.view_matrix(),
+            &projection,
+            self.depth_mode,
+        )
+        .unwrap_or_else(|| Ray::new(self.position, self.front()))
+    }
+}
+
+impl Default for FpsCamera {
+    fn default() -> Self {
+        Self::new(Point3::new(0.0, 0.0, 3.0))
+    }
+}
+
+/// Camera orbiting `target`, rotating with left drags, panning with middle
+/// drags and zooming with the scroll wheel. Suits inspecting models better
+/// than [`FpsCamera`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Point3<f32>,
+    distance: f32,
+    /// Degrees around the target, 90 looking from `+Z`.
+    yaw: f32,
+    /// Degrees above the target, within ±89.
+    pitch: f32,
+    pub distance_range: (f32, f32),
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+    /// Degrees per screen coordinate dragged.
+    pub rotate_sensitivity: f32,
+    /// Fraction of the distance panned per screen coordinate dragged, so
+    /// panning keeps pace with the view when zoomed out.
+    pub pan_sensitivity: f32,
+    /// Fraction of the distance zoomed per scroll step.
+    pub zoom_step: f32,
+    pub near: f32,
+    pub far: f32,
+    /// Depth mapping the projection is made for.
+    pub depth_mode: DepthMode,
+}
+
+impl OrbitCamera {
+    const MAX_PITCH: f32 = 89.0;
+
+    /// Camera `distance` in front of `target`, along `+Z`.
+    pub fn new(target: Point3<f32>, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            yaw: 90.0,
+            pitch: 0.0,
+            distance_range: (0.1, 1000.0),
+            fov: 45.0,
+            rotate_sensitivity: 0.3,
+            pan_sensitivity: 0.002,
+            zoom_step: 0.1,
+            near: 0.1,
+            far: 1000.0,
+            depth_mode: DepthMode::Standard,
+        }
+    }
+
+    /// Rotates, pans and zooms from the frame's input.
+    pub fn update(&mut self, input: &Input) {
+        let (dx, dy) = input.mouse_delta();
+        let (dx, dy) = (dx as f32, dy as f32);
+        if input.is_button_pressed(MouseButton::Button1) {
+            self.rotate(dx * self.rotate_sensitivity, dy * self.rotate_sensitivity);
+        }
+        if input.is_button_pressed(MouseButton::Button3) {
+            let scale = self.pan_sensitivity * self.distance;
+            self.pan(-dx * scale, dy * scale);
+        }
+        let (_, scroll) = input.scroll_delta();
+        self.zoom(scroll as f32);
+    }
+
+    /// Orbits by `yaw` and `pitch` degrees, clamping the pitch.
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw) % 360.0;
+        self.pitch = (self.pitch + pitch).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// Moves the target by `right` and `up` along the view plane.
+    pub fn pan(&mut self, right: f32, up: f32) {
+        self.target += self.right() * right + self.up() * up;
+    }
+
+    /// Moves closer by `steps` of [`zoom_step`](Self::zoom_step), away when
+    /// negative.
+    pub fn zoom(&mut self, steps: f32) {
+        self.set_distance(self.distance * (1.0 - self.zoom_step).powf(steps));
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.clamp(self.distance_range.0, self.distance_range.1);
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    pub fn position(&self) -> Point3<f32> {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        let offset = Vector3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        );
+        self.target + offset * self.distance
+    }
+
+    pub fn front(&self) -> Vector3<f32> {
+        (self.target - self.position()).normalize()
+    }
+
+    pub fn right(&self) -> Vector3<f32> {
+        self.front().cross(Vector3::unit_y()).normalize()
+    }
+
+    pub fn up(&self) -> Vector3<f32> {
+        self.right().cross(self.front())
+    }
+
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at(self.position(), self.target, Vector3::unit_y())
+    }
+
+    /// Perspective projection for a viewport of `aspect` width over height.
+    pub fn projection_matrix(&self, aspect: f32) -> Matrix4<f32> {
+        match self.depth_mode {
+            DepthMode::Standard => {
+                projection::perspective(Deg(self.fov), aspect, self.near, self.far)
+            }
+            DepthMode::ReverseZ => {
+                projection::perspective_reverse_z(Deg(self.fov), aspect, self.near, self.far)
+            }
+        }
+    }
+
+    /// World space ray through `mouse_pos` on a `viewport` sized window,
+    /// e.g. [`Input::cursor_position`] and the window size, to pick objects.
+    pub fn screen_to_ray(&self, mouse_pos: (f64, f64), viewport: (f64, f64)) -> Ray {
+        let projection = self.projection_matrix((viewport.0 / viewport.1) as f32);
+        Ray::from_screen_with_depth(
+            mouse_pos,
+            viewport,
+            &self.view_matrix(),
+            &projection,
+            self.depth_mode,
+        )
+        .unwrap_or_else(|| Ray::new(self.position(), self.front()))
+    }
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self::new(Point3::new(0.0, 0.0, 0.0), 5.0)
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use image::ColorType;
+
+use crate::{
+    errors::{GLWError, GLWErrorExt},
+    framebuffer::{self, Framebuffer},
+};
+
+/// Saves the back buffer of the default framebuffer, the size of the
+/// current viewport, as a PNG. Call it after rendering and before swapping
+/// buffers.
+pub fn screenshot(path: impl AsRef<Path>) -> Result<(), GLWError> {
+    let mut viewport = [0; 4];
+    unsafe {
+        gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+    }
+    let (width, height) = (viewport[2] as u32, viewport[3] as u32);
+    let pixels = framebuffer::read_rgba(0, gl::BACK, width, height);
+
+    // the window's alpha channel is meaningless, drop it
+    let rgb: Vec<u8> = pixels
+        .chunks_exact(4)
+        .flat_map(|pixel| &pixel[..3])
+        .copied()
+        .collect();
+    save_png(path.as_ref(), &rgb, width, height, ColorType::RGB(8))
+}
+
+/// Saves color attachment `index` of `framebuffer` as an RGBA PNG.
+pub fn screenshot_framebuffer(
+    framebuffer: &Framebuffer,
+    index: u32,
+    path: impl AsRef<Path>,
+) -> Result<(), GLWError> {
+    let pixels = framebuffer.read_pixels(index);
+    save_png(
+        path.as_ref(),
+        &pixels,
+        framebuffer.width(),
+        framebuffer.height(),
+        ColorType::RGBA(8),
+    )
+}
+
+/// Writes tightly packed, bottom row first `pixels` top row first, as
+/// images are stored.
+fn save_png(
+    path: &Path,
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    color: ColorType,
+) -> Result<(), GLWError> {
+    let row_len = pixels.len() / height.max(1) as usize;
+    let flipped: Vec<u8> = pixels
+        .chunks_exact(row_len.max(1))
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+    image::save_buffer(path, &flipped, width, height, color).info(format!("{}", path.display()))
+}
@@ -0,0 +1,512 @@
+use std::{fs, path::Path};
+
+use crate::{
+    errors::{GLWError, GLWErrorExt, GLWErrorKind},
+    texture::{Texture2D, TextureFormat, TextureParams},
+    utils,
+};
+
+// S3TC isn't part of core GL, the values come from EXT_texture_compression_s3tc
+// and EXT_texture_sRGB
+const COMPRESSED_RGB_S3TC_DXT1: u32 = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1: u32 = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: u32 = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: u32 = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1: u32 = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: u32 = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: u32 = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: u32 = 0x8C4F;
+
+/// Block compressed formats that can be uploaded with
+/// `glCompressedTexImage2D`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressedFormat {
+    Bc1Rgb,
+    Bc1Rgba,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc4Signed,
+    Bc5,
+    Bc5Signed,
+    Bc6hUfloat,
+    Bc6hSfloat,
+    Bc7,
+    Etc2Rgb8,
+    Etc2Rgb8A1,
+    Etc2Rgba8,
+    EacR11,
+    EacR11Signed,
+    EacRg11,
+    EacRg11Signed,
+}
+
+impl From<CompressedFormat> for u32 {
+    fn from(val: CompressedFormat) -> Self {
+        match val {
+            CompressedFormat::Bc1Rgb => COMPRESSED_RGB_S3TC_DXT1,
+            CompressedFormat::Bc1Rgba => COMPRESSED_RGBA_S3TC_DXT1,
+            CompressedFormat::Bc2 => COMPRESSED_RGBA_S3TC_DXT3,
+            CompressedFormat::Bc3 => COMPRESSED_RGBA_S3TC_DXT5,
+            CompressedFormat::Bc4 => gl::COMPRESSED_RED_RGTC1,
+            CompressedFormat::Bc4Signed => gl::COMPRESSED_SIGNED_RED_RGTC1,
+            CompressedFormat::Bc5 => gl::COMPRESSED_RG_RGTC2,
+            CompressedFormat::Bc5Signed => gl::COMPRESSED_SIGNED_RG_RGTC2,
+            CompressedFormat::Bc6hUfloat => gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            CompressedFormat::Bc6hSfloat => gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+            CompressedFormat::Bc7 => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            CompressedFormat::Etc2Rgb8 => gl::COMPRESSED_RGB8_ETC2,
+            CompressedFormat::Etc2Rgb8A1 => gl::COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            CompressedFormat::Etc2Rgba8 => gl::COMPRESSED_RGBA8_ETC2_EAC,
+            CompressedFormat::EacR11 => gl::COMPRESSED_R11_EAC,
+            CompressedFormat::EacR11Signed => gl::COMPRESSED_SIGNED_R11_EAC,
+            CompressedFormat::EacRg11 => gl::COMPRESSED_RG11_EAC,
+            CompressedFormat::EacRg11Signed => gl::COMPRESSED_SIGNED_RG11_EAC,
+        }
+    }
+}
+
+impl CompressedFormat {
+    /// Whether the format can hold sRGB encoded color.
+    pub fn has_srgb_variant(self) -> bool {
+        matches!(
+            self,
+            Self::Bc1Rgb
+                | Self::Bc1Rgba
+                | Self::Bc2
+                | Self::Bc3
+                | Self::Bc7
+                | Self::Etc2Rgb8
+                | Self::Etc2Rgb8A1
+                | Self::Etc2Rgba8
+        )
+    }
+
+    /// GL internal format, the sRGB variant if `srgb` is set and there is
+    /// one.
+    pub fn internal_format(self, srgb: bool) -> u32 {
+        if !srgb {
+            return self.into();
+        }
+        match self {
+            Self::Bc1Rgb => COMPRESSED_SRGB_S3TC_DXT1,
+            Self::Bc1Rgba => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            Self::Bc2 => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+            Self::Bc3 => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            Self::Bc7 => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+            Self::Etc2Rgb8 => gl::COMPRESSED_SRGB8_ETC2,
+            Self::Etc2Rgb8A1 => gl::COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            Self::Etc2Rgba8 => gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+            _ => self.into(),
+        }
+    }
+
+    /// Size in bytes of one 4x4 block.
+    pub fn block_size(self) -> usize {
+        match self {
+            Self::Bc1Rgb
+            | Self::Bc1Rgba
+            | Self::Bc4
+            | Self::Bc4Signed
+            | Self::Etc2Rgb8
+            | Self::Etc2Rgb8A1
+            | Self::EacR11
+            | Self::EacR11Signed => 8,
+            _ => 16,
+        }
+    }
+
+    /// Whether the context can sample the format, according to
+    /// `GL_COMPRESSED_TEXTURE_FORMATS`. S3TC formats are accepted with the
+    /// extension alone, which some drivers don't list there.
+    pub fn supported(self) -> bool {
+        let internal_format = u32::from(self);
+        let s3tc = matches!(self, Self::Bc1Rgb | Self::Bc1Rgba | Self::Bc2 | Self::Bc3);
+        if s3tc && utils::has_extension("GL_EXT_texture_compression_s3tc") {
+            return true;
+        }
+
+        let mut count = 0;
+        unsafe {
+            gl::GetIntegerv(gl::NUM_COMPRESSED_TEXTURE_FORMATS, &mut count);
+        }
+        let mut formats = vec![0; count as usize];
+        unsafe {
+            gl::GetIntegerv(gl::COMPRESSED_TEXTURE_FORMATS, formats.as_mut_ptr());
+        }
+        formats.contains(&(internal_format as i32))
+    }
+
+    fn level_size(self, width: u32, height: u32) -> usize {
+        width.div_ceil(4).max(1) as usize * height.div_ceil(4).max(1) as usize * self.block_size()
+    }
+}
+
+/// Pre-compressed image with its mipmap chain, as stored in a DDS or KTX2
+/// file.
+pub struct CompressedImage {
+    pub format: CompressedFormat,
+    /// Whether the file tags the data as sRGB encoded. Files without a DX10
+    /// header can't, and are treated as linear.
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    /// Level 0 first, each level half the size of the previous one.
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    /// Loads a `.dds` or `.ktx2` file, picked by extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        let path = path.as_ref();
+        let data = fs::read(path).info(format!("{}", path.display()))?;
+        let image = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("dds") => Self::parse_dds(&data),
+            Some(ext) if ext.eq_ignore_ascii_case("ktx2") => Self::parse_ktx2(&data),
+            _ => Err(GLWErrorKind::UnsupportedTextureFormat(format!(
+                "{}",
+                path.display()
+            )))?,
+        };
+        image.info(format!("{}", path.display()))
+    }
+
+    /// Parses a DDS file with BC1-BC7 data, either with a legacy `DXTn`/`ATIn`
+    /// four character code or a DX10 header.
+    ///
+    /// DDS stores rows top to bottom, so textures come out upside down
+    /// compared to [`Texture2D::from_path`] and need flipped texture
+    /// coordinates.
+    pub fn parse_dds(data: &[u8]) -> Result<Self, GLWError> {
+        if data.get(..4) != Some(b"DDS ") {
+            Err(invalid("missing DDS magic"))?;
+        }
+        let height = read_u32(data, 12)?;
+        let width = read_u32(data, 16)?;
+        let mip_count = read_u32(data, 28)?.clamp(1, max_levels(width, height));
+        let four_cc = data
+            .get(84..88)
+            .ok_or_else(|| invalid("truncated header"))?;
+
+        let mut srgb = false;
+        let (format, offset) = match std::str::from_utf8(four_cc).unwrap_or_default() {
+            "DX10" => {
+                let dxgi_format = read_u32(data, 128)?;
+                srgb = matches!(dxgi_format, 72 | 75 | 78 | 99);
+                let format = match dxgi_format {
+                    71 | 72 => CompressedFormat::Bc1Rgba,
+                    74 | 75 => CompressedFormat::Bc2,
+                    77 | 78 => CompressedFormat::Bc3,
+                    80 => CompressedFormat::Bc4,
+                    81 => CompressedFormat::Bc4Signed,
+                    83 => CompressedFormat::Bc5,
+                    84 => CompressedFormat::Bc5Signed,
+                    95 => CompressedFormat::Bc6hUfloat,
+                    96 => CompressedFormat::Bc6hSfloat,
+                    98 | 99 => CompressedFormat::Bc7,
+                    dxgi => Err(unsupported(format!("DXGI format {dxgi}")))?,
+                };
+                (format, 148)
+            }
+            "DXT1" => (CompressedFormat::Bc1Rgba, 128),
+            "DXT3" => (CompressedFormat::Bc2, 128),
+            "DXT5" => (CompressedFormat::Bc3, 128),
+            "ATI1" | "BC4U" => (CompressedFormat::Bc4, 128),
+            "BC4S" => (CompressedFormat::Bc4Signed, 128),
+            "ATI2" | "BC5U" => (CompressedFormat::Bc5, 128),
+            "BC5S" => (CompressedFormat::Bc5Signed, 128),
+            other => Err(unsupported(format!("DDS four character code {other:?}")))?,
+        };
+
+        let mut levels = vec![];
+        let mut offset: usize = offset;
+        for level in 0..mip_count {
+            let size = format.level_size(width >> level, height >> level);
+            let bytes = offset
+                .checked_add(size)
+                .and_then(|end| data.get(offset..end))
+                .ok_or_else(|| invalid("truncated image data"))?;
+            levels.push(bytes.to_vec());
+            offset += size;
+        }
+
+        Ok(Self {
+            format,
+            srgb,
+            width,
+            height,
+            levels,
+        })
+    }
+
+    /// Parses a KTX2 file holding a single 2D image with BCn, ETC2 or EAC
+    /// data. Supercompressed (Basis Universal) files aren't supported.
+    pub fn parse_ktx2(data: &[u8]) -> Result<Self, GLWError> {
+        const IDENTIFIER: &[u8; 12] = b"\xABKTX 20\xBB\r\n\x1A\n";
+        if data.get(..12) != Some(IDENTIFIER) {
+            Err(invalid("missing KTX2 identifier"))?;
+        }
+
+        let vk_format = read_u32(data, 12)?;
+        let width = read_u32(data, 20)?;
+        let height = read_u32(data, 24)?;
+        let depth = read_u32(data, 28)?;
+        let layers = read_u32(data, 32)?;
+        let faces = read_u32(data, 36)?;
+        let level_count = read_u32(data, 40)?.clamp(1, max_levels(width, height));
+        let supercompression = read_u32(data, 44)?;
+        if depth > 1 || layers > 1 || faces != 1 {
+            Err(unsupported("only single 2D images are supported".into()))?;
+        }
+        if supercompression != 0 {
+            Err(unsupported(format!(
+                "supercompression scheme {supercompression}"
+            )))?;
+        }
+
+        let format = match vk_format {
+            131 | 132 => CompressedFormat::Bc1Rgb,
+            133 | 134 => CompressedFormat::Bc1Rgba,
+            135 | 136 => CompressedFormat::Bc2,
+            137 | 138 => CompressedFormat::Bc3,
+            139 => CompressedFormat::Bc4,
+            140 => CompressedFormat::Bc4Signed,
+            141 => CompressedFormat::Bc5,
+            142 => CompressedFormat::Bc5Signed,
+            143 => CompressedFormat::Bc6hUfloat,
+            144 => CompressedFormat::Bc6hSfloat,
+            145 | 146 => CompressedFormat::Bc7,
+            147 | 148 => CompressedFormat::Etc2Rgb8,
+            149 | 150 => CompressedFormat::Etc2Rgb8A1,
+            151 | 152 => CompressedFormat::Etc2Rgba8,
+            153 => CompressedFormat::EacR11,
+            154 => CompressedFormat::EacR11Signed,
+            155 => CompressedFormat::EacRg11,
+            156 => CompressedFormat::EacRg11Signed,
+            other => Err(unsupported(format!("VkFormat {other}")))?,
+        };
+        // the sRGB variants of the BC1-3, BC7 and ETC2 formats are the even
+        // ones
+        let srgb = format.has_srgb_variant() && vk_format % 2 == 0;
+
+        // the level index follows the 80 byte header, 24 bytes per level
+        let levels = (0..level_count as usize)
+            .map(|level| {
+                let entry = 80 + level * 24;
+                let offset = read_u64(data, entry)?;
+                let end = offset
+                    .checked_add(read_u64(data, entry + 8)?)
+                    .ok_or_else(|| invalid("truncated image data"))?;
+                usize::try_from(offset)
+                    .ok()
+                    .zip(usize::try_from(end).ok())
+                    .and_then(|(offset, end)| data.get(offset..end))
+                    .map(<[u8]>::to_vec)
+                    .ok_or_else(|| invalid("truncated image data"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            format,
+            srgb,
+            width,
+            height,
+            levels,
+        })
+    }
+}
+
+/// Length of a full mipmap chain of a `width` x `height` image.
+fn max_levels(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, GLWError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| invalid("truncated header"))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, GLWError> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| invalid("truncated header"))
+}
+
+fn invalid(reason: &str) -> GLWError {
+    GLWErrorKind::InvalidTextureFile(reason.to_string()).into()
+}
+
+fn unsupported(reason: String) -> GLWError {
+    GLWErrorKind::UnsupportedTextureFormat(reason).into()
+}
+
+impl Texture2D {
+    /// Loads a compressed `.dds` or `.ktx2` file, see [`CompressedImage`].
+    pub fn from_compressed_path(
+        path: impl AsRef<Path>,
+        params: &TextureParams,
+    ) -> Result<Self, GLWError> {
+        Self::from_compressed(&CompressedImage::load(path)?, params)
+    }
+
+    /// Uploads every level of `image`. Compressed data can't be mipmapped by
+    /// the driver, `params.mipmaps` only enables the levels in the file.
+    /// Fails with [`InvalidTextureFile`](GLWErrorKind::InvalidTextureFile)
+    /// for more levels than the image size has.
+    pub fn from_compressed(
+        image: &CompressedImage,
+        params: &TextureParams,
+    ) -> Result<Self, GLWError> {
+        if image.levels.len() > max_levels(image.width, image.height) as usize {
+            Err(invalid("more mipmap levels than the image size has"))?;
+        }
+        if !image.format.supported() {
+            Err(unsupported(format!(
+                "{:?} is not supported by the context",
+                image.format
+            )))?;
+        }
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+            for (level, data) in (0..).zip(&image.levels) {
+                gl::CompressedTexImage2D(
+                    gl::TEXTURE_2D,
+                    level,
+                    image.format.internal_format(image.srgb),
+                    (image.width >> level).max(1) as i32,
+                    (image.height >> level).max(1) as i32,
+                    0,
+                    data.len() as i32,
+                    data.as_ptr().cast(),
+                );
+            }
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_MAX_LEVEL,
+                image.levels.len() as i32 - 1,
+            );
+        }
+
+        let mipmaps = params.mipmaps && image.levels.len() > 1;
+        TextureParams {
+            mipmaps: false,
+            ..*params
+        }
+        .apply(gl::TEXTURE_2D);
+        if mipmaps {
+            unsafe {
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_MIN_FILTER,
+                    params.min_filter.into(),
+                );
+            }
+        }
+
+        Ok(Self {
+            texture_id,
+            width: image.width,
+            height: image.height,
+            format: TextureFormat::Compressed {
+                format: image.format,
+                srgb: image.srgb,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dds(width: u32, height: u32, mip_count: u32, four_cc: &[u8; 4], data_len: usize) -> Vec<u8> {
+        let mut file = vec![0; 128];
+        file[..4].copy_from_slice(b"DDS ");
+        file[12..16].copy_from_slice(&height.to_le_bytes());
+        file[16..20].copy_from_slice(&width.to_le_bytes());
+        file[28..32].copy_from_slice(&mip_count.to_le_bytes());
+        file[84..88].copy_from_slice(four_cc);
+        file.resize(128 + data_len, 0xAB);
+        file
+    }
+
+    fn ktx2(vk_format: u32, width: u32, height: u32, levels: &[(u64, u64)]) -> Vec<u8> {
+        let mut file = vec![0; 80 + levels.len() * 24];
+        file[..12].copy_from_slice(b"\xABKTX 20\xBB\r\n\x1A\n");
+        file[12..16].copy_from_slice(&vk_format.to_le_bytes());
+        file[20..24].copy_from_slice(&width.to_le_bytes());
+        file[24..28].copy_from_slice(&height.to_le_bytes());
+        file[36..40].copy_from_slice(&1u32.to_le_bytes());
+        file[40..44].copy_from_slice(&(levels.len() as u32).to_le_bytes());
+        for (level, &(offset, size)) in levels.iter().enumerate() {
+            let entry = 80 + level * 24;
+            file[entry..entry + 8].copy_from_slice(&offset.to_le_bytes());
+            file[entry + 8..entry + 16].copy_from_slice(&size.to_le_bytes());
+        }
+        file
+    }
+
+    #[test]
+    fn parses_dds_mip_chains() {
+        // 8x8, 4x4, 2x2 and 1x1 levels, the last two padded to a block each
+        let image = CompressedImage::parse_dds(&dds(8, 8, 4, b"DXT5", 64 + 3 * 16)).unwrap();
+        assert_eq!(image.format, CompressedFormat::Bc3);
+        assert!(!image.srgb);
+        assert_eq!((image.width, image.height), (8, 8));
+        let sizes: Vec<usize> = image.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [64, 16, 16, 16]);
+    }
+
+    #[test]
+    fn parses_dds_dx10_headers() {
+        let mut file = dds(4, 4, 1, b"DX10", 20 + 16);
+        file[128..132].copy_from_slice(&99u32.to_le_bytes());
+        let image = CompressedImage::parse_dds(&file).unwrap();
+        assert_eq!(image.format, CompressedFormat::Bc7);
+        assert!(image.srgb);
+        assert_eq!(image.levels, [vec![0xAB; 16]]);
+    }
+
+    #[test]
+    fn clamps_dds_mip_counts_to_the_image_size() {
+        let image = CompressedImage::parse_dds(&dds(4, 4, 40, b"DXT1", 3 * 8)).unwrap();
+        assert_eq!(image.levels.len(), 3);
+    }
+
+    #[test]
+    fn rejects_malformed_dds_files() {
+        assert!(CompressedImage::parse_dds(b"DDS").is_err());
+        assert!(CompressedImage::parse_dds(&dds(4, 4, 1, b"XXXX", 8)).is_err());
+        assert!(CompressedImage::parse_dds(&dds(8, 8, 1, b"DXT1", 31)).is_err());
+    }
+
+    #[test]
+    fn parses_ktx2_levels() {
+        let mut file = ktx2(146, 4, 4, &[(128, 16), (144, 16)]);
+        file.resize(160, 0xCD);
+        let image = CompressedImage::parse_ktx2(&file).unwrap();
+        assert_eq!(image.format, CompressedFormat::Bc7);
+        assert!(image.srgb);
+        assert_eq!(image.levels, [vec![0xCD; 16], vec![0xCD; 16]]);
+    }
+
+    #[test]
+    fn rejects_overflowing_ktx2_ranges() {
+        let file = ktx2(145, 4, 4, &[(u64::MAX - 4, 16)]);
+        assert!(CompressedImage::parse_ktx2(&file).is_err());
+        let file = ktx2(145, 4, 4, &[(104, 16)]);
+        assert!(CompressedImage::parse_ktx2(&file).is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_ktx2_files() {
+        let mut file = ktx2(145, 4, 4, &[(104, 16)]);
+        file[44..48].copy_from_slice(&1u32.to_le_bytes());
+        assert!(CompressedImage::parse_ktx2(&file).is_err());
+        assert!(CompressedImage::parse_ktx2(&ktx2(37, 4, 4, &[(104, 16)])).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use std::{ops::BitOr, path::Path};
+
+use gl::types::GLbitfield;
+
+use crate::{
+    errors::GLWError,
+    shader::{Shader, ShaderProgram, ShaderType},
+};
+
+/// Set of `glMemoryBarrier` bits, combinable with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBarrier(GLbitfield);
+
+impl MemoryBarrier {
+    pub const VERTEX_ATTRIB_ARRAY: Self = Self(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    pub const ELEMENT_ARRAY: Self = Self(gl::ELEMENT_ARRAY_BARRIER_BIT);
+    pub const UNIFORM: Self = Self(gl::UNIFORM_BARRIER_BIT);
+    pub const TEXTURE_FETCH: Self = Self(gl::TEXTURE_FETCH_BARRIER_BIT);
+    pub const SHADER_IMAGE_ACCESS: Self = Self(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    pub const COMMAND: Self = Self(gl::COMMAND_BARRIER_BIT);
+    pub const PIXEL_BUFFER: Self = Self(gl::PIXEL_BUFFER_BARRIER_BIT);
+    pub const TEXTURE_UPDATE: Self = Self(gl::TEXTURE_UPDATE_BARRIER_BIT);
+    pub const BUFFER_UPDATE: Self = Self(gl::BUFFER_UPDATE_BARRIER_BIT);
+    pub const FRAMEBUFFER: Self = Self(gl::FRAMEBUFFER_BARRIER_BIT);
+    pub const TRANSFORM_FEEDBACK: Self = Self(gl::TRANSFORM_FEEDBACK_BARRIER_BIT);
+    pub const ATOMIC_COUNTER: Self = Self(gl::ATOMIC_COUNTER_BARRIER_BIT);
+    pub const SHADER_STORAGE: Self = Self(gl::SHADER_STORAGE_BARRIER_BIT);
+    pub const ALL: Self = Self(gl::ALL_BARRIER_BITS);
+
+    pub fn bits(self) -> GLbitfield {
+        self.0
+    }
+}
+
+impl BitOr for MemoryBarrier {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Orders memory accesses of previously dispatched shaders against the
+/// accesses selected by `barrier`.
+pub fn memory_barrier(barrier: MemoryBarrier) {
+    unsafe {
+        gl::MemoryBarrier(barrier.bits());
+    }
+}
+
+pub struct ComputeProgram {
+    program: ShaderProgram,
+}
+
+impl ComputeProgram {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        let program = ShaderProgram::builder()
+            .attach_shader_path(path, ShaderType::ComputeShader)
+            .build()?;
+        Ok(Self { program })
+    }
+
+    pub fn from_shader(shader: &Shader) -> Result<Self, GLWError> {
+        let program = ShaderProgram::builder().attach_shader(shader).build()?;
+        Ok(Self { program })
+    }
+
+    pub fn program(&self) -> &ShaderProgram {
+        &self.program
+    }
+
+    /// Local work group size declared by the shader with `layout(local_size_*)`.
+    pub fn work_group_size(&self) -> [i32; 3] {
+        let mut size = [0; 3];
+        unsafe {
+            gl::GetProgramiv(
+                self.program.shader_program_id,
+                gl::COMPUTE_WORK_GROUP_SIZE,
+                size.as_mut_ptr(),
+            );
+        }
+        size
+    }
+
+    /// Binds the program and launches `x * y * z` work groups.
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        self.program.use_program();
+        unsafe {
+            gl::DispatchCompute(x, y, z);
+        }
+    }
+
+    /// [`dispatch`](Self::dispatch) followed by a [`memory_barrier`], for
+    /// the common case of consuming the results right away.
+    pub fn dispatch_with_barrier(&self, x: u32, y: u32, z: u32, barrier: MemoryBarrier) {
+        self.dispatch(x, y, z);
+        memory_barrier(barrier);
+    }
+}
+
+/// Maximum number of work groups that can be dispatched along each axis.
+pub fn max_work_group_count() -> [i32; 3] {
+    let mut count = [0; 3];
+    unsafe {
+        for (axis, value) in count.iter_mut().enumerate() {
+            gl::GetIntegeri_v(gl::MAX_COMPUTE_WORK_GROUP_COUNT, axis as u32, value);
+        }
+    }
+    count
+}
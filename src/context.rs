@@ -0,0 +1,183 @@
+use std::ffi::c_void;
+
+use crate::{
+    bindless,
+    errors::{GLWError, GLWErrorKind},
+    preprocessor::GlslTarget,
+    texture::TextureFormat,
+    utils,
+};
+
+/// Client API of a context. The `gl` bindings load for both, GLES 3.0 being
+/// close to a subset of GL 3.3 core.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GlApi {
+    #[default]
+    OpenGl,
+    OpenGlEs,
+}
+
+impl GlApi {
+    /// API of the current context, GLES contexts report a version string
+    /// starting with `OpenGL ES`.
+    pub fn current() -> Self {
+        if utils::gl_string(gl::VERSION).starts_with("OpenGL ES") {
+            Self::OpenGlEs
+        } else {
+            Self::OpenGl
+        }
+    }
+
+    /// Version a context of this API is created with by default.
+    pub fn default_version(&self) -> (u32, u32) {
+        match self {
+            Self::OpenGl => (3, 3),
+            Self::OpenGlEs => (3, 0),
+        }
+    }
+
+    /// GLSL dialect for shaders of a context of this API.
+    pub fn glsl_target(&self) -> GlslTarget {
+        match self {
+            Self::OpenGl => GlslTarget::Core(330),
+            Self::OpenGlEs => GlslTarget::Es(300),
+        }
+    }
+}
+
+/// Profile of a desktop GL context, GLES has none.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GlProfile {
+    #[default]
+    Core,
+    /// Keeps the functions deprecated in 3.2, like immediate mode. macOS
+    /// only offers it up to 2.1.
+    Compatibility,
+}
+
+/// Loads the GL functions, and the extension functions the `gl` crate
+/// doesn't generate, with the context's `loader`. The context has to be
+/// current.
+pub fn load_with(mut loader: impl FnMut(&'static str) -> *const c_void) {
+    gl::load_with(&mut loader);
+    bindless::load_with(loader);
+}
+
+/// Enables multisampling of the default framebuffer. GLES always
+/// multisamples multisampled surfaces and lacks the switch.
+pub fn enable_multisample() {
+    if GlApi::current() == GlApi::OpenGl {
+        unsafe {
+            gl::Enable(gl::MULTISAMPLE);
+        }
+    }
+}
+
+/// Sets how polygons are rasterized, e.g. `gl::LINE` for wireframes.
+/// Desktop only, on GLES this does nothing.
+pub fn set_polygon_mode(mode: gl::types::GLenum) {
+    if GlApi::current() == GlApi::OpenGl {
+        unsafe {
+            gl::PolygonMode(gl::FRONT_AND_BACK, mode);
+        }
+    }
+}
+
+/// Fails with [`ContextLost`](GLWErrorKind::ContextLost) if the GPU was
+/// reset since the context was made. Always succeeds without
+/// `glGetGraphicsResetStatus` (GL 4.5, `KHR_robustness`), and reports
+/// resets only for contexts created with robust access.
+pub fn check_reset() -> Result<(), GLWError> {
+    if !gl::GetGraphicsResetStatus::is_loaded() {
+        return Ok(());
+    }
+    match unsafe { gl::GetGraphicsResetStatus() } {
+        gl::NO_ERROR => Ok(()),
+        _ => Err(GLWErrorKind::ContextLost)?,
+    }
+}
+
+/// How depth is mapped to the depth buffer and compared.
+///
+/// Reverse-Z maps the near plane to 1 and the far plane to 0, with
+/// `glClipControl` making clip space depth 0..1. Float depth is densest near
+/// 0, which then evens out the precision over the distance, fixing
+/// z-fighting far away. Use it with the `reverse_z` projections and a
+/// [`Depth32F`](TextureFormat::Depth32F) depth buffer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DepthMode {
+    /// GL's -1..1 clip space depth, `LESS` passing nearer fragments.
+    #[default]
+    Standard,
+    ReverseZ,
+}
+
+impl DepthMode {
+    /// Mode of the current context, from its clip control state.
+    pub fn current() -> Self {
+        if !gl::ClipControl::is_loaded() {
+            return Self::Standard;
+        }
+        let mut depth_mode = 0;
+        unsafe {
+            gl::GetIntegerv(gl::CLIP_DEPTH_MODE, &mut depth_mode);
+        }
+        match depth_mode as u32 {
+            gl::ZERO_TO_ONE => Self::ReverseZ,
+            _ => Self::Standard,
+        }
+    }
+
+    /// Comparison passing fragments nearer than the stored depth.
+    pub fn depth_func(&self) -> gl::types::GLenum {
+        match self {
+            Self::Standard => gl::LESS,
+            Self::ReverseZ => gl::GREATER,
+        }
+    }
+
+    /// Depth cleared to, the farthest possible.
+    pub fn clear_depth(&self) -> f64 {
+        match self {
+            Self::Standard => 1.0,
+            Self::ReverseZ => 0.0,
+        }
+    }
+
+    /// Format of depth buffers, reverse-Z gains nothing without floats.
+    pub fn depth_format(&self) -> TextureFormat {
+        match self {
+            Self::Standard => TextureFormat::Depth24,
+            Self::ReverseZ => TextureFormat::Depth32F,
+        }
+    }
+
+    /// Normalized device depth of the near plane.
+    pub fn near_ndc(&self) -> f32 {
+        match self {
+            Self::Standard => -1.0,
+            Self::ReverseZ => 1.0,
+        }
+    }
+}
+
+/// Sets up clip control, the depth test and the clear depth for `mode`.
+/// Reverse-Z needs `glClipControl` (GL 4.5, `ARB_clip_control`).
+pub fn set_depth_mode(mode: DepthMode) -> Result<(), GLWError> {
+    let clip_depth = match mode {
+        DepthMode::Standard => gl::NEGATIVE_ONE_TO_ONE,
+        DepthMode::ReverseZ => gl::ZERO_TO_ONE,
+    };
+    match gl::ClipControl::is_loaded() {
+        true => unsafe { gl::ClipControl(gl::LOWER_LEFT, clip_depth) },
+        false if mode == DepthMode::ReverseZ => Err(GLWErrorKind::ExtensionNotSupported(
+            "GL_ARB_clip_control".to_string(),
+        ))?,
+        false => {}
+    }
+    unsafe {
+        gl::DepthFunc(mode.depth_func());
+        gl::ClearDepth(mode.clear_depth());
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    texture::{
+        self, ColorSpace, Filter, ImageData, MinFilter, TextureFormat, TextureParams, WrapMode,
+    },
+};
+
+pub struct Cubemap {
+    texture_id: u32,
+    size: u32,
+}
+
+impl Cubemap {
+    /// Loads the six faces in the order `GL_TEXTURE_CUBE_MAP_POSITIVE_X`
+    /// onwards expects them: right, left, top, bottom, front, back.
+    ///
+    /// Cubemap faces use a top-left origin, so unlike 2D textures the images
+    /// are not flipped. Color faces are loaded as sRGB. Fails with
+    /// [`ImageSizeMismatch`](GLWErrorKind::ImageSizeMismatch) unless all
+    /// faces are square and of the same size.
+    pub fn from_paths<P: AsRef<Path>>(paths: [P; 6]) -> Result<Self, GLWError> {
+        let faces = paths
+            .iter()
+            .map(|path| ImageData::load(path.as_ref(), false, ColorSpace::Srgb))
+            .collect::<Result<Vec<_>, _>>()?;
+        let size = faces[0].width;
+        for face in &faces {
+            if (face.width, face.height) != (size, size) {
+                Err(GLWErrorKind::ImageSizeMismatch {
+                    expected: (size, size),
+                    found: (face.width, face.height),
+                })?;
+            }
+        }
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
+        }
+        texture::with_unpack_alignment_1(|| {
+            for (face, image) in (0..).zip(&faces) {
+                unsafe {
+                    gl::TexImage2D(
+                        gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                        0,
+                        u32::from(image.internal_format) as i32,
+                        image.width as i32,
+                        image.height as i32,
+                        0,
+                        image.format,
+                        gl::UNSIGNED_BYTE,
+                        image.pixels.as_ptr().cast(),
+                    );
+                }
+            }
+        });
+        Self::default_params().apply(gl::TEXTURE_CUBE_MAP);
+
+        Ok(Self { texture_id, size })
+    }
+
+    /// Allocates `size` x `size` faces without data, e.g. for rendering an
+    /// environment or point light shadow map into.
+    pub fn empty(size: u32, format: TextureFormat) -> Self {
+        let (pixel_format, ty) = format.pixel_format();
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
+            for face in 0..6 {
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                    0,
+                    u32::from(format) as i32,
+                    size as i32,
+                    size as i32,
+                    0,
+                    pixel_format,
+                    ty,
+                    std::ptr::null(),
+                );
+            }
+        }
+        Self::default_params().apply(gl::TEXTURE_CUBE_MAP);
+
+        Self { texture_id, size }
+    }
+
+    /// Seamless-looking defaults: clamped edges and no mipmaps.
+    fn default_params() -> TextureParams {
+        TextureParams {
+            wrap: [WrapMode::ClampToEdge; 3],
+            min_filter: MinFilter::Linear,
+            mag_filter: Filter::Linear,
+            mipmaps: false,
+            ..TextureParams::default()
+        }
+    }
+
+    pub fn bind(&self, unit: u32) {
+        texture::bind_to_unit(gl::TEXTURE_CUBE_MAP, self.texture_id, unit);
+    }
+
+    /// Width and height of each face.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn id(&self) -> u32 {
+        self.texture_id
+    }
+}
+
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture_id);
+        }
+    }
+}
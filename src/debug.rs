@@ -0,0 +1,424 @@
+use std::{
+    ffi::{c_void, CStr},
+    fmt,
+    sync::Mutex,
+};
+
+use gl::types::{GLchar, GLenum, GLsizei, GLuint};
+
+use crate::errors::{GLWError, GLWErrorKind};
+
+/// Where a debug message comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugSource {
+    Api,
+    WindowSystem,
+    ShaderCompiler,
+    ThirdParty,
+    Application,
+    Other,
+}
+
+impl From<GLenum> for DebugSource {
+    fn from(value: GLenum) -> Self {
+        match value {
+            gl::DEBUG_SOURCE_API => Self::Api,
+            gl::DEBUG_SOURCE_WINDOW_SYSTEM => Self::WindowSystem,
+            gl::DEBUG_SOURCE_SHADER_COMPILER => Self::ShaderCompiler,
+            gl::DEBUG_SOURCE_THIRD_PARTY => Self::ThirdParty,
+            gl::DEBUG_SOURCE_APPLICATION => Self::Application,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// What a debug message is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugType {
+    /// A call failed, what `glGetError` would report.
+    Error,
+    DeprecatedBehavior,
+    UndefinedBehavior,
+    Portability,
+    Performance,
+    Marker,
+    PushGroup,
+    PopGroup,
+    Other,
+}
+
+impl From<GLenum> for DebugType {
+    fn from(value: GLenum) -> Self {
+        match value {
+            gl::DEBUG_TYPE_ERROR => Self::Error,
+            gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => Self::DeprecatedBehavior,
+            gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => Self::UndefinedBehavior,
+            gl::DEBUG_TYPE_PORTABILITY => Self::Portability,
+            gl::DEBUG_TYPE_PERFORMANCE => Self::Performance,
+            gl::DEBUG_TYPE_MARKER => Self::Marker,
+            gl::DEBUG_TYPE_PUSH_GROUP => Self::PushGroup,
+            gl::DEBUG_TYPE_POP_GROUP => Self::PopGroup,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Severity of a debug message, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DebugSeverity {
+    Notification,
+    Low,
+    Medium,
+    High,
+}
+
+impl From<GLenum> for DebugSeverity {
+    fn from(value: GLenum) -> Self {
+        match value {
+            gl::DEBUG_SEVERITY_HIGH => Self::High,
+            gl::DEBUG_SEVERITY_MEDIUM => Self::Medium,
+            gl::DEBUG_SEVERITY_LOW => Self::Low,
+            _ => Self::Notification,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugMessage {
+    pub source: DebugSource,
+    pub kind: DebugType,
+    /// Implementation defined id, the same for every instance of a message.
+    pub id: u32,
+    pub severity: DebugSeverity,
+    pub message: String,
+}
+
+impl fmt::Display for DebugMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GL {:?} {:?} ({:?}, {}): {}",
+            self.severity, self.kind, self.source, self.id, self.message
+        )
+    }
+}
+
+/// What happens to the messages a [`DebugOutput`] receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugSink {
+    /// Prints every message to stderr.
+    Log,
+    /// Prints every message, and panics on errors. The callback can't
+    /// unwind into the driver, so the panic aborts; run under a debugger to
+    /// see the offending call.
+    PanicOnError,
+    /// Keeps the messages for [`DebugOutput::take_messages`].
+    Collect,
+}
+
+struct DebugState {
+    sink: DebugSink,
+    min_severity: DebugSeverity,
+    messages: Mutex<Vec<DebugMessage>>,
+}
+
+/// A `KHR_debug` message callback installed on the current context. Output
+/// is synchronous, so messages arrive during the call causing them.
+/// Dropping it stops the output, and has to happen while the context lives.
+pub struct DebugOutput {
+    // boxed so the pointer the driver keeps stays valid
+    state: Box<DebugState>,
+}
+
+impl DebugOutput {
+    /// Installs the callback, passing messages of at least `min_severity`
+    /// on to `sink`. Contexts created without the debug flag may only
+    /// report few or no messages.
+    pub fn new(sink: DebugSink, min_severity: DebugSeverity) -> Result<Self, GLWError> {
+        if !supported() {
+            Err(GLWErrorKind::ExtensionNotSupported(
+                "GL_KHR_debug".to_string(),
+            ))?;
+        }
+        let state = Box::new(DebugState {
+            sink,
+            min_severity,
+            messages: Mutex::new(Vec::new()),
+        });
+        unsafe {
+            gl::Enable(gl::DEBUG_OUTPUT);
+            gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl::DebugMessageCallback(callback, (&*state as *const DebugState).cast());
+            gl::DebugMessageControl(
+                gl::DONT_CARE,
+                gl::DONT_CARE,
+                gl::DONT_CARE,
+                0,
+                std::ptr::null(),
+                gl::TRUE,
+            );
+        }
+        Ok(Self { state })
+    }
+
+    /// Messages received so far with [`DebugSink::Collect`].
+    pub fn take_messages(&self) -> Vec<DebugMessage> {
+        std::mem::take(&mut self.state.messages.lock().unwrap())
+    }
+
+    pub fn sink(&self) -> DebugSink {
+        self.state.sink
+    }
+
+    pub fn min_severity(&self) -> DebugSeverity {
+        self.state.min_severity
+    }
+}
+
+impl Drop for DebugOutput {
+    fn drop(&mut self) {
+        unsafe {
+            // a replacement may have been installed already
+            let mut user_param = std::ptr::null_mut();
+            gl::GetPointerv(
+                gl::DEBUG_CALLBACK_USER_PARAM,
+                std::ptr::addr_of_mut!(user_param),
+            );
+            if user_param as *const DebugState != &*self.state {
+                return;
+            }
+            gl::Disable(gl::DEBUG_OUTPUT);
+            gl::DebugMessageCallback(ignore, std::ptr::null());
+        }
+    }
+}
+
+/// Whether the context has debug output, core since 4.3 and GLES 3.2.
+pub fn supported() -> bool {
+    gl::DebugMessageCallback::is_loaded()
+}
+
+/// Whether the context was created with the debug flag.
+pub fn is_debug_context() -> bool {
+    let mut flags = 0;
+    unsafe {
+        gl::GetIntegerv(gl::CONTEXT_FLAGS, &mut flags);
+    }
+    flags as u32 & gl::CONTEXT_FLAG_DEBUG_BIT != 0
+}
+
+extern "system" fn callback(
+    source: GLenum,
+    kind: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    _length: GLsizei,
+    message: *const GLchar,
+    user_param: *mut c_void,
+) {
+    // SAFETY: the pointer is the boxed state, which outlives the callback
+    let state = unsafe { &*(user_param as *const DebugState) };
+    let severity = DebugSeverity::from(severity);
+    if severity < state.min_severity {
+        return;
+    }
+    let message = DebugMessage {
+        source: source.into(),
+        kind: kind.into(),
+        id,
+        severity,
+        message: unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .to_string(),
+    };
+
+    match state.sink {
+        DebugSink::Log => eprintln!("{message}"),
+        DebugSink::PanicOnError => {
+            eprintln!("{message}");
+            if message.kind == DebugType::Error {
+                panic!("{message}");
+            }
+        }
+        DebugSink::Collect => state.messages.lock().unwrap().push(message),
+    }
+}
+
+extern "system" fn ignore(
+    _source: GLenum,
+    _kind: GLenum,
+    _id: GLuint,
+    _severity: GLenum,
+    _length: GLsizei,
+    _message: *const GLchar,
+    _user_param: *mut c_void,
+) {
+}
+
+/// Groups the commands issued until it drops, under `name`, in frame
+/// captures and debug output. Groups nest, up to
+/// `GL_MAX_DEBUG_GROUP_STACK_DEPTH` deep. Does nothing without debug
+/// output.
+///
+/// ```ignore
+/// let _group = DebugGroup::push("geometry pass");
+/// let _group = debug_group!("shadow pass {}", light);
+/// ```
+#[must_use = "the group ends when dropped"]
+pub struct DebugGroup {
+    pushed: bool,
+}
+
+impl DebugGroup {
+    pub fn push(name: &str) -> Self {
+        if !supported() {
+            return Self { pushed: false };
+        }
+        unsafe {
+            gl::PushDebugGroup(
+                gl::DEBUG_SOURCE_APPLICATION,
+                0,
+                name.len() as GLsizei,
+                name.as_ptr().cast(),
+            );
+        }
+        Self { pushed: true }
+    }
+
+    /// Like [`push`](Self::push), formatting the name only when groups are
+    /// supported. Used by [`debug_group!`](crate::debug_group).
+    pub fn push_fmt(name: fmt::Arguments) -> Self {
+        match (supported(), name.as_str()) {
+            (false, _) => Self { pushed: false },
+            (true, Some(name)) => Self::push(name),
+            (true, None) => Self::push(&name.to_string()),
+        }
+    }
+}
+
+impl Drop for DebugGroup {
+    fn drop(&mut self) {
+        if self.pushed {
+            unsafe {
+                gl::PopDebugGroup();
+            }
+        }
+    }
+}
+
+/// Pushes a [`DebugGroup`] named with `format!` arguments, returning the
+/// guard.
+#[macro_export]
+macro_rules! debug_group {
+    ($($arg:tt)+) => {
+        $crate::debug::DebugGroup::push_fmt(format_args!($($arg)+))
+    };
+}
+
+/// An error code from `glGetError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlError {
+    InvalidEnum,
+    InvalidValue,
+    InvalidOperation,
+    InvalidFramebufferOperation,
+    OutOfMemory,
+    StackOverflow,
+    StackUnderflow,
+    ContextLost,
+    Other(u32),
+}
+
+impl From<GLenum> for GlError {
+    fn from(value: GLenum) -> Self {
+        match value {
+            gl::INVALID_ENUM => Self::InvalidEnum,
+            gl::INVALID_VALUE => Self::InvalidValue,
+            gl::INVALID_OPERATION => Self::InvalidOperation,
+            gl::INVALID_FRAMEBUFFER_OPERATION => Self::InvalidFramebufferOperation,
+            gl::OUT_OF_MEMORY => Self::OutOfMemory,
+            gl::STACK_OVERFLOW => Self::StackOverflow,
+            gl::STACK_UNDERFLOW => Self::StackUnderflow,
+            gl::CONTEXT_LOST => Self::ContextLost,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for GlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEnum => write!(f, "GL_INVALID_ENUM"),
+            Self::InvalidValue => write!(f, "GL_INVALID_VALUE"),
+            Self::InvalidOperation => write!(f, "GL_INVALID_OPERATION"),
+            Self::InvalidFramebufferOperation => write!(f, "GL_INVALID_FRAMEBUFFER_OPERATION"),
+            Self::OutOfMemory => write!(f, "GL_OUT_OF_MEMORY"),
+            Self::StackOverflow => write!(f, "GL_STACK_OVERFLOW"),
+            Self::StackUnderflow => write!(f, "GL_STACK_UNDERFLOW"),
+            Self::ContextLost => write!(f, "GL_CONTEXT_LOST"),
+            Self::Other(code) => write!(f, "GL error {code:#x}"),
+        }
+    }
+}
+
+/// Whether [`gl_check!`](crate::gl_check) checks, in debug builds or with
+/// the `gl-check` feature.
+pub const CHECKS_ENABLED: bool = cfg!(any(debug_assertions, feature = "gl-check"));
+
+/// Reports the first error `glGetError` has recorded, clearing all of
+/// them, as a failure of `call` at `location`, or as
+/// [`ContextLost`](GLWErrorKind::ContextLost). Used by
+/// [`gl_check!`](crate::gl_check), which passes the call site.
+pub fn check_error(call: &'static str, location: &'static str) -> Result<(), GLWError> {
+    let errors = take_errors();
+    if errors.contains(&GlError::ContextLost) {
+        Err(GLWErrorKind::ContextLost)?;
+    }
+    match errors.first().copied() {
+        Some(error) => Err(GLWErrorKind::GLCallFailed {
+            error,
+            call,
+            location,
+        })?,
+        None => Ok(()),
+    }
+}
+
+/// Every error recorded since they were last read, oldest first. Errors
+/// stick until read, so call this before a checked call to not blame it
+/// for older ones.
+pub fn take_errors() -> Vec<GlError> {
+    let mut errors = Vec::new();
+    loop {
+        let error = unsafe { gl::GetError() };
+        // contexts that lost their device keep returning an error
+        if error == gl::NO_ERROR || errors.len() == 16 {
+            return errors;
+        }
+        errors.push(error.into());
+    }
+}
+
+/// Evaluates a GL call and, in debug builds or with the `gl-check` feature,
+/// checks `glGetError` afterwards, evaluating to
+/// `Result<return value, GLWError>` naming the call and its location. The
+/// call isn't wrapped in `unsafe`.
+///
+/// ```ignore
+/// unsafe { gl_check!(gl::BindBuffer(gl::ARRAY_BUFFER, id))? };
+/// ```
+#[macro_export]
+macro_rules! gl_check {
+    ($call:expr) => {{
+        let result = $call;
+        if $crate::debug::CHECKS_ENABLED {
+            $crate::debug::check_error(
+                stringify!($call),
+                concat!(file!(), ":", line!(), ":", column!()),
+            )
+            .map(|()| result)
+        } else {
+            Ok::<_, $crate::errors::GLWError>(result)
+        }
+    }};
+}
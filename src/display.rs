@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use crate::errors::{GLWError, GLWErrorExt};
+
+/// A monitor resolution and refresh rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: u32,
+}
+
+impl From<glfw::VidMode> for VideoMode {
+    fn from(val: glfw::VidMode) -> Self {
+        Self {
+            width: val.width,
+            height: val.height,
+            refresh_rate: val.refresh_rate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorInfo {
+    pub name: String,
+    /// Position of the monitor on the virtual desktop.
+    pub position: (i32, i32),
+    pub current_mode: Option<VideoMode>,
+    pub modes: Vec<VideoMode>,
+    pub is_primary: bool,
+}
+
+/// Every connected monitor with its video modes.
+pub fn monitors(glfw: &mut glfw::Glfw) -> Vec<MonitorInfo> {
+    let primary_name = glfw.with_primary_monitor(|_, monitor| monitor.map(|m| m.get_name()));
+    glfw.with_connected_monitors(|_, monitors| {
+        monitors
+            .iter()
+            .map(|monitor| {
+                let name = monitor.get_name();
+                MonitorInfo {
+                    is_primary: primary_name.as_ref() == Some(&name),
+                    name,
+                    position: monitor.get_pos(),
+                    current_mode: monitor.get_video_mode().map(VideoMode::from),
+                    modes: monitor
+                        .get_video_modes()
+                        .into_iter()
+                        .map(VideoMode::from)
+                        .collect(),
+                }
+            })
+            .collect()
+    })
+}
+
+/// How the window covers the screen. Fullscreen modes use the primary
+/// monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// Exclusive fullscreen in the given mode, or the monitor's current one.
+    Fullscreen(Option<VideoMode>),
+    /// Fullscreen at the monitor's current mode, so switching to it doesn't
+    /// change the resolution.
+    Borderless,
+}
+
+/// Position and size of a window in windowed mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WindowGeometry {
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+}
+
+impl WindowGeometry {
+    pub fn of(window: &glfw::Window) -> Self {
+        let (width, height) = window.get_size();
+        Self {
+            position: window.get_pos(),
+            size: (width as u32, height as u32),
+        }
+    }
+}
+
+/// Switches `window` to `mode`, going back to `windowed` geometry for
+/// windowed mode.
+pub(crate) fn apply(
+    glfw: &mut glfw::Glfw,
+    window: &mut glfw::Window,
+    mode: DisplayMode,
+    windowed: WindowGeometry,
+) {
+    let fullscreen_mode = match mode {
+        DisplayMode::Windowed => {
+            let (x, y) = windowed.position;
+            let (width, height) = windowed.size;
+            window.set_monitor(glfw::WindowMode::Windowed, x, y, width, height, None);
+            return;
+        }
+        DisplayMode::Fullscreen(video_mode) => video_mode,
+        DisplayMode::Borderless => None,
+    };
+
+    glfw.with_primary_monitor_mut(|_, monitor| {
+        let Some(monitor) = monitor else { return };
+        let Some(video_mode) = fullscreen_mode.or(monitor.get_video_mode().map(VideoMode::from))
+        else {
+            return;
+        };
+        window.set_monitor(
+            glfw::WindowMode::FullScreen(monitor),
+            0,
+            0,
+            video_mode.width,
+            video_mode.height,
+            Some(video_mode.refresh_rate),
+        );
+    });
+}
+
+/// Sets the window icon to the image at `path`. The window system picks
+/// the size it needs, 32x32 or 48x48 images work best.
+pub fn set_icon(window: &mut glfw::Window, path: impl AsRef<Path>) -> Result<(), GLWError> {
+    let path = path.as_ref();
+    let image = image::open(path)
+        .info(format!("{}", path.display()))?
+        .to_rgba();
+    let (width, height) = (image.width(), image.height());
+    // GLFW reads the pixels as bytes, 4 per pixel
+    let pixels = image
+        .into_raw()
+        .chunks_exact(4)
+        .map(|pixel| u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]))
+        .collect();
+    window.set_icon_from_pixels(vec![glfw::PixelImage {
+        width,
+        height,
+        pixels,
+    }]);
+    Ok(())
+}
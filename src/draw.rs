@@ -0,0 +1,48 @@
+/// How vertices are assembled into primitives by draw calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Points,
+    Lines,
+    LineStrip,
+    LineLoop,
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+    /// Input of the tessellation stages, see
+    /// [`set_patch_vertices`](crate::shader::set_patch_vertices).
+    Patches,
+}
+
+impl From<Primitive> for u32 {
+    fn from(val: Primitive) -> Self {
+        match val {
+            Primitive::Points => gl::POINTS,
+            Primitive::Lines => gl::LINES,
+            Primitive::LineStrip => gl::LINE_STRIP,
+            Primitive::LineLoop => gl::LINE_LOOP,
+            Primitive::Triangles => gl::TRIANGLES,
+            Primitive::TriangleStrip => gl::TRIANGLE_STRIP,
+            Primitive::TriangleFan => gl::TRIANGLE_FAN,
+            Primitive::Patches => gl::PATCHES,
+        }
+    }
+}
+
+/// Draws `count` vertices starting at `first` from the bound vertex array.
+pub fn draw_arrays(primitive: Primitive, first: usize, count: usize) {
+    unsafe {
+        gl::DrawArrays(primitive.into(), first as i32, count as i32);
+    }
+}
+
+/// Draws `instances` instances of `count` vertices starting at `first`.
+pub fn draw_arrays_instanced(primitive: Primitive, first: usize, count: usize, instances: usize) {
+    unsafe {
+        gl::DrawArraysInstanced(
+            primitive.into(),
+            first as i32,
+            count as i32,
+            instances as i32,
+        );
+    }
+}
@@ -1,31 +1,72 @@
-use std::path::PathBuf;
+#[cfg(feature = "backtrace")]
+use std::{backtrace::Backtrace, panic::Location};
+use std::{fmt, path::PathBuf};
 use thiserror::Error;
 
+use crate::shader::ShaderType;
+
 pub type Result<T, E = GLWError> = std::result::Result<T, E>;
 
-#[derive(Debug, Error)]
-#[error("{kind}\ninfo:\n{info:?}")]
+/// With the `backtrace` feature errors also record where they were raised
+/// and, if `RUST_BACKTRACE` is set, a backtrace, both shown when printed.
+#[derive(Debug)]
 pub struct GLWError {
     kind: GLWErrorKind,
     info: Option<String>,
+    #[cfg(feature = "backtrace")]
+    location: &'static Location<'static>,
+    #[cfg(feature = "backtrace")]
+    // boxed to keep results small
+    backtrace: Box<Backtrace>,
 }
 
 impl GLWError {
+    #[track_caller]
     pub fn new(kind: GLWErrorKind, info: impl Into<Option<String>>) -> Self {
         Self {
             kind,
             info: info.into(),
+            #[cfg(feature = "backtrace")]
+            location: Location::caller(),
+            #[cfg(feature = "backtrace")]
+            backtrace: Box::new(Backtrace::capture()),
         }
     }
 
     pub fn info(self, info: String) -> Self {
         Self {
-            kind: self.kind,
             info: Some(info),
+            ..self
         }
     }
+
+    pub fn kind(&self) -> &GLWErrorKind {
+        &self.kind
+    }
+
+    /// Where the error was made or converted with `?`.
+    #[cfg(feature = "backtrace")]
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
 }
 
+impl fmt::Display for GLWError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\ninfo:\n{:?}", self.kind, self.info)?;
+        #[cfg(feature = "backtrace")]
+        write!(f, "\nat {}\nbacktrace:\n{}", self.location, self.backtrace)?;
+        Ok(())
+    }
+}
+
+impl std::error::Error for GLWError {}
+
 #[derive(Debug, Error)]
 pub enum GLWErrorKind {
     #[error("Io Error: {0}")]
@@ -34,21 +75,130 @@ pub enum GLWErrorKind {
     ShaderCompilationFailed(Option<PathBuf>),
     #[error("Shader Linking Failed")]
     ShaderProgramLinkingFailed,
+    #[error("Shader Program Validation Failed")]
+    ShaderProgramValidationFailed,
+    #[error("Pending shader program polled after it was ready")]
+    ProgramAlreadyBuilt,
+    #[error("Shader program was not linked as separable")]
+    ProgramNotSeparable,
+    #[error("Shader program is missing a {0:?} stage")]
+    MissingShaderStage(ShaderType),
     #[error(transparent)]
     CStringNulError(#[from] std::ffi::NulError),
     #[error("Uniform not found: {0}")]
     UniformNotFound(String),
+    #[error("Uniform location belongs to another program")]
+    UniformLocationMismatch,
+    #[error("Fragment shader output not found: {0}")]
+    FragmentOutputNotFound(String),
+    #[error("Uniform block not found: {0}")]
+    UniformBlockNotFound(String),
+    #[error("Shader storage block not found: {0}")]
+    ShaderStorageBlockNotFound(String),
+    #[error("Buffer mapping failed")]
+    BufferMapFailed,
+    #[error("Waiting for fence failed")]
+    FenceWaitFailed,
+    #[error("Include cycle detected at: {0:?}")]
+    ShaderIncludeCycle(PathBuf),
+    #[error("Malformed include directive: {0}")]
+    InvalidIncludeDirective(String),
+    #[error("File watcher error: {0}")]
+    WatchError(#[from] notify::Error),
+    #[error("Image error: {0}")]
+    ImageError(#[from] image::ImageError),
+    #[error("Failed to load texture {path:?}: {source}")]
+    TextureLoadFailed {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    #[error("Failed to load model {path:?}: {source}")]
+    ModelLoadFailed {
+        path: PathBuf,
+        source: tobj::LoadError,
+    },
+    #[cfg(feature = "assimp")]
+    #[error("Failed to import {path:?} with Assimp: {source}")]
+    AssimpLoadFailed {
+        path: PathBuf,
+        source: russimp::RussimpError,
+    },
+    #[cfg(feature = "gltf")]
+    #[error("Failed to load glTF {path:?}: {source}")]
+    GltfLoadFailed {
+        path: PathBuf,
+        source: Box<gltf::Error>,
+    },
+    #[error("Invalid texture file: {0}")]
+    InvalidTextureFile(String),
+    #[error("Unsupported texture format: {0}")]
+    UnsupportedTextureFormat(String),
+    #[error("Image size {found:?} doesn't match texture size {expected:?}")]
+    ImageSizeMismatch {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    #[error("Pixel data is {found} bytes, the image takes {expected}")]
+    PixelDataSizeMismatch { expected: usize, found: usize },
+    #[error("Sample count {requested} is not supported, at most {max} are")]
+    UnsupportedSampleCount { requested: u32, max: u32 },
+    #[error("No space left in the atlas for a {width}x{height} entry")]
+    AtlasFull { width: u32, height: u32 },
+    #[error("Framebuffer incomplete: {0}")]
+    FramebufferIncomplete(crate::framebuffer::FramebufferStatus),
+    #[error("Attachment size {found:?} doesn't match framebuffer size {expected:?}")]
+    AttachmentSizeMismatch {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    #[error("All {0} texture units are in use")]
+    TextureUnitsExhausted(u32),
+    #[error("Anisotropy {requested} is not supported, at most {max} is")]
+    UnsupportedAnisotropy { requested: f32, max: f32 },
+    #[error("Extension not supported: {0}")]
+    ExtensionNotSupported(String),
+    #[cfg(feature = "glfw")]
+    #[error("GLFW initialization failed: {0}")]
+    GlfwInitError(#[from] glfw::InitError),
+    #[error("{error} from {call} at {location}")]
+    GLCallFailed {
+        error: crate::debug::GlError,
+        call: &'static str,
+        location: &'static str,
+    },
+    #[error("Invalid vertex layout: {0}")]
+    InvalidVertexLayout(String),
+    /// The GPU was reset, e.g. after a driver crash or timeout. Every GL
+    /// object is gone, and the context has to be recreated.
+    #[error("GL context lost")]
+    ContextLost,
+    #[error("{count} joints don't fit the palette's {max}")]
+    TooManyJoints { count: usize, max: usize },
+    #[error("Joint {0} has a parent that doesn't come before it")]
+    InvalidJointParent(usize),
+    #[error("Node can't be parented to itself or its descendants")]
+    InvalidSceneParent,
+    #[error("Material {0:?} has no shader program")]
+    MaterialWithoutProgram(Option<String>),
+    #[error("GIF frames are at most 65535 pixels wide and high, not {width}x{height}")]
+    GifTooLarge { width: u32, height: u32 },
+    #[error("Window creation failed")]
+    WindowCreationFailed,
+    #[cfg(feature = "winit")]
+    #[error("Glutin Error: {0}")]
+    GlutinError(#[from] glutin::error::Error),
+    #[cfg(feature = "renderdoc")]
+    #[error("RenderDoc Error: {0}")]
+    RenderDocError(#[from] renderdoc::Error),
 }
 
 impl<T> From<T> for GLWError
 where
     T: Into<GLWErrorKind>,
 {
+    #[track_caller]
     fn from(value: T) -> Self {
-        Self {
-            kind: value.into(),
-            info: None,
-        }
+        Self::new(value.into(), None)
     }
 }
 
@@ -64,10 +214,15 @@ where
 {
     type Ok = O;
 
+    #[track_caller]
     fn info(
         self,
         info: String,
     ) -> Result<<std::result::Result<O, E> as GLWErrorExt>::Ok, GLWError> {
-        self.map_err(|e| e.into().info(info))
+        // not `map_err`, the closure would be the caller
+        match self {
+            Ok(ok) => Ok(ok),
+            Err(err) => Err(err.into().info(info)),
+        }
     }
 }
@@ -0,0 +1,204 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use glfw::{Action, Key, Modifiers, MouseButton, WindowEvent};
+
+use crate::app::AppContext;
+
+type Handler = Box<dyn FnMut(&mut AppContext, &WindowEvent)>;
+type CustomHandler = Box<dyn FnMut(&mut AppContext, &dyn Any)>;
+
+/// Identifies a registered handler, to [`remove`](EventDispatcher::remove)
+/// it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(u64);
+
+impl HandlerId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Handlers for window events and app defined events, run by
+/// [`run`](crate::run) in registration order after [`App::on_event`].
+/// The run loop keeps updating the viewport on resizes regardless.
+///
+/// Handlers get the [`AppContext`], and can register, remove and emit from
+/// there while running. Registrations take effect with the next event.
+///
+/// [`App::on_event`]: crate::app::App::on_event
+#[derive(Default)]
+pub struct EventDispatcher {
+    handlers: Vec<(HandlerId, Handler)>,
+    custom_handlers: HashMap<TypeId, Vec<(HandlerId, CustomHandler)>>,
+    /// Emitted custom events, dispatched once per frame before `update`.
+    queue: Vec<(TypeId, Box<dyn Any>)>,
+    /// Handlers removed while the dispatcher was taken out to run them.
+    removed: Vec<HandlerId>,
+}
+
+impl EventDispatcher {
+    /// Registers `handler` for every window event.
+    pub fn on_event(
+        &mut self,
+        handler: impl FnMut(&mut AppContext, &WindowEvent) + 'static,
+    ) -> HandlerId {
+        let id = HandlerId::next();
+        self.handlers.push((id, Box::new(handler)));
+        id
+    }
+
+    /// Registers `handler` for framebuffer resizes, in pixels.
+    pub fn on_resize(
+        &mut self,
+        mut handler: impl FnMut(&mut AppContext, i32, i32) + 'static,
+    ) -> HandlerId {
+        self.on_event(move |ctx, event| {
+            if let WindowEvent::FramebufferSize(width, height) = *event {
+                handler(ctx, width, height);
+            }
+        })
+    }
+
+    pub fn on_key(
+        &mut self,
+        mut handler: impl FnMut(&mut AppContext, Key, Action, Modifiers) + 'static,
+    ) -> HandlerId {
+        self.on_event(move |ctx, event| {
+            if let WindowEvent::Key(key, _, action, modifiers) = *event {
+                handler(ctx, key, action, modifiers);
+            }
+        })
+    }
+
+    pub fn on_mouse_button(
+        &mut self,
+        mut handler: impl FnMut(&mut AppContext, MouseButton, Action, Modifiers) + 'static,
+    ) -> HandlerId {
+        self.on_event(move |ctx, event| {
+            if let WindowEvent::MouseButton(button, action, modifiers) = *event {
+                handler(ctx, button, action, modifiers);
+            }
+        })
+    }
+
+    /// Registers `handler` for cursor moves, in screen coordinates.
+    pub fn on_cursor_move(
+        &mut self,
+        mut handler: impl FnMut(&mut AppContext, f64, f64) + 'static,
+    ) -> HandlerId {
+        self.on_event(move |ctx, event| {
+            if let WindowEvent::CursorPos(x, y) = *event {
+                handler(ctx, x, y);
+            }
+        })
+    }
+
+    pub fn on_scroll(
+        &mut self,
+        mut handler: impl FnMut(&mut AppContext, f64, f64) + 'static,
+    ) -> HandlerId {
+        self.on_event(move |ctx, event| {
+            if let WindowEvent::Scroll(x, y) = *event {
+                handler(ctx, x, y);
+            }
+        })
+    }
+
+    /// Registers `handler` for files dropped onto the window.
+    pub fn on_file_drop(
+        &mut self,
+        mut handler: impl FnMut(&mut AppContext, &[PathBuf]) + 'static,
+    ) -> HandlerId {
+        self.on_event(move |ctx, event| {
+            if let WindowEvent::FileDrop(paths) = event {
+                handler(ctx, paths);
+            }
+        })
+    }
+
+    /// Registers `handler` for app defined events of type `T`, see
+    /// [`emit`](Self::emit).
+    pub fn on_custom<T: 'static>(
+        &mut self,
+        mut handler: impl FnMut(&mut AppContext, &T) + 'static,
+    ) -> HandlerId {
+        let id = HandlerId::next();
+        let handler: CustomHandler = Box::new(move |ctx, event| {
+            if let Some(event) = event.downcast_ref() {
+                handler(ctx, event);
+            }
+        });
+        self.custom_handlers
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push((id, handler));
+        id
+    }
+
+    /// Queues `event` for the handlers of `T`, which run before the next
+    /// `update`.
+    pub fn emit<T: 'static>(&mut self, event: T) {
+        self.queue.push((TypeId::of::<T>(), Box::new(event)));
+    }
+
+    pub fn remove(&mut self, id: HandlerId) {
+        self.handlers.retain(|(handler_id, _)| *handler_id != id);
+        for handlers in self.custom_handlers.values_mut() {
+            handlers.retain(|(handler_id, _)| *handler_id != id);
+        }
+        self.removed.push(id);
+    }
+
+    /// Runs the handlers of every window event for `event`.
+    pub(crate) fn dispatch(ctx: &mut AppContext, event: &WindowEvent) {
+        let mut dispatcher = std::mem::take(&mut ctx.events);
+        for (id, handler) in &mut dispatcher.handlers {
+            if !ctx.events.removed.contains(id) {
+                handler(ctx, event);
+            }
+        }
+        dispatcher.absorb(ctx);
+    }
+
+    /// Runs the handlers of the custom events emitted since the last call.
+    /// Events emitted meanwhile wait for the next call.
+    pub(crate) fn dispatch_custom(ctx: &mut AppContext) {
+        let mut dispatcher = std::mem::take(&mut ctx.events);
+        for (type_id, event) in std::mem::take(&mut dispatcher.queue) {
+            let Some(handlers) = dispatcher.custom_handlers.get_mut(&type_id) else {
+                continue;
+            };
+            for (id, handler) in handlers {
+                if !ctx.events.removed.contains(id) {
+                    handler(ctx, event.as_ref());
+                }
+            }
+        }
+        dispatcher.absorb(ctx);
+    }
+
+    /// Puts the dispatcher back into `ctx`, with what the handlers
+    /// registered, removed and emitted through `ctx` meanwhile.
+    fn absorb(mut self, ctx: &mut AppContext) {
+        let changes = std::mem::take(&mut ctx.events);
+        for id in changes.removed {
+            self.remove(id);
+        }
+        self.removed.clear();
+        self.handlers.extend(changes.handlers);
+        for (type_id, handlers) in changes.custom_handlers {
+            self.custom_handlers
+                .entry(type_id)
+                .or_default()
+                .extend(handlers);
+        }
+        self.queue.extend(changes.queue);
+        ctx.events = self;
+    }
+}
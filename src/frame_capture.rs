@@ -0,0 +1,60 @@
+use renderdoc::{InputButton, RenderDoc, V141};
+
+use crate::errors::GLWError;
+
+/// RenderDoc's in-application API, for capturing exactly the frames of
+/// interest from code. Only connects when RenderDoc is injected, i.e. the
+/// app was launched from RenderDoc.
+///
+/// [`run`](crate::run) starts and ends captures itself on
+/// [`AppConfig::capture_key`](crate::app::AppConfig::capture_key).
+pub struct FrameCapture {
+    api: RenderDoc<V141>,
+}
+
+impl FrameCapture {
+    pub fn new() -> Result<Self, GLWError> {
+        Ok(Self {
+            api: RenderDoc::new()?,
+        })
+    }
+
+    /// Starts capturing the commands of the current context until
+    /// [`end`](Self::end).
+    pub fn start(&mut self) {
+        self.api
+            .start_frame_capture(std::ptr::null(), std::ptr::null());
+    }
+
+    /// Ends the capture and saves it.
+    pub fn end(&mut self) {
+        self.api
+            .end_frame_capture(std::ptr::null(), std::ptr::null());
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.api.is_frame_capturing()
+    }
+
+    /// Captures the next presented frame.
+    pub fn trigger(&mut self) {
+        self.api.trigger_capture();
+    }
+
+    /// Stops RenderDoc's own capture hotkeys, e.g. when they would clash
+    /// with a key the app captures on.
+    pub fn disable_capture_keys(&mut self) {
+        self.api.set_capture_keys::<InputButton>(&[]);
+    }
+
+    /// Number of captures saved so far.
+    pub fn capture_count(&self) -> u32 {
+        self.api.get_num_captures()
+    }
+
+    /// Opens the replay UI connected to the app, e.g. after a capture,
+    /// returning its process id.
+    pub fn launch_replay_ui(&self) -> Result<u32, GLWError> {
+        Ok(self.api.launch_replay_ui(true, None)?)
+    }
+}
@@ -0,0 +1,575 @@
+use std::cell::Cell;
+
+use crate::{
+    context::DepthMode,
+    errors::{GLWError, GLWErrorKind},
+    multisample_texture::Texture2DMultisample,
+    shader::ShaderProgram,
+    texture::{Filter, MinFilter, Texture2D, TextureFormat, WrapMode},
+    trace,
+};
+
+/// Why `glCheckFramebufferStatus` found a framebuffer incomplete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramebufferStatus {
+    Undefined,
+    IncompleteAttachment,
+    MissingAttachment,
+    IncompleteDrawBuffer,
+    IncompleteReadBuffer,
+    /// The combination of formats isn't supported by the implementation.
+    Unsupported,
+    /// Attachments differ in sample count or fixed sample locations.
+    IncompleteMultisample,
+    IncompleteLayerTargets,
+    Other(u32),
+}
+
+impl From<u32> for FramebufferStatus {
+    fn from(value: u32) -> Self {
+        match value {
+            gl::FRAMEBUFFER_UNDEFINED => Self::Undefined,
+            gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => Self::IncompleteAttachment,
+            gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => Self::MissingAttachment,
+            gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => Self::IncompleteDrawBuffer,
+            gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => Self::IncompleteReadBuffer,
+            gl::FRAMEBUFFER_UNSUPPORTED => Self::Unsupported,
+            gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => Self::IncompleteMultisample,
+            gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => Self::IncompleteLayerTargets,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl std::fmt::Display for FramebufferStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Undefined => "FRAMEBUFFER_UNDEFINED",
+            Self::IncompleteAttachment => "FRAMEBUFFER_INCOMPLETE_ATTACHMENT",
+            Self::MissingAttachment => "FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT",
+            Self::IncompleteDrawBuffer => "FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER",
+            Self::IncompleteReadBuffer => "FRAMEBUFFER_INCOMPLETE_READ_BUFFER",
+            Self::Unsupported => "FRAMEBUFFER_UNSUPPORTED",
+            Self::IncompleteMultisample => "FRAMEBUFFER_INCOMPLETE_MULTISAMPLE",
+            Self::IncompleteLayerTargets => "FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS",
+            Self::Other(status) => return write!(f, "status {status:#x}"),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Runs `f` with `framebuffer_id` bound, restoring the previous binding.
+fn with_bound<R>(framebuffer_id: u32, f: impl FnOnce() -> R) -> R {
+    let mut previous = 0;
+    unsafe {
+        gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
+    }
+    let result = f();
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+    }
+    result
+}
+
+/// Draws into the first `count` color attachments of the bound
+/// framebuffer, or none for depth only targets.
+fn set_draw_buffers(count: u32) {
+    let buffers: Vec<u32> = (0..count)
+        .map(|index| gl::COLOR_ATTACHMENT0 + index)
+        .collect();
+    unsafe {
+        // `glDrawBuffer` is desktop only
+        if buffers.is_empty() {
+            gl::DrawBuffers(1, &gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+        } else {
+            gl::DrawBuffers(buffers.len() as i32, buffers.as_ptr());
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+        }
+    }
+}
+
+/// Checks the framebuffer `framebuffer_id` can be rendered into.
+fn check_complete(framebuffer_id: u32) -> Result<(), GLWError> {
+    let status = with_bound(framebuffer_id, || unsafe {
+        gl::CheckFramebufferStatus(gl::FRAMEBUFFER)
+    });
+    if status != gl::FRAMEBUFFER_COMPLETE {
+        Err(GLWErrorKind::FramebufferIncomplete(status.into()))?
+    }
+    Ok(())
+}
+
+/// Binds `framebuffer_id` with a `width` by `height` viewport, returning the
+/// previous viewport.
+fn bind_with_viewport(framebuffer_id: u32, width: u32, height: u32) -> [i32; 4] {
+    let mut viewport = [0; 4];
+    unsafe {
+        gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
+        gl::Viewport(0, 0, width as i32, height as i32);
+    }
+    viewport
+}
+
+/// Binds the default framebuffer and restores `viewport`.
+fn unbind_to_viewport([x, y, width, height]: [i32; 4]) {
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl::Viewport(x, y, width, height);
+    }
+}
+
+/// Reads `width` by `height` RGBA8 pixels from `read_buffer` of framebuffer
+/// `framebuffer_id`, bottom row first.
+pub(crate) fn read_rgba(framebuffer_id: u32, read_buffer: u32, width: u32, height: u32) -> Vec<u8> {
+    let mut pixels = vec![0; width as usize * height as usize * 4];
+    unsafe {
+        let mut previous = 0;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer_id);
+        gl::ReadBuffer(read_buffer);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr().cast(),
+        );
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous as u32);
+    }
+    pixels
+}
+
+/// Texture to use as a render target: no mipmaps, clamped, linear.
+pub(crate) fn render_target(width: u32, height: u32, format: TextureFormat) -> Texture2D {
+    Texture2D::builder()
+        .internal_format(format)
+        .wrap(WrapMode::ClampToEdge)
+        .min_filter(MinFilter::Linear)
+        .mag_filter(Filter::Linear)
+        .mipmaps(false)
+        .build_empty(width, height)
+}
+
+/// Offscreen render target owning the textures attached to it.
+pub struct Framebuffer {
+    framebuffer_id: u32,
+    width: u32,
+    height: u32,
+    color_textures: Vec<Texture2D>,
+    /// Fragment shader output written to each color attachment, if named.
+    color_names: Vec<Option<String>>,
+    depth_texture: Option<Texture2D>,
+    /// Viewport to restore on [`unbind`](Self::unbind).
+    previous_viewport: Cell<[i32; 4]>,
+}
+
+impl Framebuffer {
+    /// Creates a framebuffer without attachments, which is incomplete until
+    /// some are attached.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut framebuffer_id = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+        }
+        trace::event!(TRACE, framebuffer_id, width, height, "created framebuffer");
+
+        Self {
+            framebuffer_id,
+            width,
+            height,
+            color_textures: Vec::new(),
+            color_names: Vec::new(),
+            depth_texture: None,
+            previous_viewport: Cell::new([0; 4]),
+        }
+    }
+
+    /// Framebuffer with a color texture per fragment shader output in
+    /// `outputs` and a depth texture, e.g. a G-buffer for deferred shading.
+    /// The depth format suits the current [`DepthMode`].
+    /// Map the outputs with [`bind_outputs`](Self::bind_outputs).
+    pub fn with_outputs(
+        width: u32,
+        height: u32,
+        outputs: &[(&str, TextureFormat)],
+    ) -> Result<Self, GLWError> {
+        let depth_format = DepthMode::current().depth_format();
+        let mut framebuffer = Self::new(width, height);
+        for &(name, format) in outputs {
+            framebuffer.attach_named_color(name, render_target(width, height, format))?;
+        }
+        framebuffer.attach_depth(Texture2D::depth(width, height, depth_format))?;
+        framebuffer.check()?;
+        Ok(framebuffer)
+    }
+
+    /// Framebuffer with one color texture of `format` and a depth texture,
+    /// the usual target of a post-processing pass. The depth format suits
+    /// the current [`DepthMode`].
+    pub fn with_color(width: u32, height: u32, format: TextureFormat) -> Result<Self, GLWError> {
+        let depth_format = DepthMode::current().depth_format();
+        let mut framebuffer = Self::new(width, height);
+        framebuffer.attach_color(render_target(width, height, format))?;
+        framebuffer.attach_depth(Texture2D::depth(width, height, depth_format))?;
+        framebuffer.check()?;
+        Ok(framebuffer)
+    }
+
+    /// Depth only framebuffer rendering into a [`Texture2D::shadow_map`].
+    pub fn shadow_map(width: u32, height: u32) -> Result<Self, GLWError> {
+        let mut framebuffer = Self::new(width, height);
+        framebuffer.attach_depth(Texture2D::shadow_map(width, height))?;
+        framebuffer.check()?;
+        Ok(framebuffer)
+    }
+
+    /// Attaches `texture` as the next color attachment, returning its index,
+    /// which is the fragment shader output location writing it. Fails if the
+    /// texture size doesn't match the framebuffer's.
+    pub fn attach_color(&mut self, texture: Texture2D) -> Result<u32, GLWError> {
+        self.check_size(&texture)?;
+        let index = self.color_textures.len() as u32;
+        with_bound(self.framebuffer_id, || unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0 + index,
+                gl::TEXTURE_2D,
+                texture.id(),
+                0,
+            );
+        });
+        self.color_textures.push(texture);
+        self.color_names.push(None);
+        self.update_draw_buffers();
+        Ok(index)
+    }
+
+    /// Like [`attach_color`](Self::attach_color), for the fragment shader
+    /// output `name`.
+    pub fn attach_named_color(
+        &mut self,
+        name: impl Into<String>,
+        texture: Texture2D,
+    ) -> Result<u32, GLWError> {
+        let index = self.attach_color(texture)?;
+        self.color_names[index as usize] = Some(name.into());
+        Ok(index)
+    }
+
+    /// Routes each fragment shader output of `program` to the attachment
+    /// named after it, whatever its `layout(location)`. Unnamed attachments
+    /// are not written. Attaching more textures resets the mapping.
+    pub fn bind_outputs(&self, program: &ShaderProgram) -> Result<(), GLWError> {
+        let mut buffers = Vec::new();
+        for (index, name) in self.color_names.iter().enumerate() {
+            let Some(name) = name else { continue };
+            let location = program.fragment_output_location(name)? as usize;
+            if buffers.len() <= location {
+                buffers.resize(location + 1, gl::NONE);
+            }
+            buffers[location] = gl::COLOR_ATTACHMENT0 + index as u32;
+        }
+        with_bound(self.framebuffer_id, || unsafe {
+            gl::DrawBuffers(buffers.len() as i32, buffers.as_ptr());
+        });
+        Ok(())
+    }
+
+    /// Attaches `texture` as the depth attachment, or the depth-stencil one
+    /// for `Depth24Stencil8` textures, replacing the previous one. Fails if
+    /// the texture isn't a depth texture or its size doesn't match the
+    /// framebuffer's.
+    pub fn attach_depth(&mut self, texture: Texture2D) -> Result<(), GLWError> {
+        self.check_size(&texture)?;
+        let attachment = match texture.format() {
+            TextureFormat::Depth24Stencil8 => gl::DEPTH_STENCIL_ATTACHMENT,
+            format if format.is_depth() => gl::DEPTH_ATTACHMENT,
+            format => Err(GLWErrorKind::UnsupportedTextureFormat(format!(
+                "{format:?} is not a depth format"
+            )))?,
+        };
+        with_bound(self.framebuffer_id, || unsafe {
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture.id(), 0);
+        });
+        self.depth_texture = Some(texture);
+        self.update_draw_buffers();
+        Ok(())
+    }
+
+    /// Checks the framebuffer can be rendered into.
+    pub fn check(&self) -> Result<(), GLWError> {
+        check_complete(self.framebuffer_id)
+    }
+
+    /// Renders into the framebuffer from now on, with the viewport covering
+    /// it.
+    pub fn bind(&self) {
+        self.previous_viewport.set(bind_with_viewport(
+            self.framebuffer_id,
+            self.width,
+            self.height,
+        ));
+    }
+
+    /// Renders into the default framebuffer again, restoring the viewport
+    /// from before [`bind`](Self::bind).
+    pub fn unbind(&self) {
+        unbind_to_viewport(self.previous_viewport.get());
+    }
+
+    /// The color texture at attachment `index`.
+    pub fn color_texture(&self, index: usize) -> Option<&Texture2D> {
+        self.color_textures.get(index)
+    }
+
+    /// Reads color attachment `index` back as RGBA8, bottom row first.
+    pub fn read_pixels(&self, index: u32) -> Vec<u8> {
+        read_rgba(
+            self.framebuffer_id,
+            gl::COLOR_ATTACHMENT0 + index,
+            self.width,
+            self.height,
+        )
+    }
+
+    /// The color texture attached for output `name`.
+    pub fn color_texture_named(&self, name: &str) -> Option<&Texture2D> {
+        let index = self
+            .color_names
+            .iter()
+            .position(|attached| attached.as_deref() == Some(name))?;
+        self.color_textures.get(index)
+    }
+
+    pub fn depth_texture(&self) -> Option<&Texture2D> {
+        self.depth_texture.as_ref()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn id(&self) -> u32 {
+        self.framebuffer_id
+    }
+
+    fn check_size(&self, texture: &Texture2D) -> Result<(), GLWError> {
+        let found = (texture.width(), texture.height());
+        if found != (self.width, self.height) {
+            Err(GLWErrorKind::AttachmentSizeMismatch {
+                expected: (self.width, self.height),
+                found,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn update_draw_buffers(&self) {
+        with_bound(self.framebuffer_id, || {
+            set_draw_buffers(self.color_textures.len() as u32)
+        });
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        trace::event!(
+            TRACE,
+            framebuffer_id = self.framebuffer_id,
+            "deleting framebuffer"
+        );
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer_id);
+        }
+    }
+}
+
+/// Framebuffer rendering into multisampled attachments, which are resolved
+/// into a regular [`Framebuffer`] (or the screen) to be sampled or shown.
+pub struct MsaaFramebuffer {
+    framebuffer_id: u32,
+    width: u32,
+    height: u32,
+    samples: u32,
+    color_textures: Vec<Texture2DMultisample>,
+    depth_texture: Option<Texture2DMultisample>,
+    previous_viewport: Cell<[i32; 4]>,
+}
+
+impl MsaaFramebuffer {
+    /// Creates one multisampled color attachment per entry of
+    /// `color_formats`, and a depth attachment if `depth_format` is given.
+    pub fn new(
+        width: u32,
+        height: u32,
+        samples: u32,
+        color_formats: &[TextureFormat],
+        depth_format: Option<TextureFormat>,
+    ) -> Result<Self, GLWError> {
+        let color_textures = color_formats
+            .iter()
+            .map(|&format| Texture2DMultisample::new(width, height, samples, format))
+            .collect::<Result<Vec<_>, _>>()?;
+        let depth_texture = depth_format
+            .map(|format| Texture2DMultisample::new(width, height, samples, format))
+            .transpose()?;
+
+        let mut framebuffer_id = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+        }
+        with_bound(framebuffer_id, || {
+            for (index, texture) in color_textures.iter().enumerate() {
+                texture.attach(gl::COLOR_ATTACHMENT0 + index as u32);
+            }
+            if let Some(texture) = &depth_texture {
+                texture.attach(match texture.format() {
+                    TextureFormat::Depth24Stencil8 => gl::DEPTH_STENCIL_ATTACHMENT,
+                    _ => gl::DEPTH_ATTACHMENT,
+                });
+            }
+            set_draw_buffers(color_textures.len() as u32);
+        });
+
+        let framebuffer = Self {
+            framebuffer_id,
+            width,
+            height,
+            samples,
+            color_textures,
+            depth_texture,
+            previous_viewport: Cell::new([0; 4]),
+        };
+        check_complete(framebuffer_id)?;
+        Ok(framebuffer)
+    }
+
+    /// Renders into the framebuffer from now on, with the viewport covering
+    /// it.
+    pub fn bind(&self) {
+        self.previous_viewport.set(bind_with_viewport(
+            self.framebuffer_id,
+            self.width,
+            self.height,
+        ));
+    }
+
+    /// Renders into the default framebuffer again, restoring the viewport
+    /// from before [`bind`](Self::bind).
+    pub fn unbind(&self) {
+        unbind_to_viewport(self.previous_viewport.get());
+    }
+
+    /// Resolves every color attachment into the matching attachment of
+    /// `target`, and depth if both have it.
+    ///
+    /// # Panics
+    /// If the sizes differ, multisampled blits can't scale.
+    pub fn resolve_to(&self, target: &Framebuffer) {
+        assert_eq!(
+            (self.width, self.height),
+            (target.width(), target.height()),
+            "resolve target size doesn't match the framebuffer"
+        );
+        let color_count = self.color_textures.len().min(target.color_textures.len());
+        let resolve_depth = self.depth_texture.is_some() && target.depth_texture().is_some();
+
+        self.blit(target.id(), color_count as u32, resolve_depth);
+        with_bound(target.id(), || {
+            set_draw_buffers(target.color_textures.len() as u32)
+        });
+    }
+
+    /// Resolves the first color attachment onto the default framebuffer,
+    /// which must have the same size.
+    pub fn resolve_to_default(&self) {
+        self.blit(0, self.color_textures.len().min(1) as u32, false);
+    }
+
+    fn blit(&self, target_id: u32, color_count: u32, resolve_depth: bool) {
+        let (width, height) = (self.width as i32, self.height as i32);
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer_id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target_id);
+            for index in 0..color_count {
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + index);
+                if target_id == 0 {
+                    gl::DrawBuffers(1, &gl::BACK);
+                } else {
+                    gl::DrawBuffers(1, &(gl::COLOR_ATTACHMENT0 + index));
+                }
+                gl::BlitFramebuffer(
+                    0,
+                    0,
+                    width,
+                    height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    gl::COLOR_BUFFER_BIT,
+                    gl::NEAREST,
+                );
+            }
+            if resolve_depth {
+                gl::BlitFramebuffer(
+                    0,
+                    0,
+                    width,
+                    height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    gl::DEPTH_BUFFER_BIT,
+                    gl::NEAREST,
+                );
+            }
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    pub fn color_texture(&self, index: usize) -> Option<&Texture2DMultisample> {
+        self.color_textures.get(index)
+    }
+
+    pub fn depth_texture(&self) -> Option<&Texture2DMultisample> {
+        self.depth_texture.as_ref()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn id(&self) -> u32 {
+        self.framebuffer_id
+    }
+}
+
+impl Drop for MsaaFramebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer_id);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3, Vector4};
+
+use crate::{
+    bounds::{Aabb, Sphere},
+    context::DepthMode,
+};
+
+/// Plane of points `p` with `normal.dot(p) + distance == 0`, the normal
+/// pointing inside the frustum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    fn from_coefficients(coefficients: Vector4<f32>) -> Self {
+        let normal = coefficients.truncate();
+        let length = normal.magnitude();
+        // the far plane of an infinite projection, containing everything
+        if length == 0.0 {
+            return Self {
+                normal,
+                distance: 1.0,
+            };
+        }
+        Self {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        }
+    }
+
+    /// Signed distance of `point`, positive on the inner side.
+    pub fn distance_to(&self, point: Point3<f32>) -> f32 {
+        self.normal.dot(Vector3::new(point.x, point.y, point.z)) + self.distance
+    }
+}
+
+/// The volume a view-projection matrix sees, for skipping objects outside
+/// of it before drawing. Tests are conservative: they may report objects
+/// near the corners as visible, never visible ones as outside.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near and far.
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes of `view_projection`, `projection * view` for a
+    /// world space frustum. Expects GL's -1..1 depth range.
+    pub fn from_matrix(view_projection: &Matrix4<f32>) -> Self {
+        Self::from_matrix_with_depth(view_projection, DepthMode::Standard)
+    }
+
+    /// [`from_matrix`](Self::from_matrix) for a projection made for
+    /// `depth_mode`.
+    pub fn from_matrix_with_depth(view_projection: &Matrix4<f32>, depth_mode: DepthMode) -> Self {
+        let m = view_projection;
+        let row = |i: usize| Vector4::new(m.x[i], m.y[i], m.z[i], m.w[i]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let (near, far) = match depth_mode {
+            DepthMode::Standard => (w + z, w - z),
+            DepthMode::ReverseZ => (w - z, z),
+        };
+        Self {
+            planes: [w + x, w - x, w + y, w - y, near, far].map(Plane::from_coefficients),
+        }
+    }
+
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.distance_to(point) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.distance_to(sphere.center) >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner furthest along the normal
+            let corner = Point3::new(
+                if plane.normal.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.normal.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.normal.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            plane.distance_to(corner) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Deg;
+
+    use super::*;
+    use crate::projection;
+
+    /// Frustum of a camera at the origin looking down -z, near 1 and far 10.
+    fn frustum(depth_mode: DepthMode) -> Frustum {
+        let projection = match depth_mode {
+            DepthMode::Standard => projection::perspective(Deg(90.0), 1.0, 1.0, 10.0),
+            DepthMode::ReverseZ => projection::perspective_reverse_z(Deg(90.0), 1.0, 1.0, 10.0),
+        };
+        Frustum::from_matrix_with_depth(&projection, depth_mode)
+    }
+
+    #[test]
+    fn contains_points_between_the_planes() {
+        for depth_mode in [DepthMode::Standard, DepthMode::ReverseZ] {
+            let frustum = frustum(depth_mode);
+            assert!(frustum.contains_point(Point3::new(0.0, 0.0, -5.0)));
+            assert!(frustum.contains_point(Point3::new(4.9, -4.9, -5.0)));
+            assert!(!frustum.contains_point(Point3::new(0.0, 0.0, -0.5)));
+            assert!(!frustum.contains_point(Point3::new(0.0, 0.0, -10.5)));
+            assert!(!frustum.contains_point(Point3::new(5.1, 0.0, -5.0)));
+            assert!(!frustum.contains_point(Point3::new(0.0, 0.0, 5.0)));
+        }
+    }
+
+    #[test]
+    fn depth_planes_sit_at_near_and_far() {
+        for depth_mode in [DepthMode::Standard, DepthMode::ReverseZ] {
+            let [.., near, far] = frustum(depth_mode).planes;
+            assert!(near.distance_to(Point3::new(0.0, 0.0, -1.0)).abs() < 1e-4);
+            assert!(far.distance_to(Point3::new(0.0, 0.0, -10.0)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn infinite_projections_have_no_far_plane() {
+        let projection = projection::infinite_perspective_reverse_z(Deg(90.0), 1.0, 1.0);
+        let frustum = Frustum::from_matrix_with_depth(&projection, DepthMode::ReverseZ);
+        assert!(frustum.contains_point(Point3::new(0.0, 0.0, -1e6)));
+    }
+
+    #[test]
+    fn tests_spheres_and_boxes_conservatively() {
+        let frustum = frustum(DepthMode::Standard);
+        let sphere = |z| Sphere::new(Point3::new(0.0, 0.0, z), 1.0);
+        assert!(frustum.intersects_sphere(&sphere(-5.0)));
+        assert!(frustum.intersects_sphere(&sphere(-0.5)));
+        assert!(!frustum.intersects_sphere(&sphere(2.5)));
+
+        let aabb = |x| Aabb::new(Point3::new(x, -1.0, -6.0), Point3::new(x + 2.0, 1.0, -4.0));
+        assert!(frustum.intersects_aabb(&aabb(-1.0)));
+        assert!(frustum.intersects_aabb(&aabb(5.0)));
+        assert!(!frustum.intersects_aabb(&aabb(7.0)));
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    ffi::{c_int, c_uchar},
+};
+
+use glfw::JoystickId;
+
+extern "C" {
+    // part of GLFW 3.3, which the glfw crate links but doesn't wrap
+    fn glfwGetJoystickHats(jid: c_int, count: *mut c_int) -> *const c_uchar;
+}
+
+/// Bits of a hat (d-pad) state.
+pub const HAT_UP: u8 = 1;
+pub const HAT_RIGHT: u8 = 2;
+pub const HAT_DOWN: u8 = 4;
+pub const HAT_LEFT: u8 = 8;
+
+const JOYSTICKS: [JoystickId; 16] = [
+    JoystickId::Joystick1,
+    JoystickId::Joystick2,
+    JoystickId::Joystick3,
+    JoystickId::Joystick4,
+    JoystickId::Joystick5,
+    JoystickId::Joystick6,
+    JoystickId::Joystick7,
+    JoystickId::Joystick8,
+    JoystickId::Joystick9,
+    JoystickId::Joystick10,
+    JoystickId::Joystick11,
+    JoystickId::Joystick12,
+    JoystickId::Joystick13,
+    JoystickId::Joystick14,
+    JoystickId::Joystick15,
+    JoystickId::Joystick16,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadEvent {
+    Connected(JoystickId),
+    Disconnected(JoystickId),
+}
+
+/// State of one joystick, as of the last poll. Axis, button and hat indices
+/// are the device's own, GLFW doesn't map them to a standard layout here.
+#[derive(Debug, Default, Clone)]
+pub struct GamepadState {
+    pub name: String,
+    axes: Vec<f32>,
+    buttons: Vec<bool>,
+    previous_buttons: Vec<bool>,
+    hats: Vec<u8>,
+}
+
+impl GamepadState {
+    /// Value of `axis` in `[-1, 1]` with the dead zone applied, 0 if the
+    /// device doesn't have it.
+    pub fn axis(&self, axis: usize) -> f32 {
+        self.axes.get(axis).copied().unwrap_or(0.0)
+    }
+
+    pub fn axis_count(&self) -> usize {
+        self.axes.len()
+    }
+
+    pub fn is_pressed(&self, button: usize) -> bool {
+        self.buttons.get(button).copied().unwrap_or(false)
+    }
+
+    /// Whether `button` went down since the previous poll.
+    pub fn just_pressed(&self, button: usize) -> bool {
+        self.is_pressed(button) && !self.previous_buttons.get(button).copied().unwrap_or(false)
+    }
+
+    /// Whether `button` went up since the previous poll.
+    pub fn just_released(&self, button: usize) -> bool {
+        !self.is_pressed(button) && self.previous_buttons.get(button).copied().unwrap_or(false)
+    }
+
+    pub fn button_count(&self) -> usize {
+        self.buttons.len()
+    }
+
+    /// `HAT_*` bits of `hat`; diagonals set two of them.
+    pub fn hat(&self, hat: usize) -> u8 {
+        self.hats.get(hat).copied().unwrap_or(0)
+    }
+
+    pub fn hat_count(&self) -> usize {
+        self.hats.len()
+    }
+}
+
+/// Every connected joystick, polled once per frame.
+#[derive(Debug, Clone)]
+pub struct Gamepads {
+    states: HashMap<JoystickId, GamepadState>,
+    events: Vec<GamepadEvent>,
+    dead_zone: f32,
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new(0.15)
+    }
+}
+
+impl Gamepads {
+    /// Axis values within `dead_zone` of the center read as 0, the rest is
+    /// rescaled to still cover `[-1, 1]`.
+    pub fn new(dead_zone: f32) -> Self {
+        Self {
+            states: HashMap::new(),
+            events: Vec::new(),
+            dead_zone,
+        }
+    }
+
+    pub fn set_dead_zone(&mut self, dead_zone: f32) {
+        self.dead_zone = dead_zone;
+    }
+
+    /// Reads the state of every joystick, noting connections and
+    /// disconnections since the previous poll.
+    pub fn poll(&mut self, glfw: &glfw::Glfw) {
+        self.events.clear();
+        for id in JOYSTICKS {
+            let joystick = glfw.get_joystick(id);
+            if !joystick.is_present() {
+                if self.states.remove(&id).is_some() {
+                    self.events.push(GamepadEvent::Disconnected(id));
+                }
+                continue;
+            }
+
+            let state = self.states.entry(id).or_insert_with(|| {
+                self.events.push(GamepadEvent::Connected(id));
+                GamepadState {
+                    name: joystick.get_name(),
+                    ..GamepadState::default()
+                }
+            });
+            state.axes = joystick
+                .get_axes()
+                .into_iter()
+                .map(|value| apply_dead_zone(value, self.dead_zone))
+                .collect();
+            state.previous_buttons = std::mem::take(&mut state.buttons);
+            state.buttons = joystick
+                .get_buttons()
+                .into_iter()
+                .map(|state| state == glfw::ffi::PRESS)
+                .collect();
+            state.hats = unsafe {
+                let mut count = 0;
+                let hats = glfwGetJoystickHats(id as c_int, &mut count);
+                if hats.is_null() {
+                    Vec::new()
+                } else {
+                    std::slice::from_raw_parts(hats, count as usize).to_vec()
+                }
+            };
+        }
+    }
+
+    /// Connections and disconnections found by the last poll.
+    pub fn events(&self) -> &[GamepadEvent] {
+        &self.events
+    }
+
+    pub fn get(&self, id: JoystickId) -> Option<&GamepadState> {
+        self.states.get(&id)
+    }
+
+    /// The connected joysticks, in no particular order.
+    pub fn connected(&self) -> impl Iterator<Item = (JoystickId, &GamepadState)> {
+        self.states.iter().map(|(&id, state)| (id, state))
+    }
+}
+
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() < dead_zone {
+        0.0
+    } else {
+        value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+    }
+}
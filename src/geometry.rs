@@ -0,0 +1,305 @@
+use std::f32::consts::{PI, TAU};
+
+use cgmath::Vector3;
+
+use crate::{
+    bounds::{Aabb, Sphere},
+    lod,
+    mesh::Mesh,
+    model::ModelVertex,
+    normals, optimize,
+};
+
+/// Indexed triangles on the CPU, to generate or process before uploading
+/// them as a [`Mesh`].
+///
+/// The shapes are centered on the origin and a unit across, wind
+/// counter-clockwise seen from outside and have `v` pointing up on sides
+/// and towards `-Z` on horizontal faces.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Geometry {
+    pub vertices: Vec<ModelVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl Geometry {
+    pub fn new(vertices: Vec<ModelVertex>, indices: Vec<u32>) -> Self {
+        Self { vertices, indices }
+    }
+
+    /// Cube with a side of 1 and 24 vertices, so every face has its own
+    /// normals and full texture.
+    pub fn cube() -> Self {
+        // normal, then the face's right and up directions
+        let faces = [
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ];
+        let mut geometry = Self::default();
+        for (normal, right, up) in faces {
+            let (normal, right, up) = (
+                Vector3::from(normal),
+                Vector3::from(right),
+                Vector3::from(up),
+            );
+            let first = geometry.vertices.len() as u32;
+            for (u, v) in [(0.0f32, 0.0f32), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                let position = (normal + right * (u * 2.0 - 1.0) + up * (v * 2.0 - 1.0)) * 0.5;
+                geometry.vertices.push(ModelVertex {
+                    position: position.into(),
+                    normal: normal.into(),
+                    tex_coords: [u, v],
+                    tangent: [0.0; 4],
+                });
+            }
+            geometry
+                .indices
+                .extend([0, 1, 2, 0, 2, 3].map(|index| first + index));
+        }
+        geometry.generate_tangents();
+        geometry
+    }
+
+    /// UV sphere with a radius of 0.5, `rings` bands from pole to pole and
+    /// `sectors` around. The texture wraps around once, its seam at `+X`.
+    pub fn sphere(rings: u32, sectors: u32) -> Self {
+        let (rings, sectors) = (rings.max(2), sectors.max(3));
+        let mut geometry = Self::default();
+        for ring in 0..=rings {
+            let phi = PI * ring as f32 / rings as f32;
+            for sector in 0..=sectors {
+                let theta = TAU * sector as f32 / sectors as f32;
+                let normal = [phi.sin() * theta.cos(), phi.cos(), -phi.sin() * theta.sin()];
+                geometry.vertices.push(ModelVertex {
+                    position: normal.map(|component| component * 0.5),
+                    normal,
+                    tex_coords: [
+                        sector as f32 / sectors as f32,
+                        1.0 - ring as f32 / rings as f32,
+                    ],
+                    tangent: [0.0; 4],
+                });
+            }
+        }
+        for ring in 0..rings {
+            for sector in 0..sectors {
+                let [a, b, c, d] = grid_quad(ring, sector, sectors);
+                // the triangles touching the poles would be degenerate
+                if ring != rings - 1 {
+                    geometry.indices.extend([a, b, c]);
+                }
+                if ring != 0 {
+                    geometry.indices.extend([a, c, d]);
+                }
+            }
+        }
+        geometry.generate_tangents();
+        geometry
+    }
+
+    /// Square with a side of 1 in the XZ plane facing `+Y`, split into
+    /// `subdivisions` quads along each side, e.g. for displacing.
+    pub fn plane(subdivisions: u32) -> Self {
+        let quads = subdivisions.max(1);
+        let mut geometry = Self::default();
+        for row in 0..=quads {
+            let v = row as f32 / quads as f32;
+            for column in 0..=quads {
+                let u = column as f32 / quads as f32;
+                geometry.vertices.push(ModelVertex {
+                    position: [u - 0.5, 0.0, 0.5 - v],
+                    normal: [0.0, 1.0, 0.0],
+                    tex_coords: [u, v],
+                    tangent: [0.0; 4],
+                });
+            }
+        }
+        for row in 0..quads {
+            for column in 0..quads {
+                let [a, b, c, d] = grid_quad(row, column, quads);
+                geometry.indices.extend([a, c, b, a, d, c]);
+            }
+        }
+        geometry.generate_tangents();
+        geometry
+    }
+
+    /// Capped cylinder along `Y` with a radius of 0.5 and a height of 1,
+    /// made of `sectors` sides. The caps are textured with a disc.
+    pub fn cylinder(sectors: u32) -> Self {
+        let sectors = sectors.max(3);
+        let angle = |sector: u32| TAU * sector as f32 / sectors as f32;
+        let mut geometry = Self::default();
+        for (row, y) in [-0.5, 0.5].into_iter().enumerate() {
+            for sector in 0..=sectors {
+                let (sin, cos) = angle(sector).sin_cos();
+                geometry.vertices.push(ModelVertex {
+                    position: [cos * 0.5, y, -sin * 0.5],
+                    normal: [cos, 0.0, -sin],
+                    tex_coords: [sector as f32 / sectors as f32, row as f32],
+                    tangent: [0.0; 4],
+                });
+            }
+        }
+        for sector in 0..sectors {
+            let [a, b, c, d] = grid_quad(0, sector, sectors);
+            geometry.indices.extend([a, c, b, a, d, c]);
+        }
+
+        for y in [-0.5f32, 0.5] {
+            let center = geometry.vertices.len() as u32;
+            geometry.vertices.push(ModelVertex {
+                position: [0.0, y, 0.0],
+                normal: [0.0, y.signum(), 0.0],
+                tex_coords: [0.5, 0.5],
+                tangent: [0.0; 4],
+            });
+            for sector in 0..sectors {
+                let (sin, cos) = angle(sector).sin_cos();
+                geometry.vertices.push(ModelVertex {
+                    position: [cos * 0.5, y, -sin * 0.5],
+                    normal: [0.0, y.signum(), 0.0],
+                    // seen from above on top, from below on the bottom
+                    tex_coords: [0.5 + cos * 0.5, 0.5 + sin * 0.5 * y.signum()],
+                    tangent: [0.0; 4],
+                });
+            }
+            for sector in 0..sectors {
+                let (current, next) = (center + 1 + sector, center + 1 + (sector + 1) % sectors);
+                geometry.indices.extend(match y > 0.0 {
+                    true => [center, current, next],
+                    false => [center, next, current],
+                });
+            }
+        }
+        geometry.generate_tangents();
+        geometry
+    }
+
+    /// Torus around `Y`, its tube of `tube_radius` circling 0.5 from the
+    /// center, with `rings` segments around the center and `sectors`
+    /// around the tube.
+    pub fn torus(rings: u32, sectors: u32, tube_radius: f32) -> Self {
+        let (rings, sectors) = (rings.max(3), sectors.max(3));
+        let mut geometry = Self::default();
+        for ring in 0..=rings {
+            let (sin_theta, cos_theta) = (TAU * ring as f32 / rings as f32).sin_cos();
+            let center = Vector3::new(cos_theta * 0.5, 0.0, -sin_theta * 0.5);
+            for sector in 0..=sectors {
+                let (sin_phi, cos_phi) = (TAU * sector as f32 / sectors as f32).sin_cos();
+                let normal = Vector3::new(cos_phi * cos_theta, sin_phi, -cos_phi * sin_theta);
+                geometry.vertices.push(ModelVertex {
+                    position: (center + normal * tube_radius).into(),
+                    normal: normal.into(),
+                    tex_coords: [ring as f32 / rings as f32, sector as f32 / sectors as f32],
+                    tangent: [0.0; 4],
+                });
+            }
+        }
+        for ring in 0..rings {
+            for sector in 0..sectors {
+                let [a, b, c, d] = grid_quad(ring, sector, sectors);
+                geometry.indices.extend([a, b, c, a, c, d]);
+            }
+        }
+        geometry.generate_tangents();
+        geometry
+    }
+
+    /// Replaces the normals with [smooth ones](normals::smooth_normals).
+    pub fn generate_normals(&mut self) {
+        let positions: Vec<_> = self.vertices.iter().map(|vertex| vertex.position).collect();
+        let normals = normals::smooth_normals(&positions, &self.indices);
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = normal;
+        }
+    }
+
+    /// Replaces the tangents with ones [generated](normals::tangents) from
+    /// the normals and texture coordinates.
+    pub fn generate_tangents(&mut self) {
+        let positions: Vec<_> = self.vertices.iter().map(|vertex| vertex.position).collect();
+        let normals: Vec<_> = self.vertices.iter().map(|vertex| vertex.normal).collect();
+        let tex_coords: Vec<_> = self
+            .vertices
+            .iter()
+            .map(|vertex| vertex.tex_coords)
+            .collect();
+        let tangents = normals::tangents(&positions, &normals, &tex_coords, &self.indices);
+        for (vertex, tangent) in self.vertices.iter_mut().zip(tangents) {
+            vertex.tangent = tangent;
+        }
+    }
+
+    /// Welds identical vertices and reorders both for drawing, see
+    /// [`optimize::optimize`].
+    pub fn optimize(&mut self) {
+        optimize::optimize(
+            &mut self.vertices,
+            &mut self.indices,
+            ModelVertex::to_bits,
+            |vertex| vertex.position,
+        );
+    }
+
+    /// Coarser copy with about `ratio` of the triangles, see
+    /// [`lod::simplify`].
+    pub fn simplified(&self, ratio: f32) -> Self {
+        let positions: Vec<_> = self.vertices.iter().map(|vertex| vertex.position).collect();
+        let indices = lod::simplify(&positions, &self.indices, ratio);
+        let mut geometry = Self::new(self.vertices.clone(), indices);
+        geometry.optimize();
+        geometry
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().map(|vertex| vertex.position.into()))
+    }
+
+    pub fn bounding_sphere(&self) -> Option<Sphere> {
+        Sphere::from_points(self.vertices.iter().map(|vertex| vertex.position.into()))
+    }
+
+    /// Uploads the triangles as an indexed mesh with its bounds.
+    pub fn to_mesh(&self) -> Mesh<ModelVertex> {
+        Mesh::from_vertices(&self.vertices, Some(&self.indices))
+            .with_bounds_of(self.vertices.iter().map(|vertex| vertex.position))
+    }
+}
+
+/// Corners of the quad at `row` and `column` of a grid of vertices
+/// `columns + 1` wide, going from the row to the next one and back along
+/// the next column.
+fn grid_quad(row: u32, column: u32, columns: u32) -> [u32; 4] {
+    let width = columns + 1;
+    let a = row * width + column;
+    let d = (row + 1) * width + column;
+    [a, d, d + 1, a + 1]
+}
+
+/// Generated shapes, see [`Geometry`] for their size and texturing.
+impl Mesh<ModelVertex> {
+    pub fn cube() -> Self {
+        Geometry::cube().to_mesh()
+    }
+
+    pub fn sphere(rings: u32, sectors: u32) -> Self {
+        Geometry::sphere(rings, sectors).to_mesh()
+    }
+
+    pub fn plane(subdivisions: u32) -> Self {
+        Geometry::plane(subdivisions).to_mesh()
+    }
+
+    pub fn cylinder(sectors: u32) -> Self {
+        Geometry::cylinder(sectors).to_mesh()
+    }
+
+    pub fn torus(rings: u32, sectors: u32, tube_radius: f32) -> Self {
+        Geometry::torus(rings, sectors, tube_radius).to_mesh()
+    }
+}
@@ -0,0 +1,609 @@
+use std::{collections::HashMap, path::Path, rc::Rc};
+
+use cgmath::{Matrix4, Quaternion, SquareMatrix, Vector3};
+use gltf::{
+    animation::util::ReadOutputs,
+    image::Format,
+    mesh::Mode,
+    texture::{MagFilter, MinFilter as GltfMinFilter, WrappingMode},
+};
+
+use crate::{
+    animation::{AnimationChannel, AnimationClip, Interpolation, Keyframes},
+    bounds::Aabb,
+    draw::Primitive,
+    errors::{GLWError, GLWErrorKind},
+    material::{skip_missing, AlphaMode, Material},
+    mesh::Mesh,
+    normals,
+    scene::{Drawable, Node, NodeId, Scene},
+    shader::ShaderProgram,
+    skinning::{Joint, JointPalette, Skeleton},
+    texture::{ColorSpace, Filter, MinFilter, Texture2D, WrapMode},
+    transform::Transform,
+    vertex::{Vertex, VertexLayout},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct GltfVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coords: [f32; 2],
+    /// Tangent with the bitangent's sign in `w`, generated if the file has
+    /// none, zero without texture coordinates.
+    pub tangent: [f32; 4],
+    /// Indices into the skin's joints, zero for unskinned meshes.
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+impl Vertex for GltfVertex {
+    fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .push_field::<[f32; 3]>(std::mem::offset_of!(Self, position), false)
+            .push_field::<[f32; 3]>(std::mem::offset_of!(Self, normal), false)
+            .push_field::<[f32; 2]>(std::mem::offset_of!(Self, tex_coords), false)
+            .push_field::<[f32; 4]>(std::mem::offset_of!(Self, tangent), false)
+            .push_field::<[u32; 4]>(std::mem::offset_of!(Self, joints), false)
+            .push_field::<[f32; 4]>(std::mem::offset_of!(Self, weights), false)
+            .with_stride(std::mem::size_of::<Self>())
+    }
+}
+
+pub struct GltfMesh {
+    pub name: Option<String>,
+    /// One mesh per primitive, each with its material.
+    pub primitives: Vec<Rc<Mesh<GltfVertex>>>,
+}
+
+pub struct GltfNode {
+    pub name: Option<String>,
+    /// Relative to the parent node.
+    pub transform: Transform,
+    /// Index into [`GltfModel::meshes`].
+    pub mesh: Option<usize>,
+    /// Index into [`GltfModel::skins`] the mesh is skinned with.
+    pub skin: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+/// Joints of a glTF skin, nodes of the model.
+pub struct GltfSkin {
+    pub name: Option<String>,
+    /// Indices into [`GltfModel::nodes`], as the vertices' joint indices
+    /// refer to them.
+    pub joints: Vec<usize>,
+    pub inverse_bind_matrices: Vec<Matrix4<f32>>,
+}
+
+/// Meshes, materials, node hierarchy and animations of a glTF 2.0 file.
+pub struct GltfModel {
+    pub meshes: Vec<GltfMesh>,
+    /// Metallic-roughness materials, without a program, shared with the
+    /// primitives using them.
+    pub materials: Vec<Rc<Material>>,
+    pub nodes: Vec<GltfNode>,
+    /// Root nodes of the default scene, or of the first one.
+    pub roots: Vec<usize>,
+    pub skins: Vec<GltfSkin>,
+    /// Clips targeting nodes by their index.
+    pub animations: Vec<AnimationClip>,
+}
+
+impl GltfModel {
+    /// Imports a `.gltf` or `.glb` file with its buffers and images.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        let path = path.as_ref();
+        let (document, buffers, images) =
+            gltf::import(path).map_err(|source| GLWErrorKind::GltfLoadFailed {
+                path: path.to_path_buf(),
+                source: Box::new(source),
+            })?;
+        let buffer_data = |buffer: gltf::Buffer| buffers.get(buffer.index()).map(|data| &**data);
+
+        let mut textures = HashMap::new();
+        let mut texture = |info: Option<gltf::Texture>, color_space| -> Result<_, GLWError> {
+            let Some(info) = info else {
+                return Ok(None);
+            };
+            let key = (info.index(), color_space == ColorSpace::Srgb);
+            if let Some(texture) = textures.get(&key) {
+                return Ok(Some(Rc::clone(texture)));
+            }
+            let image = &images[info.source().index()];
+            let texture = Rc::new(load_texture(&info, image, color_space)?);
+            textures.insert(key, Rc::clone(&texture));
+            Ok(Some(texture))
+        };
+
+        let mut materials = Vec::new();
+        for material in document.materials() {
+            let pbr = material.pbr_metallic_roughness();
+            let mut pbr_material = Material::without_program()
+                .with_param("material.baseColorFactor", pbr.base_color_factor())
+                .with_param("material.metallicFactor", pbr.metallic_factor())
+                .with_param("material.roughnessFactor", pbr.roughness_factor())
+                .with_param("material.emissiveFactor", material.emissive_factor())
+                .with_alpha_mode(match material.alpha_mode() {
+                    gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                    gltf::material::AlphaMode::Mask => {
+                        AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5))
+                    }
+                    gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+                })
+                .with_double_sided(material.double_sided());
+            pbr_material.name = material.name().map(str::to_string);
+
+            let normal = material.normal_texture();
+            let occlusion = material.occlusion_texture();
+            pbr_material.set_param(
+                "material.normalScale",
+                normal.as_ref().map_or(1.0, |normal| normal.scale()),
+            );
+            pbr_material.set_param(
+                "material.occlusionStrength",
+                occlusion
+                    .as_ref()
+                    .map_or(1.0, |occlusion| occlusion.strength()),
+            );
+            // roughness is in green and metalness in blue
+            let slots = [
+                (
+                    "material.baseColor",
+                    pbr.base_color_texture().map(|info| info.texture()),
+                    ColorSpace::Srgb,
+                ),
+                (
+                    "material.metallicRoughness",
+                    pbr.metallic_roughness_texture().map(|info| info.texture()),
+                    ColorSpace::Linear,
+                ),
+                (
+                    "material.normal",
+                    normal.map(|normal| normal.texture()),
+                    ColorSpace::Linear,
+                ),
+                (
+                    "material.occlusion",
+                    occlusion.map(|occlusion| occlusion.texture()),
+                    ColorSpace::Linear,
+                ),
+                (
+                    "material.emissive",
+                    material.emissive_texture().map(|info| info.texture()),
+                    ColorSpace::Srgb,
+                ),
+            ];
+            for (slot, info, color_space) in slots {
+                if let Some(texture) = texture(info, color_space)? {
+                    pbr_material.set_texture(slot, texture);
+                }
+            }
+            materials.push(Rc::new(pbr_material));
+        }
+
+        let meshes = document
+            .meshes()
+            .map(|mesh| GltfMesh {
+                name: mesh.name().map(str::to_string),
+                primitives: mesh
+                    .primitives()
+                    .filter_map(|primitive| {
+                        let reader = primitive.reader(buffer_data);
+                        let positions: Vec<[f32; 3]> = reader.read_positions()?.collect();
+                        let indices: Option<Vec<u32>> = reader
+                            .read_indices()
+                            .map(|indices| indices.into_u32().collect());
+                        let triangles = indices
+                            .clone()
+                            .unwrap_or_else(|| (0..positions.len() as u32).collect());
+                        let normals: Vec<[f32; 3]> = match reader.read_normals() {
+                            Some(normals) => normals.collect(),
+                            None => normals::smooth_normals(&positions, &triangles),
+                        };
+                        let tex_coords: Vec<[f32; 2]> = reader
+                            .read_tex_coords(0)
+                            .map(|coords| coords.into_f32().collect())
+                            .unwrap_or_default();
+                        // only textured meshes can be normal mapped
+                        let tangents: Vec<[f32; 4]> = match reader.read_tangents() {
+                            Some(tangents) => tangents.collect(),
+                            None if !tex_coords.is_empty() => {
+                                normals::tangents(&positions, &normals, &tex_coords, &triangles)
+                            }
+                            None => Vec::new(),
+                        };
+                        let joints: Vec<[u16; 4]> = reader
+                            .read_joints(0)
+                            .map(|joints| joints.into_u16().collect())
+                            .unwrap_or_default();
+                        let weights: Vec<[f32; 4]> = reader
+                            .read_weights(0)
+                            .map(|weights| weights.into_f32().collect())
+                            .unwrap_or_default();
+
+                        let vertices: Vec<_> = (0..positions.len())
+                            .map(|i| GltfVertex {
+                                position: positions[i],
+                                normal: normals[i],
+                                tex_coords: tex_coords.get(i).copied().unwrap_or_default(),
+                                tangent: tangents.get(i).copied().unwrap_or_default(),
+                                joints: joints.get(i).map_or([0; 4], |j| j.map(u32::from)),
+                                weights: weights.get(i).copied().unwrap_or_default(),
+                            })
+                            .collect();
+                        let mesh = Mesh::from_vertices(&vertices, indices.as_deref())
+                            .with_primitive(primitive_of(primitive.mode()))
+                            .with_bounds_of(positions.iter().copied());
+                        Some(Rc::new(match primitive.material().index() {
+                            Some(index) => mesh.with_material(Rc::clone(&materials[index])),
+                            None => mesh,
+                        }))
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let nodes = document
+            .nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                let [x, y, z, w] = rotation;
+                GltfNode {
+                    name: node.name().map(str::to_string),
+                    transform: Transform {
+                        translation: translation.into(),
+                        rotation: Quaternion::new(w, x, y, z),
+                        scale: scale.into(),
+                    },
+                    mesh: node.mesh().map(|mesh| mesh.index()),
+                    skin: node.skin().map(|skin| skin.index()),
+                    children: node.children().map(|child| child.index()).collect(),
+                }
+            })
+            .collect();
+
+        let roots = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| scene.nodes().map(|node| node.index()).collect())
+            .unwrap_or_default();
+
+        let skins = document
+            .skins()
+            .map(|skin| {
+                let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+                let inverse_bind_matrices =
+                    match skin.reader(buffer_data).read_inverse_bind_matrices() {
+                        Some(matrices) => matrices.map(Matrix4::from).collect(),
+                        None => vec![Matrix4::identity(); joints.len()],
+                    };
+                GltfSkin {
+                    name: skin.name().map(str::to_string),
+                    joints,
+                    inverse_bind_matrices,
+                }
+            })
+            .collect();
+
+        let animations = document
+            .animations()
+            .map(|animation| AnimationClip {
+                name: animation.name().map(str::to_string),
+                channels: animation
+                    .channels()
+                    .filter_map(|channel| {
+                        let reader = channel.reader(buffer_data);
+                        let times: Vec<f32> = reader.read_inputs()?.collect();
+                        let interpolation = match channel.sampler().interpolation() {
+                            gltf::animation::Interpolation::Step => Interpolation::Step,
+                            gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                            gltf::animation::Interpolation::CubicSpline => {
+                                Interpolation::CubicSpline
+                            }
+                        };
+                        let keyframes = match reader.read_outputs()? {
+                            ReadOutputs::Translations(translations) => {
+                                Keyframes::Translation(translations.map(Vector3::from).collect())
+                            }
+                            ReadOutputs::Rotations(rotations) => Keyframes::Rotation(
+                                rotations
+                                    .into_f32()
+                                    .map(|[x, y, z, w]| Quaternion::new(w, x, y, z))
+                                    .collect(),
+                            ),
+                            ReadOutputs::Scales(scales) => {
+                                Keyframes::Scale(scales.map(Vector3::from).collect())
+                            }
+                            ReadOutputs::MorphTargetWeights(_) => return None,
+                        };
+                        Some(AnimationChannel {
+                            target: channel.target().node().index(),
+                            interpolation,
+                            times,
+                            keyframes,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            meshes,
+            materials,
+            nodes,
+            roots,
+            skins,
+            animations,
+        })
+    }
+
+    /// Poses the nodes as animation `index` has them at `time` seconds,
+    /// e.g. `elapsed % duration` to loop it. An [`Animator`] blends clips
+    /// into a [`pose`](Self::pose) instead.
+    ///
+    /// [`Animator`]: crate::animation::Animator
+    pub fn animate(&mut self, index: usize, time: f32) {
+        let mut pose = self.pose();
+        self.animations[index].sample(time, &mut pose);
+        self.set_pose(&pose);
+    }
+
+    /// Local transform of every node, indexed like [`nodes`](Self::nodes).
+    pub fn pose(&self) -> Vec<Transform> {
+        self.nodes.iter().map(|node| node.transform).collect()
+    }
+
+    pub fn set_pose(&mut self, pose: &[Transform]) {
+        for (node, transform) in self.nodes.iter_mut().zip(pose) {
+            node.transform = *transform;
+        }
+    }
+
+    /// Adds the node hierarchy under `parent`, or as roots without one,
+    /// sharing the meshes. Nodes with several primitives get a child node
+    /// per primitive. Returns the scene node of every glTF node, indexed
+    /// like [`nodes`](Self::nodes), `None` for those outside the glTF scene,
+    /// to pose them with [`Scene::set_pose`].
+    pub fn instantiate(&self, scene: &mut Scene, parent: Option<NodeId>) -> Vec<Option<NodeId>> {
+        let mut ids = vec![None; self.nodes.len()];
+        let mut stack: Vec<_> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|&root| (root, parent))
+            .collect();
+        while let Some((index, parent)) = stack.pop() {
+            let node = &self.nodes[index];
+            let mut scene_node = Node::new(node.transform);
+            scene_node.name = node.name.clone();
+            let primitives = match node.mesh {
+                Some(mesh) => self.meshes[mesh].primitives.as_slice(),
+                None => &[],
+            };
+            if let [primitive] = primitives {
+                scene_node.mesh = Some(Rc::clone(primitive) as Rc<dyn Drawable>);
+            }
+            let id = scene.add(scene_node, parent);
+            if primitives.len() > 1 {
+                for primitive in primitives {
+                    let child = Node::default().with_mesh(Rc::clone(primitive) as Rc<dyn Drawable>);
+                    scene.add_child(id, child);
+                }
+            }
+            ids[index] = Some(id);
+            stack.extend(node.children.iter().rev().map(|&child| (child, Some(id))));
+        }
+        ids
+    }
+
+    /// World transform of every node, placed with `model`, indexed like
+    /// [`nodes`](Self::nodes). Nodes outside the scene get `model`.
+    pub fn world_transforms(&self, model: Matrix4<f32>) -> Vec<Matrix4<f32>> {
+        let mut transforms = vec![model; self.nodes.len()];
+        let mut stack: Vec<(usize, Matrix4<f32>)> =
+            self.roots.iter().map(|&root| (root, model)).collect();
+        while let Some((index, parent)) = stack.pop() {
+            let node = &self.nodes[index];
+            let world = parent * node.transform.matrix();
+            transforms[index] = world;
+            stack.extend(node.children.iter().map(|&child| (child, world)));
+        }
+        transforms
+    }
+
+    /// World space bounds of the meshes in the current pose, placed with
+    /// `model`. Skinned vertices are bounded in their bind pose.
+    pub fn bounds(&self, model: Matrix4<f32>) -> Option<Aabb> {
+        let transforms = self.world_transforms(model);
+        self.nodes
+            .iter()
+            .zip(&transforms)
+            .filter_map(|(node, world)| Some((&self.meshes[node.mesh?], world)))
+            .flat_map(|(mesh, world)| {
+                mesh.primitives
+                    .iter()
+                    .filter_map(|primitive| Some(primitive.bounds()?.transformed(world)))
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Matrices skinning the vertices of `skin` into the nodes' current
+    /// pose, for a [`JointPalette`].
+    pub fn joint_matrices(&self, skin: usize) -> Vec<Matrix4<f32>> {
+        let transforms = self.world_transforms(Matrix4::identity());
+        let skin = &self.skins[skin];
+        skin.joints
+            .iter()
+            .zip(&skin.inverse_bind_matrices)
+            .map(|(&joint, inverse_bind)| transforms[joint] * inverse_bind)
+            .collect()
+    }
+
+    /// The joints of `skin` as a [`Skeleton`] at rest in the nodes' current
+    /// pose, placed relative to the nearest ancestor that's a joint too.
+    pub fn skeleton(&self, skin: usize) -> Result<Skeleton, GLWError> {
+        let mut parents = vec![None; self.nodes.len()];
+        for (index, node) in self.nodes.iter().enumerate() {
+            for &child in &node.children {
+                parents[child] = Some(index);
+            }
+        }
+        let skin = &self.skins[skin];
+        let joints = skin
+            .joints
+            .iter()
+            .zip(&skin.inverse_bind_matrices)
+            .map(|(&node, &inverse_bind)| {
+                let mut bind_pose = self.nodes[node].transform;
+                let mut ancestor = parents[node];
+                let mut parent = None;
+                while let Some(index) = ancestor {
+                    parent = skin.joints.iter().position(|&joint| joint == index);
+                    if parent.is_some() {
+                        break;
+                    }
+                    bind_pose = self.nodes[index].transform * bind_pose;
+                    ancestor = parents[index];
+                }
+                Joint {
+                    name: self.nodes[node].name.clone(),
+                    parent,
+                    bind_pose,
+                    inverse_bind,
+                }
+            })
+            .collect();
+        Skeleton::new(joints)
+    }
+
+    /// Draws the scene placed with `model`, setting `program`'s `model`
+    /// uniform per node and [binding](Material::bind_with) each primitive's
+    /// material. Textures go to the `material.baseColor`,
+    /// `material.metallicRoughness`, `material.normal`, `material.occlusion`
+    /// and `material.emissive` samplers, the factors to uniforms named like
+    /// `material.baseColorFactor`.
+    ///
+    /// Leaves the joint palette alone, use
+    /// [`draw_skinned`](Self::draw_skinned) to pose skinned meshes.
+    pub fn draw(&self, program: &ShaderProgram, model: Matrix4<f32>) -> Result<(), GLWError> {
+        self.draw_nodes(program, model, None)
+    }
+
+    /// Like [`draw`](Self::draw), uploading the [joint
+    /// matrices](Self::joint_matrices) of each skinned node to `palette`
+    /// before drawing it. `palette` has to be [bound](JointPalette::bind)
+    /// to `program`. Skinned meshes are placed by their joints, ignoring
+    /// their nodes' transforms.
+    pub fn draw_skinned(
+        &self,
+        program: &ShaderProgram,
+        model: Matrix4<f32>,
+        palette: &mut JointPalette,
+    ) -> Result<(), GLWError> {
+        self.draw_nodes(program, model, Some(palette))
+    }
+
+    fn draw_nodes(
+        &self,
+        program: &ShaderProgram,
+        model: Matrix4<f32>,
+        mut palette: Option<&mut JointPalette>,
+    ) -> Result<(), GLWError> {
+        let transforms = self.world_transforms(model);
+        let mut stack = self.roots.clone();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            stack.extend(&node.children);
+            let Some(mesh) = node.mesh else {
+                continue;
+            };
+            match (node.skin, palette.as_deref_mut()) {
+                (Some(skin), Some(palette)) => {
+                    palette.update(&self.joint_matrices(skin))?;
+                    skip_missing(program.set_mat4("model", model))?;
+                }
+                _ => skip_missing(program.set_mat4("model", transforms[index]))?,
+            }
+            for primitive in &self.meshes[mesh].primitives {
+                if let Some(material) = primitive.material() {
+                    material.bind_with(program)?;
+                }
+                program.use_program();
+                primitive.draw();
+            }
+        }
+        Ok(())
+    }
+}
+
+fn primitive_of(mode: Mode) -> Primitive {
+    match mode {
+        Mode::Points => Primitive::Points,
+        Mode::Lines => Primitive::Lines,
+        Mode::LineLoop => Primitive::LineLoop,
+        Mode::LineStrip => Primitive::LineStrip,
+        Mode::Triangles => Primitive::Triangles,
+        Mode::TriangleStrip => Primitive::TriangleStrip,
+        Mode::TriangleFan => Primitive::TriangleFan,
+    }
+}
+
+fn load_texture(
+    texture: &gltf::Texture,
+    image: &gltf::image::Data,
+    color_space: ColorSpace,
+) -> Result<Texture2D, GLWError> {
+    // 16 bit images keep their high bytes
+    let narrow = |channels: usize| -> Vec<u8> {
+        image
+            .pixels
+            .chunks_exact(2)
+            .take(image.width as usize * image.height as usize * channels)
+            .map(|sample| sample[1])
+            .collect()
+    };
+    let (format, pixels) = match image.format {
+        Format::R8 => (gl::RED, image.pixels.clone()),
+        Format::R8G8 => (gl::RG, image.pixels.clone()),
+        Format::R8G8B8 => (gl::RGB, image.pixels.clone()),
+        Format::R8G8B8A8 => (gl::RGBA, image.pixels.clone()),
+        Format::R16 => (gl::RED, narrow(1)),
+        Format::R16G16 => (gl::RG, narrow(2)),
+        Format::R16G16B16 => (gl::RGB, narrow(3)),
+        Format::R16G16B16A16 => (gl::RGBA, narrow(4)),
+        format => Err(GLWErrorKind::UnsupportedTextureFormat(format!(
+            "{format:?}"
+        )))?,
+    };
+
+    let sampler = texture.sampler();
+    let wrap = |mode| match mode {
+        WrappingMode::ClampToEdge => WrapMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => WrapMode::MirroredRepeat,
+        WrappingMode::Repeat => WrapMode::Repeat,
+    };
+    let min_filter = match sampler.min_filter() {
+        Some(GltfMinFilter::Nearest) => MinFilter::Nearest,
+        Some(GltfMinFilter::Linear) => MinFilter::Linear,
+        Some(GltfMinFilter::NearestMipmapNearest) => MinFilter::NearestMipmapNearest,
+        Some(GltfMinFilter::LinearMipmapNearest) => MinFilter::LinearMipmapNearest,
+        Some(GltfMinFilter::NearestMipmapLinear) => MinFilter::NearestMipmapLinear,
+        Some(GltfMinFilter::LinearMipmapLinear) | None => MinFilter::LinearMipmapLinear,
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => Filter::Nearest,
+        Some(MagFilter::Linear) | None => Filter::Linear,
+    };
+
+    // glTF puts the first row at `v = 0`, as GL does without flipping
+    Ok(Texture2D::builder()
+        .wrap_s(wrap(sampler.wrap_s()))
+        .wrap_t(wrap(sampler.wrap_t()))
+        .min_filter(min_filter)
+        .mag_filter(mag_filter)
+        .color_space(color_space)
+        .flip_vertically(false)
+        .build_from_data(image.width, image.height, format, &pixels))
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use crate::errors::{GLWError, GLWErrorKind};
+
+/// GPU time a [`GpuScope`] took.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuTiming {
+    pub name: String,
+    /// How many scopes enclosed this one.
+    pub depth: usize,
+    pub duration: Duration,
+}
+
+struct ScopeQueries {
+    name: String,
+    depth: usize,
+    start: u32,
+    end: u32,
+}
+
+/// Queries of one frame, reused once their results are read.
+#[derive(Default)]
+struct FrameQueries {
+    pool: Vec<u32>,
+    used: usize,
+    scopes: Vec<ScopeQueries>,
+}
+
+impl FrameQueries {
+    fn query(&mut self) -> u32 {
+        if self.used == self.pool.len() {
+            let mut query_id = 0;
+            unsafe {
+                gl::GenQueries(1, &mut query_id);
+            }
+            self.pool.push(query_id);
+        }
+        self.used += 1;
+        self.pool[self.used - 1]
+    }
+
+    /// Whether the GPU got through every scope, timestamps completing in
+    /// order.
+    fn is_available(&self) -> bool {
+        let Some(last) = self.scopes.last() else {
+            return true;
+        };
+        let mut available = 0;
+        unsafe {
+            gl::GetQueryObjectuiv(last.end, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        }
+        available == gl::TRUE as u32
+    }
+
+    fn read(&self) -> Vec<GpuTiming> {
+        let timestamp = |query_id| {
+            let mut nanos = 0;
+            unsafe {
+                gl::GetQueryObjectui64v(query_id, gl::QUERY_RESULT, &mut nanos);
+            }
+            nanos
+        };
+        self.scopes
+            .iter()
+            .map(|scope| GpuTiming {
+                name: scope.name.clone(),
+                depth: scope.depth,
+                duration: Duration::from_nanos(
+                    timestamp(scope.end).saturating_sub(timestamp(scope.start)),
+                ),
+            })
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        self.used = 0;
+        self.scopes.clear();
+    }
+}
+
+/// Measures how long the GPU spends on parts of a frame, with timestamp
+/// queries (GL 3.3 or `ARB_timer_query`). Unlike `GL_TIME_ELAPSED` queries
+/// these nest.
+///
+/// Results are read a frame later than they're recorded, from the other of
+/// two query sets, so reading them doesn't wait for the GPU. A frame whose
+/// results still aren't ready by then is dropped.
+///
+/// ```ignore
+/// timer.begin_frame();
+/// {
+///     let mut frame = timer.scope("frame");
+///     let _shadows = frame.scope("shadow pass");
+/// }
+/// for timing in timer.results() { /* ... */ }
+/// ```
+pub struct GpuTimer {
+    frames: [FrameQueries; 2],
+    current: usize,
+    depth: usize,
+    results: Vec<GpuTiming>,
+}
+
+impl GpuTimer {
+    pub fn new() -> Result<Self, GLWError> {
+        if !supported() {
+            Err(GLWErrorKind::ExtensionNotSupported(
+                "GL_ARB_timer_query".to_string(),
+            ))?;
+        }
+        Ok(Self {
+            frames: Default::default(),
+            current: 0,
+            depth: 0,
+            results: Vec::new(),
+        })
+    }
+
+    /// Starts recording a new frame, picking up the results of the frame
+    /// before the previous one if the GPU is done with it.
+    pub fn begin_frame(&mut self) {
+        self.current = 1 - self.current;
+        self.depth = 0;
+        let frame = &mut self.frames[self.current];
+        if !frame.scopes.is_empty() && frame.is_available() {
+            self.results = frame.read();
+        }
+        frame.reset();
+    }
+
+    /// Times the GPU commands issued until the returned guard drops.
+    pub fn scope(&mut self, name: impl Into<String>) -> GpuScope<'_> {
+        let frame = &mut self.frames[self.current];
+        let start = frame.query();
+        let end = frame.query();
+        unsafe {
+            gl::QueryCounter(start, gl::TIMESTAMP);
+        }
+        frame.scopes.push(ScopeQueries {
+            name: name.into(),
+            depth: self.depth,
+            start,
+            end,
+        });
+        self.depth += 1;
+        GpuScope { timer: self, end }
+    }
+
+    /// Timings of the latest frame with results, in the order the scopes
+    /// began.
+    pub fn results(&self) -> &[GpuTiming] {
+        &self.results
+    }
+
+    /// Duration of the first scope called `name` in [`results`](Self::results).
+    pub fn get(&self, name: &str) -> Option<Duration> {
+        self.results
+            .iter()
+            .find(|timing| timing.name == name)
+            .map(|timing| timing.duration)
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        for frame in &self.frames {
+            unsafe {
+                gl::DeleteQueries(frame.pool.len() as i32, frame.pool.as_ptr());
+            }
+        }
+    }
+}
+
+/// A running [`GpuTimer`] scope, ended on drop.
+pub struct GpuScope<'a> {
+    timer: &'a mut GpuTimer,
+    end: u32,
+}
+
+impl GpuScope<'_> {
+    /// Times a part of this scope.
+    pub fn scope(&mut self, name: impl Into<String>) -> GpuScope<'_> {
+        self.timer.scope(name)
+    }
+}
+
+impl Drop for GpuScope<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gl::QueryCounter(self.end, gl::TIMESTAMP);
+        }
+        self.timer.depth -= 1;
+    }
+}
+
+/// Whether timestamp queries are available, GLES lacks them.
+pub fn supported() -> bool {
+    gl::QueryCounter::is_loaded()
+}
@@ -0,0 +1,106 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use image::hdr::HDRDecoder;
+
+use crate::{
+    errors::{GLWError, GLWErrorExt, GLWErrorKind},
+    texture::{self, Texture2D, TextureFormat, TextureParams},
+};
+
+/// Floating point RGB image, e.g. an HDR environment map.
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    /// Tightly packed RGB values, bottom row first.
+    pub pixels: Vec<f32>,
+}
+
+impl HdrImage {
+    /// Loads a Radiance `.hdr` file. EXR files aren't supported.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        let path = path.as_ref();
+        let is_hdr = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("hdr"));
+        if !is_hdr {
+            Err(GLWErrorKind::UnsupportedTextureFormat(format!(
+                "{}",
+                path.display()
+            )))?
+        }
+
+        let file = File::open(path).info(format!("{}", path.display()))?;
+        Self::decode(BufReader::new(file)).info(format!("{}", path.display()))
+    }
+
+    fn decode(reader: BufReader<File>) -> Result<Self, GLWError> {
+        let decoder = HDRDecoder::new(reader)?;
+        let metadata = decoder.metadata();
+        let (width, height) = (metadata.width, metadata.height);
+        let rows = decoder.read_image_hdr()?;
+
+        // Radiance files store rows top to bottom
+        let pixels = rows
+            .chunks(width as usize)
+            .rev()
+            .flatten()
+            .flat_map(|pixel| pixel.data)
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+}
+
+impl Texture2D {
+    /// Loads a Radiance `.hdr` file into an `Rgb16F` texture.
+    pub fn from_hdr_path(path: impl AsRef<Path>, params: &TextureParams) -> Result<Self, GLWError> {
+        Ok(Self::from_hdr(
+            &HdrImage::load(path)?,
+            TextureFormat::Rgb16F,
+            params,
+        ))
+    }
+
+    /// Uploads `image` as `format`, usually `Rgb16F` or `Rgb32F`.
+    ///
+    /// # Panics
+    /// If `format` isn't a floating point format.
+    pub fn from_hdr(image: &HdrImage, format: TextureFormat, params: &TextureParams) -> Self {
+        assert!(
+            format.pixel_format().1 == gl::FLOAT && !format.is_depth(),
+            "{format:?} is not a floating point color format"
+        );
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+        }
+        texture::with_unpack_alignment_1(|| unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                u32::from(format) as i32,
+                image.width as i32,
+                image.height as i32,
+                0,
+                gl::RGB,
+                gl::FLOAT,
+                image.pixels.as_ptr().cast(),
+            );
+        });
+        params.apply(gl::TEXTURE_2D);
+
+        Self {
+            texture_id,
+            width: image.width,
+            height: image.height,
+            format,
+        }
+    }
+}
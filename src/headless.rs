@@ -0,0 +1,83 @@
+use std::sync::mpsc::Receiver;
+
+use glfw::Context;
+
+use crate::{
+    context,
+    errors::{GLWError, GLWErrorKind},
+    framebuffer::Framebuffer,
+    texture::TextureFormat,
+};
+
+/// GL context without a visible window, rendering into an offscreen
+/// framebuffer, for batch rendering and tests.
+///
+/// This uses a hidden GLFW window, so a display server is still needed; on
+/// machines without one run under e.g. `xvfb-run`.
+pub struct HeadlessContext {
+    // fields drop in order, the framebuffer has to go while the context lives
+    framebuffer: Framebuffer,
+    window: glfw::Window,
+    _events: Receiver<(f64, glfw::WindowEvent)>,
+    glfw: glfw::Glfw,
+}
+
+impl HeadlessContext {
+    /// Creates a GL 3.3 core context and a `width` by `height` RGBA8
+    /// framebuffer with depth, which is left bound.
+    pub fn new(width: u32, height: u32) -> Result<Self, GLWError> {
+        let mut glfw = glfw::init(glfw::LOG_ERRORS)?;
+        glfw.window_hint(glfw::WindowHint::Visible(false));
+        glfw.window_hint(glfw::WindowHint::ContextVersion(3, 3));
+        glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+            glfw::OpenGlProfileHint::Core,
+        ));
+        #[cfg(target_os = "macos")]
+        glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+        let (mut window, events) = glfw
+            .create_window(width, height, "learngl", glfw::WindowMode::Windowed)
+            .ok_or(GLWErrorKind::WindowCreationFailed)?;
+        window.make_current();
+        context::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+
+        let framebuffer = Framebuffer::with_color(width, height, TextureFormat::Rgba8)?;
+        framebuffer.bind();
+
+        Ok(Self {
+            framebuffer,
+            window,
+            _events: events,
+            glfw,
+        })
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// Waits for rendering to finish and reads the framebuffer back as
+    /// RGBA8, bottom row first.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        unsafe {
+            gl::Finish();
+        }
+        self.framebuffer.read_pixels(0)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.framebuffer.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.framebuffer.height()
+    }
+
+    pub fn window(&self) -> &glfw::Window {
+        &self.window
+    }
+
+    pub fn glfw(&mut self) -> &mut glfw::Glfw {
+        &mut self.glfw
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    errors::GLWError,
+    shader::{self, Shader, ShaderProgram},
+};
+
+pub struct ShaderWatcher {
+    // kept alive for as long as events should be delivered
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    paths: Vec<PathBuf>,
+}
+
+impl ShaderWatcher {
+    pub fn new<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self, GLWError> {
+        let paths = paths
+            .into_iter()
+            .map(fs::canonicalize)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+
+        // Editors commonly save by writing a new file and renaming it over the
+        // old one, which drops watches placed on the file itself, so the
+        // containing directories are watched instead.
+        let dirs: HashSet<&Path> = paths.iter().filter_map(|path| path.parent()).collect();
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            paths,
+        })
+    }
+
+    /// Drains pending file system events and reports whether any of them
+    /// touched a watched path.
+    pub fn changed(&self) -> bool {
+        // every pending event is consumed, so no short-circuiting here
+        let mut changed = false;
+        for event in self.events.try_iter().filter_map(Result::ok) {
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                changed |= event.paths.iter().any(|path| self.paths.contains(path));
+            }
+        }
+        changed
+    }
+}
+
+impl ShaderProgram {
+    pub fn source_paths(&self) -> impl Iterator<Item = &Path> {
+        self.source_paths.iter().map(|file| file.path.as_path())
+    }
+
+    /// Starts watching the source files the program was built from.
+    /// Use [`reload_if_changed`](Self::reload_if_changed) to pick up edits.
+    pub fn watch(&mut self) -> Result<(), GLWError> {
+        self.watcher = Some(ShaderWatcher::new(
+            self.source_paths()
+                .chain(self.include_paths.iter().map(PathBuf::as_path)),
+        )?);
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self) {
+        self.watcher = None;
+    }
+
+    /// Recompiles the file backed shaders with the defines and GLSL target
+    /// each was first compiled with, and relinks the program.
+    ///
+    /// On failure the error is returned and the previous program stays in use.
+    pub fn reload(&mut self) -> Result<(), GLWError> {
+        let shaders: Vec<Shader> = self
+            .source_paths
+            .iter()
+            .map(|file| {
+                let (source, shader_type) = shader::preprocess_path(
+                    &file.path,
+                    Some(file.shader_type),
+                    &file.options.defines,
+                    file.options.glsl_target,
+                )?;
+                Shader::from_preprocessed(&source, shader_type)
+            })
+            .collect::<Result<_, GLWError>>()?;
+
+        let shader_program_id = shader::link_program(
+            shaders
+                .iter()
+                .map(|shader| shader.shader_id)
+                .chain(self.retained_shader_ids.iter().copied()),
+            &self.link_options,
+        )?;
+
+        // SAFETY: the old id was created by the builder or a previous reload
+        // and is replaced by a freshly linked one
+        unsafe {
+            gl::DeleteProgram(self.shader_program_id);
+        }
+        self.shader_program_id = shader_program_id;
+        self.generation = shader::next_generation();
+        self.cache_uniform_locations();
+
+        // the set of included files may have changed with the edit
+        self.include_paths = shaders
+            .iter()
+            .flat_map(|shader| shader.includes.iter().cloned())
+            .chain(self.retained_includes.iter().cloned())
+            .collect();
+        if self.watcher.is_some() {
+            self.watch()?;
+        }
+
+        Ok(())
+    }
+
+    /// Polls the watcher started by [`watch`](Self::watch) and reloads the
+    /// program if a source changed. Returns whether a reload happened.
+    pub fn reload_if_changed(&mut self) -> Result<bool, GLWError> {
+        match &self.watcher {
+            Some(watcher) if watcher.changed() => self.reload().map(|_| true),
+            _ => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    buffer::{Buffer, BufferTarget, BufferUsage, Index, IndexBuffer},
+    draw::Primitive,
+};
+
+/// Parameters of one indexed draw, laid out as `glMultiDrawElementsIndirect`
+/// expects them.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawElementsIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub base_instance: u32,
+}
+
+/// Draw commands built on the CPU before uploading them into a
+/// [`DrawIndirectBuffer`].
+#[derive(Debug, Clone, Default)]
+pub struct DrawCommandList {
+    commands: Vec<DrawElementsIndirectCommand>,
+}
+
+impl DrawCommandList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draws `count` indices starting at `first_index`, with `base_vertex`
+    /// added to each index.
+    pub fn push(mut self, count: u32, first_index: u32, base_vertex: i32) -> Self {
+        self.commands.push(DrawElementsIndirectCommand {
+            count,
+            instance_count: 1,
+            first_index,
+            base_vertex,
+            base_instance: 0,
+        });
+        self
+    }
+
+    pub fn push_command(mut self, command: DrawElementsIndirectCommand) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn commands(&self) -> &[DrawElementsIndirectCommand] {
+        &self.commands
+    }
+
+    pub fn upload(&self, usage: BufferUsage) -> DrawIndirectBuffer {
+        DrawIndirectBuffer::new(&self.commands, usage)
+    }
+}
+
+/// `DRAW_INDIRECT_BUFFER` of draw commands submitted with a single
+/// `glMultiDrawElementsIndirect` call (GL 4.3).
+///
+/// A copy of the commands is kept so [`draw`](Self::draw) can check them
+/// against the index buffer they are drawn with.
+pub struct DrawIndirectBuffer {
+    buffer: Buffer<DrawElementsIndirectCommand>,
+    commands: Vec<DrawElementsIndirectCommand>,
+}
+
+impl DrawIndirectBuffer {
+    pub fn supported() -> bool {
+        gl::MultiDrawElementsIndirect::is_loaded()
+    }
+
+    pub fn new(commands: &[DrawElementsIndirectCommand], usage: BufferUsage) -> Self {
+        Self {
+            buffer: Buffer::new(BufferTarget::DrawIndirect, commands, usage),
+            commands: commands.to_vec(),
+        }
+    }
+
+    pub fn update(&mut self, offset: usize, commands: &[DrawElementsIndirectCommand]) {
+        self.buffer.update(offset, commands);
+        self.commands[offset..offset + commands.len()].copy_from_slice(commands);
+    }
+
+    pub fn commands(&self) -> &[DrawElementsIndirectCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Submits every command, reading indices from `indices`, which has to be
+    /// the index buffer of the bound vertex array.
+    ///
+    /// # Panics
+    /// If a command reads past the end of `indices`.
+    pub fn draw<I: Index>(&self, primitive: Primitive, indices: &IndexBuffer<I>) {
+        assert!(
+            self.commands.iter().all(|command| {
+                u64::from(command.first_index) + u64::from(command.count) <= indices.len() as u64
+            }),
+            "indirect draw command out of bounds of the index buffer"
+        );
+
+        self.buffer.bind();
+        unsafe {
+            gl::MultiDrawElementsIndirect(
+                primitive.into(),
+                I::GL_TYPE,
+                std::ptr::null(),
+                self.commands.len() as i32,
+                0,
+            );
+        }
+        self.buffer.unbind();
+    }
+}
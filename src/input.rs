@@ -0,0 +1,163 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use glfw::{Action, CursorMode, JoystickId, Key, MouseButton, WindowEvent};
+
+use crate::gamepad::{GamepadEvent, GamepadState, Gamepads};
+
+/// Keyboard, mouse and gamepad state built from window events and joystick
+/// polls, polled instead of matching events one by one.
+#[derive(Debug, Default, Clone)]
+pub struct Input {
+    held: HashSet<Key>,
+    pressed: HashSet<Key>,
+    released: HashSet<Key>,
+    buttons_held: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    /// Last cursor position, `None` until the first move so the first
+    /// position doesn't count as a jump from the origin.
+    cursor: Option<(f64, f64)>,
+    mouse_delta: (f64, f64),
+    scroll_delta: (f64, f64),
+    dropped_files: Vec<PathBuf>,
+    gamepads: Gamepads,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the presses and releases of the previous frame, call it
+    /// before handling the frame's events.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.mouse_delta = (0.0, 0.0);
+        self.scroll_delta = (0.0, 0.0);
+        self.dropped_files.clear();
+    }
+
+    /// Reads every joystick's state, the run loop does this each frame.
+    pub fn poll_gamepads(&mut self, glfw: &glfw::Glfw) {
+        self.gamepads.poll(glfw);
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::FileDrop(paths) = event {
+            self.dropped_files.extend_from_slice(paths);
+        }
+        match *event {
+            WindowEvent::Key(key, _, Action::Press, _) => {
+                self.held.insert(key);
+                self.pressed.insert(key);
+            }
+            WindowEvent::Key(key, _, Action::Release, _) => {
+                self.held.remove(&key);
+                self.released.insert(key);
+            }
+            WindowEvent::MouseButton(button, Action::Press, _) => {
+                self.buttons_held.insert(button);
+                self.buttons_pressed.insert(button);
+            }
+            WindowEvent::MouseButton(button, Action::Release, _) => {
+                self.buttons_held.remove(&button);
+                self.buttons_released.insert(button);
+            }
+            WindowEvent::CursorPos(x, y) => {
+                if let Some((last_x, last_y)) = self.cursor {
+                    self.mouse_delta.0 += x - last_x;
+                    self.mouse_delta.1 += y - last_y;
+                }
+                self.cursor = Some((x, y));
+            }
+            WindowEvent::Scroll(x, y) => {
+                self.scroll_delta.0 += x;
+                self.scroll_delta.1 += y;
+            }
+            // keys released while unfocused never report it
+            WindowEvent::Focus(false) => {
+                self.held.clear();
+                self.buttons_held.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `key` is held down.
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.held.contains(&key)
+    }
+
+    /// Whether `key` went down this frame.
+    pub fn just_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    /// Whether `key` went up this frame.
+    pub fn just_released(&self, key: Key) -> bool {
+        self.released.contains(&key)
+    }
+
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_held.contains(&button)
+    }
+
+    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Cursor position in screen coordinates from the top left, once the
+    /// cursor moved.
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor
+    }
+
+    /// How far the cursor moved this frame, `y` growing downwards.
+    pub fn mouse_delta(&self) -> (f64, f64) {
+        self.mouse_delta
+    }
+
+    /// Scrolling this frame, summed over its events. `y` is positive when
+    /// scrolling up (away from the user), e.g. to zoom in.
+    pub fn scroll_delta(&self) -> (f64, f64) {
+        self.scroll_delta
+    }
+
+    /// Files dropped onto the window this frame.
+    pub fn dropped_files(&self) -> &[PathBuf] {
+        &self.dropped_files
+    }
+
+    pub fn gamepad(&self, id: JoystickId) -> Option<&GamepadState> {
+        self.gamepads.get(id)
+    }
+
+    /// Gamepad connections and disconnections this frame.
+    pub fn gamepad_events(&self) -> &[GamepadEvent] {
+        self.gamepads.events()
+    }
+
+    /// The gamepads, e.g. to change the dead zone.
+    pub fn gamepads_mut(&mut self) -> &mut Gamepads {
+        &mut self.gamepads
+    }
+
+    /// Hides the cursor and locks it to the window for unbounded mouse
+    /// look, or releases it. The next move after either doesn't count as a
+    /// jump.
+    pub fn capture_cursor(&mut self, window: &mut glfw::Window, captured: bool) {
+        window.set_cursor_mode(if captured {
+            CursorMode::Disabled
+        } else {
+            CursorMode::Normal
+        });
+        self.cursor = None;
+    }
+}
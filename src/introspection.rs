@@ -0,0 +1,284 @@
+use std::ffi::CString;
+
+use gl::types::{GLchar, GLenum};
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    shader::ShaderProgram,
+};
+
+#[derive(Debug, Clone)]
+pub struct ActiveUniform {
+    pub name: String,
+    pub gl_type: GLenum,
+    /// Number of elements, 1 unless the uniform is an array.
+    pub size: i32,
+    /// -1 for uniforms living in a uniform block.
+    pub location: i32,
+    /// Index of the containing uniform block, -1 for default block uniforms.
+    pub block_index: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ActiveAttribute {
+    pub name: String,
+    pub gl_type: GLenum,
+    pub size: i32,
+    pub location: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct UniformBlock {
+    pub name: String,
+    pub index: u32,
+    pub binding: u32,
+    pub data_size: i32,
+    pub active_uniforms: i32,
+}
+
+/// Program interfaces that can be enumerated with
+/// [`ShaderProgram::resource_names`]. Requires GL 4.3.
+#[derive(Debug, Clone, Copy)]
+pub enum ProgramInterface {
+    Uniform,
+    UniformBlock,
+    ProgramInput,
+    ProgramOutput,
+    ShaderStorageBlock,
+    BufferVariable,
+    TransformFeedbackVarying,
+}
+
+impl From<ProgramInterface> for u32 {
+    fn from(val: ProgramInterface) -> Self {
+        match val {
+            ProgramInterface::Uniform => gl::UNIFORM,
+            ProgramInterface::UniformBlock => gl::UNIFORM_BLOCK,
+            ProgramInterface::ProgramInput => gl::PROGRAM_INPUT,
+            ProgramInterface::ProgramOutput => gl::PROGRAM_OUTPUT,
+            ProgramInterface::ShaderStorageBlock => gl::SHADER_STORAGE_BLOCK,
+            ProgramInterface::BufferVariable => gl::BUFFER_VARIABLE,
+            ProgramInterface::TransformFeedbackVarying => gl::TRANSFORM_FEEDBACK_VARYING,
+        }
+    }
+}
+
+impl ShaderProgram {
+    fn program_iv(&self, pname: GLenum) -> i32 {
+        let mut value = 0;
+        unsafe {
+            gl::GetProgramiv(self.shader_program_id, pname, &mut value);
+        }
+        value
+    }
+
+    pub fn active_uniforms(&self) -> Vec<ActiveUniform> {
+        let count = self.program_iv(gl::ACTIVE_UNIFORMS);
+        let max_length = self.program_iv(gl::ACTIVE_UNIFORM_MAX_LENGTH);
+
+        (0..count as u32)
+            .map(|index| unsafe {
+                let (name, gl_type, size) =
+                    read_active(max_length, |len, written, size, ty, buf| {
+                        gl::GetActiveUniform(
+                            self.shader_program_id,
+                            index,
+                            len,
+                            written,
+                            size,
+                            ty,
+                            buf,
+                        )
+                    });
+
+                let mut block_index = -1;
+                gl::GetActiveUniformsiv(
+                    self.shader_program_id,
+                    1,
+                    &index,
+                    gl::UNIFORM_BLOCK_INDEX,
+                    &mut block_index,
+                );
+
+                let c_name = CString::new(name.as_str()).unwrap_or_default();
+                let location = gl::GetUniformLocation(self.shader_program_id, c_name.as_ptr());
+
+                ActiveUniform {
+                    name,
+                    gl_type,
+                    size,
+                    location,
+                    block_index,
+                }
+            })
+            .collect()
+    }
+
+    pub fn active_attributes(&self) -> Vec<ActiveAttribute> {
+        let count = self.program_iv(gl::ACTIVE_ATTRIBUTES);
+        let max_length = self.program_iv(gl::ACTIVE_ATTRIBUTE_MAX_LENGTH);
+
+        (0..count as u32)
+            .map(|index| unsafe {
+                let (name, gl_type, size) =
+                    read_active(max_length, |len, written, size, ty, buf| {
+                        gl::GetActiveAttrib(
+                            self.shader_program_id,
+                            index,
+                            len,
+                            written,
+                            size,
+                            ty,
+                            buf,
+                        )
+                    });
+
+                let c_name = CString::new(name.as_str()).unwrap_or_default();
+                let location = gl::GetAttribLocation(self.shader_program_id, c_name.as_ptr());
+
+                ActiveAttribute {
+                    name,
+                    gl_type,
+                    size,
+                    location,
+                }
+            })
+            .collect()
+    }
+
+    pub fn uniform_blocks(&self) -> Vec<UniformBlock> {
+        let count = self.program_iv(gl::ACTIVE_UNIFORM_BLOCKS);
+        let max_length = self.program_iv(gl::ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH);
+
+        (0..count as u32)
+            .map(|index| unsafe {
+                let block_iv = |pname| {
+                    let mut value = 0;
+                    gl::GetActiveUniformBlockiv(self.shader_program_id, index, pname, &mut value);
+                    value
+                };
+
+                let mut name = vec![0u8; max_length.max(1) as usize];
+                let mut length = 0;
+                gl::GetActiveUniformBlockName(
+                    self.shader_program_id,
+                    index,
+                    max_length,
+                    &mut length,
+                    name.as_mut_ptr() as *mut GLchar,
+                );
+
+                UniformBlock {
+                    name: String::from_utf8_lossy(&name[..length as usize]).to_string(),
+                    index,
+                    binding: block_iv(gl::UNIFORM_BLOCK_BINDING) as u32,
+                    data_size: block_iv(gl::UNIFORM_BLOCK_DATA_SIZE),
+                    active_uniforms: block_iv(gl::UNIFORM_BLOCK_ACTIVE_UNIFORMS),
+                }
+            })
+            .collect()
+    }
+
+    /// Location of the fragment shader output `name`, the index into the
+    /// draw buffers it writes.
+    pub fn fragment_output_location(&self, name: impl AsRef<str>) -> Result<u32, GLWError> {
+        let name = name.as_ref();
+        let c_name = CString::new(name)?;
+        let location = unsafe { gl::GetFragDataLocation(self.shader_program_id, c_name.as_ptr()) };
+        if location == -1 {
+            Err(GLWErrorKind::FragmentOutputNotFound(name.to_string()))?
+        }
+        Ok(location as u32)
+    }
+
+    /// Names of all active resources of `interface`, in resource index order.
+    pub fn resource_names(&self, interface: ProgramInterface) -> Vec<String> {
+        let interface = interface.into();
+        let mut count = 0;
+        let mut max_length = 0;
+        unsafe {
+            gl::GetProgramInterfaceiv(
+                self.shader_program_id,
+                interface,
+                gl::ACTIVE_RESOURCES,
+                &mut count,
+            );
+            gl::GetProgramInterfaceiv(
+                self.shader_program_id,
+                interface,
+                gl::MAX_NAME_LENGTH,
+                &mut max_length,
+            );
+        }
+
+        (0..count as u32)
+            .map(|index| unsafe {
+                let mut name = vec![0u8; max_length.max(1) as usize];
+                let mut length = 0;
+                gl::GetProgramResourceName(
+                    self.shader_program_id,
+                    interface,
+                    index,
+                    max_length,
+                    &mut length,
+                    name.as_mut_ptr() as *mut GLchar,
+                );
+                String::from_utf8_lossy(&name[..length as usize]).to_string()
+            })
+            .collect()
+    }
+}
+
+/// Shared name buffer handling of `glGetActiveUniform`/`glGetActiveAttrib`.
+unsafe fn read_active(
+    max_length: i32,
+    query: impl FnOnce(i32, *mut i32, *mut i32, *mut GLenum, *mut GLchar),
+) -> (String, GLenum, i32) {
+    let mut name = vec![0u8; max_length.max(1) as usize];
+    let mut length = 0;
+    let mut size = 0;
+    let mut gl_type = 0;
+    query(
+        max_length,
+        &mut length,
+        &mut size,
+        &mut gl_type,
+        name.as_mut_ptr() as *mut GLchar,
+    );
+
+    (
+        String::from_utf8_lossy(&name[..length as usize]).to_string(),
+        gl_type,
+        size,
+    )
+}
+
+/// GLSL spelling of the common `gl_type` values reported by introspection.
+pub fn glsl_type_name(gl_type: GLenum) -> Option<&'static str> {
+    Some(match gl_type {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::UNSIGNED_INT_VEC2 => "uvec2",
+        gl::UNSIGNED_INT_VEC3 => "uvec3",
+        gl::UNSIGNED_INT_VEC4 => "uvec4",
+        gl::BOOL => "bool",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+        gl::SAMPLER_2D_MULTISAMPLE => "sampler2DMS",
+        gl::IMAGE_2D => "image2D",
+        _ => return None,
+    })
+}
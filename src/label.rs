@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use gl::types::GLenum;
+
+use crate::{
+    buffer::Buffer,
+    cubemap::Cubemap,
+    framebuffer::{Framebuffer, MsaaFramebuffer},
+    multisample_texture::Texture2DMultisample,
+    persistent_buffer::PersistentBuffer,
+    shader::{Shader, ShaderProgram},
+    ssbo::SsboBuffer,
+    texture::Texture2D,
+    texture3d::Texture3D,
+    texture_array::Texture2DArray,
+    uniform_buffer::UniformBuffer,
+    vertex_array::VertexArray,
+};
+
+/// A GL object that can be named for GPU debuggers like RenderDoc or
+/// Nsight, with `glObjectLabel` (core since 4.3, or `KHR_debug`).
+pub trait ObjectLabel {
+    /// Namespace of the object, e.g. `gl::TEXTURE`.
+    const IDENTIFIER: GLenum;
+
+    fn object_id(&self) -> u32;
+
+    /// Names the object, doing nothing if labels aren't supported.
+    fn set_label(&self, label: &str) {
+        set_label(Self::IDENTIFIER, self.object_id(), label);
+    }
+
+    /// The object's name, if labels are supported and it has one.
+    fn label(&self) -> Option<String> {
+        if !supported() {
+            return None;
+        }
+        let mut buffer = vec![0u8; max_label_length()];
+        let mut length = 0;
+        unsafe {
+            gl::GetObjectLabel(
+                Self::IDENTIFIER,
+                self.object_id(),
+                buffer.len() as i32,
+                &mut length,
+                buffer.as_mut_ptr().cast(),
+            );
+        }
+        buffer.truncate(length as usize);
+        (length > 0).then(|| String::from_utf8_lossy(&buffer).to_string())
+    }
+}
+
+macro_rules! impl_object_label {
+    (buffers => $($ty:ident),*) => {
+        $(impl<T: Copy> ObjectLabel for $ty<T> {
+            const IDENTIFIER: GLenum = gl::BUFFER;
+
+            fn object_id(&self) -> u32 {
+                <$ty<T>>::id(self)
+            }
+        })*
+    };
+    ($identifier:expr => $($ty:ty),*) => {
+        $(impl ObjectLabel for $ty {
+            const IDENTIFIER: GLenum = $identifier;
+
+            fn object_id(&self) -> u32 {
+                <$ty>::id(self)
+            }
+        })*
+    };
+}
+
+impl_object_label!(
+    gl::TEXTURE => Texture2D, Cubemap, Texture2DArray, Texture3D, Texture2DMultisample
+);
+impl_object_label!(gl::FRAMEBUFFER => Framebuffer, MsaaFramebuffer);
+impl_object_label!(gl::VERTEX_ARRAY => VertexArray);
+impl_object_label!(buffers => Buffer, SsboBuffer, UniformBuffer, PersistentBuffer);
+
+impl ObjectLabel for Shader {
+    const IDENTIFIER: GLenum = gl::SHADER;
+
+    fn object_id(&self) -> u32 {
+        self.shader_id
+    }
+}
+
+impl ObjectLabel for ShaderProgram {
+    const IDENTIFIER: GLenum = gl::PROGRAM;
+
+    fn object_id(&self) -> u32 {
+        self.shader_program_id
+    }
+}
+
+pub fn supported() -> bool {
+    gl::ObjectLabel::is_loaded()
+}
+
+/// Longest label the implementation keeps, including the terminator.
+pub fn max_label_length() -> usize {
+    let mut max = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_LABEL_LENGTH, &mut max);
+    }
+    max as usize
+}
+
+/// Names the object `id` in the namespace `identifier`, truncating long
+/// labels. Does nothing if labels aren't supported.
+pub fn set_label(identifier: GLenum, id: u32, label: &str) {
+    if !supported() {
+        return;
+    }
+    let max = max_label_length().saturating_sub(1);
+    let mut end = label.len().min(max);
+    while !label.is_char_boundary(end) {
+        end -= 1;
+    }
+    unsafe {
+        gl::ObjectLabel(identifier, id, end as i32, label.as_ptr().cast());
+    }
+}
+
+/// Labels an object after the file it was loaded from.
+pub(crate) fn set_path_label(identifier: GLenum, id: u32, path: &Path) {
+    if let Some(name) = path.file_name() {
+        set_label(identifier, id, &name.to_string_lossy());
+    }
+}
@@ -1,3 +1,92 @@
+// WebGL2 needs a backend that doesn't go through `gl` function pointers
+#[cfg(target_arch = "wasm32")]
+compile_error!("learngl has no WebGL2 backend, wasm32 isn't supported");
+
+pub mod animation;
+#[cfg(feature = "glfw")]
+pub mod app;
+#[cfg(feature = "assimp")]
+pub mod assimp_model;
+pub mod async_build;
+pub mod atlas;
+pub mod binary_cache;
+pub mod bindless;
+pub mod bounds;
+pub mod buffer;
+pub mod buffer_map;
+#[cfg(feature = "glfw")]
+pub mod camera;
+pub mod capture;
+pub mod compressed_texture;
+pub mod compute;
+pub mod context;
+pub mod cubemap;
+pub mod debug;
+#[cfg(feature = "glfw")]
+pub mod display;
+pub mod draw;
 pub mod errors;
+#[cfg(feature = "glfw")]
+pub mod events;
+#[cfg(feature = "renderdoc")]
+pub mod frame_capture;
+pub mod framebuffer;
+pub mod frustum;
+#[cfg(feature = "glfw")]
+pub mod gamepad;
+pub mod geometry;
+#[cfg(feature = "gltf")]
+pub mod gltf_model;
+pub mod gpu_timer;
+pub mod hdr;
+#[cfg(feature = "glfw")]
+pub mod headless;
+pub mod hot_reload;
+pub mod indirect;
+#[cfg(feature = "glfw")]
+pub mod input;
+pub mod introspection;
+pub mod label;
+pub mod lod;
+pub mod material;
+pub mod mesh;
+pub mod model;
+#[cfg(feature = "glfw")]
+pub mod multi_window;
+pub mod multisample_texture;
+pub mod normals;
+pub mod optimize;
+pub mod persistent_buffer;
+pub mod pipeline;
+pub mod pixel_buffer;
+pub mod preprocessor;
+pub mod projection;
+pub mod ray;
+pub mod recorder;
+pub mod scene;
+#[cfg(feature = "sdl2")]
+pub mod sdl_window;
 pub mod shader;
+pub mod shader_cache;
+pub mod skinning;
+pub mod skybox;
+pub mod ssbo;
+pub mod sync;
+pub mod texture;
+pub mod texture3d;
+pub mod texture_array;
+pub mod texture_units;
+pub mod timing;
+mod trace;
+pub mod transform;
+pub mod transform_feedback;
+pub mod uniform;
+pub mod uniform_buffer;
 pub mod utils;
+pub mod vertex;
+pub mod vertex_array;
+#[cfg(feature = "winit")]
+pub mod winit_window;
+
+#[cfg(feature = "glfw")]
+pub use app::run;
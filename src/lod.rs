@@ -0,0 +1,377 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use cgmath::{EuclideanSpace, Matrix4, MetricSpace, Point3, SquareMatrix};
+
+use crate::{
+    bounds::{Aabb, Sphere},
+    material::Material,
+    scene::Drawable,
+};
+
+/// Finest grids [`simplify`] tries, per axis.
+const MAX_GRID: u32 = 1024;
+
+/// Where levels are picked from: the camera position and how much its
+/// projection magnifies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodView {
+    pub eye: Point3<f32>,
+    /// `projection[1][1]` of a perspective projection, the cotangent of
+    /// half the vertical field of view.
+    pub projection_scale: f32,
+    /// Multiplies distances and divides coverages before comparing them,
+    /// above 1 switches to coarser levels sooner, e.g. on slow machines.
+    pub bias: f32,
+}
+
+impl LodView {
+    pub fn new(eye: Point3<f32>, projection: &Matrix4<f32>) -> Self {
+        Self {
+            eye,
+            projection_scale: projection[1][1],
+            bias: 1.0,
+        }
+    }
+
+    /// View seen through a camera's `view` matrix.
+    pub fn from_matrices(view: &Matrix4<f32>, projection: &Matrix4<f32>) -> Self {
+        let eye = view.invert().map_or(Point3::origin(), |inverse| {
+            Point3::from_vec(inverse.w.truncate())
+        });
+        Self::new(eye, projection)
+    }
+
+    pub fn with_bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Distance from the eye to the sphere's surface, 0 inside it.
+    pub fn distance(&self, sphere: &Sphere) -> f32 {
+        (self.eye.distance(sphere.center) - sphere.radius).max(0.0)
+    }
+
+    /// Share of the screen height the sphere covers, infinite around the
+    /// eye.
+    pub fn coverage(&self, sphere: &Sphere) -> f32 {
+        let distance = self.eye.distance(sphere.center);
+        match distance > sphere.radius {
+            true => sphere.radius * self.projection_scale / distance,
+            false => f32::INFINITY,
+        }
+    }
+}
+
+/// How a [`Lod`]'s thresholds are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LodMetric {
+    /// A level is drawn up to its threshold's [distance](LodView::distance)
+    /// away.
+    Distance,
+    /// A level is drawn down to its threshold's
+    /// [share of the screen](LodView::coverage), which keeps working when
+    /// the field of view changes.
+    ScreenCoverage,
+}
+
+/// Levels of detail of one mesh or model, most detailed first, each with
+/// the threshold up to which it's drawn. Past the last threshold nothing
+/// is, end on `f32::INFINITY` or `0.0` to never cull.
+/// ```ignore
+/// let lod = Lod::new(LodMetric::ScreenCoverage)
+///     .with_level(geometry.to_mesh(), 0.3)
+///     .with_level(geometry.simplified(0.5).to_mesh(), 0.1)
+///     .with_level(geometry.simplified(0.1).to_mesh(), 0.0);
+/// scene.add_root(Node::new(transform).with_mesh(Rc::new(lod)));
+/// scene.draw_lod(&program, &LodView::from_matrices(&view, &projection))?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lod<T> {
+    levels: Vec<(T, f32)>,
+    metric: LodMetric,
+}
+
+impl<T> Lod<T> {
+    pub fn new(metric: LodMetric) -> Self {
+        Self {
+            levels: Vec::new(),
+            metric,
+        }
+    }
+
+    /// Adds a level less detailed than the previous ones.
+    pub fn with_level(mut self, level: T, threshold: f32) -> Self {
+        self.levels.push((level, threshold));
+        self
+    }
+
+    pub fn levels(&self) -> &[(T, f32)] {
+        &self.levels
+    }
+
+    pub fn metric(&self) -> LodMetric {
+        self.metric
+    }
+
+    /// Index of the level to draw for world space bounds `sphere`, `None`
+    /// when it's past the last threshold.
+    pub fn select_index(&self, view: &LodView, sphere: &Sphere) -> Option<usize> {
+        match self.metric {
+            LodMetric::Distance => {
+                let distance = view.distance(sphere) * view.bias;
+                self.levels
+                    .iter()
+                    .position(|&(_, threshold)| distance <= threshold)
+            }
+            LodMetric::ScreenCoverage => {
+                let coverage = view.coverage(sphere) / view.bias;
+                self.levels
+                    .iter()
+                    .position(|&(_, threshold)| coverage >= threshold)
+            }
+        }
+    }
+
+    pub fn select(&self, view: &LodView, sphere: &Sphere) -> Option<&T> {
+        Some(&self.levels[self.select_index(view, sphere)?].0)
+    }
+}
+
+impl<T: Drawable> Lod<T> {
+    /// World space sphere around the most detailed level placed with
+    /// `model`.
+    pub fn world_bounding_sphere(&self, model: &Matrix4<f32>) -> Option<Sphere> {
+        Some(self.levels.first()?.0.bounding_sphere()?.transformed(model))
+    }
+
+    /// Sorts instance `models` by the level drawing them, to upload one
+    /// instance buffer per level. Instances past the last threshold are
+    /// left out, and all use the first level without bounds. An empty
+    /// `Lod` drops them all.
+    pub fn instances_per_level(
+        &self,
+        view: &LodView,
+        models: &[Matrix4<f32>],
+    ) -> Vec<Vec<Matrix4<f32>>> {
+        let mut levels = vec![Vec::new(); self.levels.len()];
+        for model in models {
+            let level = match self.world_bounding_sphere(model) {
+                Some(sphere) => self.select_index(view, &sphere),
+                None => Some(0),
+            };
+            if let Some(level) = level {
+                levels[level].push(*model);
+            }
+        }
+        levels
+    }
+}
+
+/// Draws the most detailed level, or the one [selected](Lod::select) in
+/// [`draw_lod`](Drawable::draw_lod).
+impl<T: Drawable> Drawable for Lod<T> {
+    fn draw(&self) {
+        if let Some((level, _)) = self.levels.first() {
+            level.draw();
+        }
+    }
+
+    fn draw_lod(&self, view: &LodView, model: &Matrix4<f32>) {
+        let level = match self.world_bounding_sphere(model) {
+            Some(sphere) => self.select(view, &sphere),
+            None => self.levels.first().map(|(level, _)| level),
+        };
+        if let Some(level) = level {
+            level.draw_lod(view, model);
+        }
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.levels.first()?.0.bounds()
+    }
+
+    fn bounding_sphere(&self) -> Option<Sphere> {
+        self.levels.first()?.0.bounding_sphere()
+    }
+
+    fn material(&self) -> Option<&Rc<Material>> {
+        self.levels.first()?.0.material()
+    }
+}
+
+/// Triangles of a coarser version of a mesh, with about `ratio` of its
+/// triangles, indexing the same vertices. Compact them with
+/// [`optimize`](crate::optimize::optimize).
+///
+/// Vertices are clustered on the finest grid over the bounds that gets
+/// down to the target, each cluster merged into its first vertex, so
+/// texture seams and sharp edges blur with distance.
+pub fn simplify(positions: &[[f32; 3]], indices: &[u32], ratio: f32) -> Vec<u32> {
+    let triangles = indices.len() / 3;
+    let target = (triangles as f32 * ratio.clamp(0.0, 1.0)).round() as usize;
+    let Some(bounds) = Aabb::from_points(positions.iter().map(|&p| p.into())) else {
+        return Vec::new();
+    };
+    if target >= triangles {
+        return indices.to_vec();
+    }
+
+    // finer grids keep more triangles, search for the finest small enough
+    let (mut low, mut high) = (1, MAX_GRID);
+    let mut simplified = cluster(positions, indices, &bounds, 1);
+    while low <= high {
+        let grid = (low + high) / 2;
+        let candidate = cluster(positions, indices, &bounds, grid);
+        match candidate.len() / 3 <= target {
+            true => {
+                simplified = candidate;
+                low = grid + 1;
+            }
+            false => high = grid - 1,
+        }
+    }
+    simplified
+}
+
+/// Triangles left after merging the vertices in each cell of a `grid`
+/// cells wide grid over `bounds`, without the collapsed or duplicated ones.
+fn cluster(positions: &[[f32; 3]], indices: &[u32], bounds: &Aabb, grid: u32) -> Vec<u32> {
+    let size = bounds.max - bounds.min;
+    let cell = |position: [f32; 3]| {
+        let axis = |i: usize| match size[i] > 0.0 {
+            true => (((position[i] - bounds.min[i]) / size[i] * grid as f32) as u32).min(grid - 1),
+            false => 0,
+        };
+        (axis(0), axis(1), axis(2))
+    };
+    let mut representatives = HashMap::new();
+    let remap: Vec<u32> = positions
+        .iter()
+        .enumerate()
+        .map(|(index, &position)| {
+            *representatives
+                .entry(cell(position))
+                .or_insert(index as u32)
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut simplified = Vec::new();
+    for face in indices.chunks_exact(3) {
+        let [a, b, c] = [face[0], face[1], face[2]].map(|index| remap[index as usize]);
+        if a == b || b == c || c == a {
+            continue;
+        }
+        // the same triangle rotated to start on its smallest index
+        let key = match a.min(b).min(c) {
+            min if min == a => [a, b, c],
+            min if min == b => [b, c, a],
+            _ => [c, a, b],
+        };
+        if seen.insert(key) {
+            simplified.extend(key);
+        }
+    }
+    simplified
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, PerspectiveFov, Point3};
+
+    use super::*;
+
+    fn view(eye: Point3<f32>) -> LodView {
+        LodView {
+            eye,
+            projection_scale: 1.0,
+            bias: 1.0,
+        }
+    }
+
+    fn plane(size: u32) -> (Vec<[f32; 3]>, Vec<u32>) {
+        let positions = (0..(size + 1) * (size + 1))
+            .map(|i| [(i % (size + 1)) as f32, (i / (size + 1)) as f32, 0.0])
+            .collect();
+        let mut indices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let corner = y * (size + 1) + x;
+                let (right, up) = (corner + 1, corner + size + 1);
+                indices.extend([corner, right, up, right, up + 1, up]);
+            }
+        }
+        (positions, indices)
+    }
+
+    #[test]
+    fn selects_levels_by_distance() {
+        let lod = Lod::new(LodMetric::Distance)
+            .with_level('a', 10.0)
+            .with_level('b', 20.0);
+        let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        let at = |z| lod.select_index(&view(Point3::new(0.0, 0.0, z)), &sphere);
+        assert_eq!(at(0.5), Some(0));
+        assert_eq!(at(11.0), Some(0));
+        assert_eq!(at(15.0), Some(1));
+        assert_eq!(at(25.0), None);
+        let biased = view(Point3::new(0.0, 0.0, 8.0)).with_bias(2.0);
+        assert_eq!(lod.select_index(&biased, &sphere), Some(1));
+    }
+
+    #[test]
+    fn selects_levels_by_screen_coverage() {
+        let lod = Lod::new(LodMetric::ScreenCoverage)
+            .with_level('a', 0.5)
+            .with_level('b', 0.0);
+        let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0);
+        let at = |z| lod.select_index(&view(Point3::new(0.0, 0.0, z)), &sphere);
+        assert_eq!(at(0.5), Some(0));
+        assert_eq!(at(1.5), Some(0));
+        assert_eq!(at(4.0), Some(1));
+        assert_eq!(at(1000.0), Some(1));
+    }
+
+    #[test]
+    fn coverage_follows_the_field_of_view() {
+        let projection: Matrix4<f32> = PerspectiveFov {
+            fovy: Deg(90.0).into(),
+            aspect: 1.0,
+            near: 0.1,
+            far: 100.0,
+        }
+        .into();
+        let view = LodView::new(Point3::new(0.0, 0.0, 10.0), &projection);
+        let coverage = view.coverage(&Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0));
+        assert!((coverage - 0.1).abs() < 1e-5);
+    }
+
+    #[test]
+    fn simplify_reaches_the_ratio() {
+        let (positions, indices) = plane(16);
+        let simplified = simplify(&positions, &indices, 0.25);
+        let triangles = simplified.len() / 3;
+        assert!(triangles > 0 && triangles <= indices.len() / 3 / 4);
+        assert!(simplified
+            .iter()
+            .all(|&index| (index as usize) < positions.len()));
+    }
+
+    #[test]
+    fn simplify_drops_degenerate_triangles() {
+        let (positions, indices) = plane(16);
+        for face in simplify(&positions, &indices, 0.1).chunks_exact(3) {
+            assert!(face[0] != face[1] && face[1] != face[2] && face[2] != face[0]);
+        }
+    }
+
+    #[test]
+    fn simplify_keeps_meshes_at_full_ratio() {
+        let (positions, indices) = plane(4);
+        assert_eq!(simplify(&positions, &indices, 1.0), indices);
+        assert!(simplify(&[], &[], 0.5).is_empty());
+    }
+}
@@ -1,119 +1,121 @@
-use std::{ffi::c_void, sync::mpsc::Receiver};
-
-use gl::types::{GLfloat, GLsizei, GLsizeiptr};
-use glfw::Context;
-use learngl::shader::{Shader, ShaderProgram};
-
-fn main() {
-    let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
-    glfw.window_hint(glfw::WindowHint::ContextVersionMajor(3));
-    glfw.window_hint(glfw::WindowHint::ContextVersionMinor(3));
-    glfw.window_hint(glfw::WindowHint::OpenGlProfile(
-        glfw::OpenGlProfileHint::Core,
-    ));
-    #[cfg(target_os = "macos")]
-    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
-
-    let (mut window, events) = glfw
-        .create_window(800, 600, "LearnOpenGl", glfw::WindowMode::Windowed)
-        .expect("Failed to create GLFW window");
-
-    window.make_current();
-    window.set_key_polling(true);
-    window.set_framebuffer_size_polling(true);
-
-    gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
-
-    let vertices: [f32; 18] = [
-        // positions    // colors
-        0.5, 0.5, 0.0, 1.0, 0.0, 0.0, // top right
-        0.5, -0.5, 0.0, 0.0, 1.0, 0.0, // bottom right
-        -0.5, -0.5, 0.0, 0.0, 0.0, 1.0, // bottom left
-    ];
-
-    let vertex_shader = Shader::from_path("shaders/shader.vs").unwrap();
-    let fragment_shader = Shader::from_path("shaders/shader.fs").unwrap();
-    let shader_program = ShaderProgram::builder()
-        .attach_shader(&vertex_shader)
-        .attach_shader(&fragment_shader)
-        .build()
-        .unwrap();
-
-    let vao = unsafe {
-        let mut vao = 0;
-        let mut vbo = 0;
-
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
-
-        gl::BindVertexArray(vao);
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(
-            gl::ARRAY_BUFFER,
-            std::mem::size_of_val(&vertices) as GLsizeiptr,
-            &vertices[0] as *const f32 as *const c_void,
-            gl::STATIC_DRAW,
-        );
-
-        gl::VertexAttribPointer(
-            0,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            6 * std::mem::size_of::<gl::types::GLfloat>() as GLsizei,
-            std::ptr::null(),
-        );
-        gl::EnableVertexAttribArray(0);
-
-        gl::VertexAttribPointer(
-            1,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            6 * std::mem::size_of::<GLfloat>() as GLsizei,
-            (3 * std::mem::size_of::<GLfloat>()) as *const c_void,
-        );
-        gl::EnableVertexAttribArray(1);
+use learngl::{
+    app::{App, AppConfig, AppContext},
+    capture,
+    errors::GLWError,
+    mesh::Mesh,
+    shader::{Shader, ShaderProgram},
+    texture::Texture2D,
+    vertex::{Vertex, VertexLayout},
+};
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct ColoredVertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
 
-        gl::BindVertexArray(0);
+// what #[derive(Vertex)] generates with the `derive` feature
+impl Vertex for ColoredVertex {
+    fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .push_field::<[f32; 3]>(std::mem::offset_of!(Self, position), false)
+            .push_field::<[f32; 3]>(std::mem::offset_of!(Self, color), false)
+            .with_stride(std::mem::size_of::<Self>())
+    }
+}
 
-        //gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+struct Triangle {
+    shader_program: ShaderProgram,
+    mesh: Mesh<ColoredVertex>,
+    /// Last image dropped onto the window.
+    dropped_texture: Option<Texture2D>,
+    take_screenshot: bool,
+}
 
-        vao
-    };
+impl App for Triangle {
+    fn init(_ctx: &mut AppContext) -> Result<Self, GLWError> {
+        let vertices = [
+            ColoredVertex {
+                position: [0.5, 0.5, 0.0],
+                color: [1.0, 0.0, 0.0],
+            }, // top right
+            ColoredVertex {
+                position: [0.5, -0.5, 0.0],
+                color: [0.0, 1.0, 0.0],
+            }, // bottom right
+            ColoredVertex {
+                position: [-0.5, -0.5, 0.0],
+                color: [0.0, 0.0, 1.0],
+            }, // bottom left
+        ];
+
+        let vertex_shader = Shader::from_path("shaders/shader.vs")?;
+        let fragment_shader = Shader::from_path("shaders/shader.fs")?;
+        let mut shader_program = ShaderProgram::builder()
+            .attach_shader(&vertex_shader)
+            .attach_shader(&fragment_shader)
+            .build()?;
+        shader_program.watch()?;
+
+        //learngl::context::set_polygon_mode(gl::LINE);
+
+        Ok(Self {
+            shader_program,
+            mesh: Mesh::from_vertices(&vertices, None),
+            dropped_texture: None,
+            take_screenshot: false,
+        })
+    }
 
-    while !window.should_close() {
-        // handle events
-        process_events(&mut window, &events);
+    fn update(&mut self, ctx: &mut AppContext, _dt: f32) {
+        if ctx.input.just_pressed(glfw::Key::Escape) {
+            ctx.close();
+        }
+        if ctx.input.just_pressed(glfw::Key::F11) {
+            ctx.toggle_fullscreen();
+        }
+        self.take_screenshot = ctx.input.just_pressed(glfw::Key::F12);
+        if let Some(report) = ctx.stats.report() {
+            ctx.set_title_fmt(format_args!("LearnOpenGl - {report}"));
+        }
+        for path in ctx.input.dropped_files() {
+            match Texture2D::from_path(path) {
+                Ok(texture) => {
+                    println!(
+                        "loaded {} ({}x{})",
+                        path.display(),
+                        texture.width(),
+                        texture.height()
+                    );
+                    self.dropped_texture = Some(texture);
+                }
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+        if let Err(err) = self.shader_program.reload_if_changed() {
+            eprintln!("{err}");
+        }
+    }
 
-        // rendering commands
+    fn render(&mut self, _ctx: &mut AppContext) {
         unsafe {
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
-
-            shader_program.use_program();
-            gl::BindVertexArray(vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, 3);
-
-            gl::BindVertexArray(0);
         }
+        self.shader_program.use_program();
+        self.mesh.draw();
 
-        // check and call events and swap the buffers
-        window.swap_buffers();
-        glfw.poll_events();
+        if self.take_screenshot {
+            if let Err(err) = capture::screenshot("screenshot.png") {
+                eprintln!("{err}");
+            }
+        }
     }
 }
 
-fn process_events(window: &mut glfw::Window, events: &Receiver<(f64, glfw::WindowEvent)>) {
-    for (_, event) in glfw::flush_messages(events) {
-        match event {
-            glfw::WindowEvent::FramebufferSize(width, height) => unsafe {
-                gl::Viewport(0, 0, width, height)
-            },
-            glfw::WindowEvent::Key(glfw::Key::Escape, _, glfw::Action::Press, _) => {
-                window.set_should_close(true)
-            }
-            _ => {}
-        }
+fn main() {
+    if let Err(err) = learngl::run::<Triangle>(AppConfig::default()) {
+        eprintln!("{err}");
     }
 }
@@ -0,0 +1,227 @@
+use std::rc::Rc;
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    shader::ShaderProgram,
+    texture::Texture2D,
+};
+
+/// Uniform value of a [`Material`] parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaterialParam {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+}
+
+impl From<bool> for MaterialParam {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for MaterialParam {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f32> for MaterialParam {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<[f32; 2]> for MaterialParam {
+    fn from(value: [f32; 2]) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<[f32; 3]> for MaterialParam {
+    fn from(value: [f32; 3]) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<[f32; 4]> for MaterialParam {
+    fn from(value: [f32; 4]) -> Self {
+        Self::Vec4(value)
+    }
+}
+
+/// How a material's alpha channel is treated.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    #[default]
+    Opaque,
+    /// Fragments with less alpha than the cutoff are discarded.
+    Mask(f32),
+    /// Blended over what's behind, drawn after the opaque meshes.
+    Blend,
+}
+
+/// Surface of a mesh: the program drawing it, its textures and its scalar
+/// parameters, each set on the uniform of the same name by
+/// [`bind`](Self::bind).
+///
+/// Textures are shared between materials through `Rc`:
+/// ```ignore
+/// let material = Material::new(program)
+///     .with_texture("material.diffuse", diffuse)
+///     .with_param("material.shininess", 32.0);
+/// material.bind()?;
+/// mesh.draw();
+/// ```
+#[derive(Clone, Default)]
+pub struct Material {
+    pub name: Option<String>,
+    program: Option<Rc<ShaderProgram>>,
+    textures: Vec<(String, Rc<Texture2D>)>,
+    params: Vec<(String, MaterialParam)>,
+    /// Left to the renderer, `bind` doesn't touch the blend state.
+    pub alpha_mode: AlphaMode,
+    /// Whether back faces are visible too, left to the renderer like
+    /// `alpha_mode`.
+    pub double_sided: bool,
+}
+
+impl Material {
+    pub fn new(program: Rc<ShaderProgram>) -> Self {
+        Self {
+            program: Some(program),
+            ..Self::default()
+        }
+    }
+
+    /// Material without a program, as loaded from model files. Give it one
+    /// with [`set_program`](Self::set_program) or bind it with
+    /// [`bind_with`](Self::bind_with).
+    pub fn without_program() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Binds `texture` to the sampler uniform `slot`, e.g.
+    /// `material.diffuse`, replacing the texture it had.
+    pub fn with_texture(mut self, slot: impl Into<String>, texture: Rc<Texture2D>) -> Self {
+        self.set_texture(slot, texture);
+        self
+    }
+
+    /// Sets the uniform `name` to `value`, replacing the value it had.
+    pub fn with_param(mut self, name: impl Into<String>, value: impl Into<MaterialParam>) -> Self {
+        self.set_param(name, value);
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    pub fn with_double_sided(mut self, double_sided: bool) -> Self {
+        self.double_sided = double_sided;
+        self
+    }
+
+    pub fn program(&self) -> Option<&Rc<ShaderProgram>> {
+        self.program.as_ref()
+    }
+
+    pub fn set_program(&mut self, program: Rc<ShaderProgram>) {
+        self.program = Some(program);
+    }
+
+    pub fn set_texture(&mut self, slot: impl Into<String>, texture: Rc<Texture2D>) {
+        let slot = slot.into();
+        match self.textures.iter_mut().find(|(name, _)| *name == slot) {
+            Some((_, existing)) => *existing = texture,
+            None => self.textures.push((slot, texture)),
+        }
+    }
+
+    pub fn set_param(&mut self, name: impl Into<String>, value: impl Into<MaterialParam>) {
+        let (name, value) = (name.into(), value.into());
+        match self.params.iter_mut().find(|(param, _)| *param == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.params.push((name, value)),
+        }
+    }
+
+    pub fn texture(&self, slot: &str) -> Option<&Rc<Texture2D>> {
+        self.textures
+            .iter()
+            .find(|(name, _)| name == slot)
+            .map(|(_, texture)| texture)
+    }
+
+    pub fn param(&self, name: &str) -> Option<MaterialParam> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn textures(&self) -> impl Iterator<Item = (&str, &Rc<Texture2D>)> {
+        self.textures
+            .iter()
+            .map(|(slot, texture)| (slot.as_str(), texture))
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, MaterialParam)> {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Uses the material's program and sets its textures and parameters,
+    /// ready for a draw. Fails with
+    /// [`MaterialWithoutProgram`](GLWErrorKind::MaterialWithoutProgram)
+    /// if it has no program.
+    pub fn bind(&self) -> Result<(), GLWError> {
+        match &self.program {
+            Some(program) => self.bind_with(program),
+            None => Err(GLWErrorKind::MaterialWithoutProgram(self.name.clone()))?,
+        }
+    }
+
+    /// Like [`bind`](Self::bind) with `program` instead of the material's.
+    /// Uniforms `program` lacks are skipped, so one material suits several
+    /// programs, e.g. a depth-only pass.
+    pub fn bind_with(&self, program: &ShaderProgram) -> Result<(), GLWError> {
+        for (slot, texture) in &self.textures {
+            skip_missing(program.bind_texture(slot, texture.as_ref()))?;
+        }
+        for (name, value) in &self.params {
+            skip_missing(match *value {
+                MaterialParam::Bool(value) => program.set(name, &value),
+                MaterialParam::Int(value) => program.set(name, &value),
+                MaterialParam::Float(value) => program.set(name, &value),
+                MaterialParam::Vec2(value) => program.set(name, &value),
+                MaterialParam::Vec3(value) => program.set(name, &value),
+                MaterialParam::Vec4(value) => program.set(name, &value),
+            })?;
+        }
+        if let AlphaMode::Mask(cutoff) = self.alpha_mode {
+            skip_missing(program.set_f32("material.alphaCutoff", cutoff))?;
+        }
+        program.use_program();
+        Ok(())
+    }
+}
+
+/// Treats a uniform missing from the program as set.
+pub(crate) fn skip_missing<T>(result: Result<T, GLWError>) -> Result<(), GLWError> {
+    match result {
+        Err(err) if !matches!(err.kind(), GLWErrorKind::UniformNotFound(_)) => Err(err),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,211 @@
+use std::rc::Rc;
+
+use cgmath::Matrix4;
+
+use crate::{
+    bounds::{Aabb, Sphere},
+    buffer::{Buffer, BufferTarget, BufferUsage, IndexBuffer, InstanceBuffer},
+    draw::{self, Primitive},
+    errors::GLWError,
+    frustum::Frustum,
+    material::Material,
+    ray::Ray,
+    vertex::{Vertex, VertexLayout},
+    vertex_array::VertexArray,
+};
+
+/// Vertex data uploaded to the GPU together with the vertex array describing
+/// it, drawn with a single call.
+pub struct Mesh<T> {
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer<T>,
+    index_buffer: Option<IndexBuffer<u32>>,
+    primitive: Primitive,
+    vertex_count: usize,
+    /// Object space bounds, for culling.
+    bounds: Option<Aabb>,
+    bounding_sphere: Option<Sphere>,
+    material: Option<Rc<Material>>,
+}
+
+impl<T: Copy> Mesh<T> {
+    /// Creates a triangle mesh, indexed if `indices` are given. `vertices`
+    /// can also be flat component data, the vertex count is derived from the
+    /// stride of `layout`.
+    pub fn new(vertices: &[T], indices: Option<&[u32]>, layout: &VertexLayout) -> Self {
+        let vertex_array = VertexArray::new();
+        let vertex_buffer = Buffer::new(BufferTarget::Array, vertices, BufferUsage::Static);
+        vertex_array.attach_buffer(&vertex_buffer, layout);
+
+        let index_buffer = indices.map(|indices| {
+            let index_buffer = IndexBuffer::new(indices, BufferUsage::Static);
+            vertex_array.attach_index_buffer(&index_buffer);
+            index_buffer
+        });
+
+        Self {
+            vertex_array,
+            vertex_buffer,
+            index_buffer,
+            primitive: Primitive::Triangles,
+            vertex_count: std::mem::size_of_val(vertices) / layout.stride().max(1),
+            bounds: None,
+            bounding_sphere: None,
+            material: None,
+        }
+    }
+}
+
+impl<V: Vertex> Mesh<V> {
+    /// Creates a triangle mesh laid out as described by [`Vertex::layout`].
+    pub fn from_vertices(vertices: &[V], indices: Option<&[u32]>) -> Self {
+        Self::new(vertices, indices, &V::layout())
+    }
+}
+
+impl<T> Mesh<T> {
+    pub fn with_primitive(mut self, primitive: Primitive) -> Self {
+        self.primitive = primitive;
+        self
+    }
+
+    /// Sets the object space bounds, e.g. from
+    /// [`Aabb::from_points`] over the vertex positions.
+    pub fn with_bounds(mut self, bounds: Aabb) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_bounding_sphere(mut self, sphere: Sphere) -> Self {
+        self.bounding_sphere = Some(sphere);
+        self
+    }
+
+    /// Sets both the bounds and the bounding sphere around `positions`.
+    pub fn with_bounds_of(mut self, positions: impl IntoIterator<Item = [f32; 3]>) -> Self {
+        let positions: Vec<_> = positions.into_iter().map(Into::into).collect();
+        self.bounds = Aabb::from_points(positions.iter().copied());
+        self.bounding_sphere = Sphere::from_points(positions);
+        self
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.bounds
+    }
+
+    /// Object space bounding sphere, or the one around the bounds if none
+    /// was set.
+    pub fn bounding_sphere(&self) -> Option<Sphere> {
+        self.bounding_sphere
+            .or_else(|| Some(self.bounds?.bounding_sphere()))
+    }
+
+    /// Sets the material [`draw_with_material`](Self::draw_with_material)
+    /// binds.
+    pub fn with_material(mut self, material: Rc<Material>) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    pub fn material(&self) -> Option<&Rc<Material>> {
+        self.material.as_ref()
+    }
+
+    pub fn set_material(&mut self, material: Option<Rc<Material>>) {
+        self.material = material;
+    }
+
+    /// Whether the mesh placed with `model` may be inside `frustum`. Meshes
+    /// without bounds always are.
+    pub fn is_visible(&self, frustum: &Frustum, model: &Matrix4<f32>) -> bool {
+        // the sphere is cheaper to test, and rejects most meshes it can
+        let outside_sphere = self
+            .bounding_sphere
+            .is_some_and(|sphere| !frustum.intersects_sphere(&sphere.transformed(model)));
+        !outside_sphere
+            && self
+                .bounds
+                .is_none_or(|bounds| frustum.intersects_aabb(&bounds.transformed(model)))
+    }
+
+    /// Distance along `ray` to the mesh's bounds, placed with `model`.
+    /// `None` for a miss or without bounds, narrow it down with
+    /// [`Ray::intersects_triangles`] when boxes aren't precise enough.
+    pub fn intersects_ray(&self, ray: &Ray, model: &Matrix4<f32>) -> Option<f32> {
+        ray.intersects_aabb(&self.bounds?.transformed(model))
+    }
+
+    /// Draws the mesh unless it's outside `frustum`, returning whether it
+    /// drew.
+    pub fn draw_culled(&self, frustum: &Frustum, model: &Matrix4<f32>) -> bool {
+        let visible = self.is_visible(frustum, model);
+        if visible {
+            self.draw();
+        }
+        visible
+    }
+
+    pub fn draw(&self) {
+        self.vertex_array.bind();
+        match &self.index_buffer {
+            Some(index_buffer) => index_buffer.draw(self.primitive),
+            None => draw::draw_arrays(self.primitive, 0, self.vertex_count()),
+        }
+        self.vertex_array.unbind();
+    }
+
+    /// [Binds](Material::bind) the mesh's material, if it has one, then
+    /// draws.
+    pub fn draw_with_material(&self) -> Result<(), GLWError> {
+        if let Some(material) = &self.material {
+            material.bind()?;
+        }
+        self.draw();
+        Ok(())
+    }
+
+    /// Adds per-instance attributes to the mesh. The mesh only references
+    /// `instances`, which must outlive the draw calls.
+    pub fn attach_instances<I>(&self, instances: &InstanceBuffer<I>) {
+        self.vertex_array.attach_instance_buffer(instances);
+    }
+
+    pub fn draw_instanced(&self, instances: usize) {
+        self.vertex_array.bind();
+        match &self.index_buffer {
+            Some(index_buffer) => index_buffer.draw_elements_instanced(
+                self.primitive,
+                index_buffer.len(),
+                0,
+                instances,
+            ),
+            None => draw::draw_arrays_instanced(self.primitive, 0, self.vertex_count(), instances),
+        }
+        self.vertex_array.unbind();
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// Number of indices, or `None` for a non-indexed mesh.
+    pub fn index_count(&self) -> Option<usize> {
+        self.index_buffer.as_ref().map(IndexBuffer::len)
+    }
+
+    pub fn primitive(&self) -> Primitive {
+        self.primitive
+    }
+
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vertex_array
+    }
+
+    pub fn vertex_buffer(&self) -> &Buffer<T> {
+        &self.vertex_buffer
+    }
+
+    pub fn index_buffer(&self) -> Option<&IndexBuffer<u32>> {
+        self.index_buffer.as_ref()
+    }
+}
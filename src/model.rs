@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use cgmath::Matrix4;
+
+use crate::{
+    bounds::{Aabb, Sphere},
+    errors::{GLWError, GLWErrorKind},
+    frustum::Frustum,
+    lod,
+    material::Material,
+    mesh::Mesh,
+    normals, optimize,
+    ray::Ray,
+    shader::ShaderProgram,
+    texture::Texture2D,
+    vertex::{Vertex, VertexLayout},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct ModelVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coords: [f32; 2],
+    /// Tangent with the bitangent's sign in `w`, see [`normals::tangents`].
+    pub tangent: [f32; 4],
+}
+
+impl ModelVertex {
+    /// Bit patterns of every component, to compare or hash vertices
+    /// exactly, e.g. to [weld](optimize::weld) them.
+    pub fn to_bits(&self) -> [u32; 12] {
+        let mut bits = [0; 12];
+        let components = self
+            .position
+            .iter()
+            .chain(&self.normal)
+            .chain(&self.tex_coords)
+            .chain(&self.tangent);
+        for (bits, component) in bits.iter_mut().zip(components) {
+            *bits = component.to_bits();
+        }
+        bits
+    }
+}
+
+impl Vertex for ModelVertex {
+    fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .push_field::<[f32; 3]>(std::mem::offset_of!(Self, position), false)
+            .push_field::<[f32; 3]>(std::mem::offset_of!(Self, normal), false)
+            .push_field::<[f32; 2]>(std::mem::offset_of!(Self, tex_coords), false)
+            .push_field::<[f32; 4]>(std::mem::offset_of!(Self, tangent), false)
+            .with_stride(std::mem::size_of::<Self>())
+    }
+}
+
+/// Meshes loaded from a model file, one per material.
+pub struct Model {
+    pub meshes: Vec<Mesh<ModelVertex>>,
+    /// Materials read from the MTL file, without a program, shared with
+    /// the meshes using them.
+    pub materials: Vec<Rc<Material>>,
+    bounds: Option<Aabb>,
+    bounding_sphere: Option<Sphere>,
+}
+
+impl Model {
+    /// Loads a Wavefront OBJ file along with its MTL materials and their
+    /// textures, resolved relative to the file. Faces are triangulated,
+    /// missing normals are smoothed from the faces, and the meshes are
+    /// [optimized](optimize::optimize) for drawing.
+    pub fn from_obj(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        let mut models = Self::from_obj_lods(path, &[1.0])?;
+        Ok(models.remove(0))
+    }
+
+    /// Loads an OBJ file as [`from_obj`](Self::from_obj) does, once per
+    /// entry of `ratios`, [simplified](lod::simplify) to about that share
+    /// of its triangles, e.g. for the levels of a [`Lod`](lod::Lod). The
+    /// materials are shared between the models.
+    pub fn from_obj_lods(path: impl AsRef<Path>, ratios: &[f32]) -> Result<Vec<Self>, GLWError> {
+        let path = path.as_ref();
+        let (obj_models, obj_materials) =
+            tobj::load_obj(path).map_err(|source| GLWErrorKind::ModelLoadFailed {
+                path: path.to_path_buf(),
+                source,
+            })?;
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut textures = HashMap::new();
+        let materials: Vec<_> = obj_materials
+            .iter()
+            .map(|material| load_material(material, directory, &mut textures).map(Rc::new))
+            .collect::<Result<_, _>>()?;
+
+        // objects sharing a material are merged to draw them at once
+        let mut groups: Vec<(Option<usize>, Vec<ModelVertex>, Vec<u32>)> = Vec::new();
+        for model in &obj_models {
+            let material = model.mesh.material_id;
+            let index = match groups.iter().position(|group| group.0 == material) {
+                Some(index) => index,
+                None => {
+                    groups.push((material, Vec::new(), Vec::new()));
+                    groups.len() - 1
+                }
+            };
+            let (_, vertices, indices) = &mut groups[index];
+            let offset = vertices.len() as u32;
+            vertices.extend(vertices_of(&model.mesh));
+            indices.extend(model.mesh.indices.iter().map(|index| index + offset));
+        }
+
+        let groups: Vec<_> = groups
+            .into_iter()
+            .filter(|(_, vertices, _)| !vertices.is_empty())
+            .map(|(material, mut vertices, mut indices)| {
+                optimize::optimize(
+                    &mut vertices,
+                    &mut indices,
+                    ModelVertex::to_bits,
+                    |vertex| vertex.position,
+                );
+                let positions: Vec<_> = vertices.iter().map(|vertex| vertex.position).collect();
+                (material, vertices, indices, positions)
+            })
+            .collect();
+
+        let model = |ratio: f32| {
+            let meshes = groups
+                .iter()
+                .filter_map(|(material, vertices, indices, positions)| {
+                    let mut vertices = vertices.clone();
+                    let mut indices = match ratio < 1.0 {
+                        true => lod::simplify(positions, indices, ratio),
+                        false => indices.clone(),
+                    };
+                    if indices.is_empty() {
+                        return None;
+                    }
+                    // the welded vertices are already optimized at full detail
+                    if ratio < 1.0 {
+                        optimize::optimize(
+                            &mut vertices,
+                            &mut indices,
+                            ModelVertex::to_bits,
+                            |vertex| vertex.position,
+                        );
+                    }
+                    let mesh = Mesh::from_vertices(&vertices, Some(&indices))
+                        .with_bounds_of(vertices.iter().map(|vertex| vertex.position));
+                    Some(match material.and_then(|index| materials.get(index)) {
+                        Some(material) => mesh.with_material(Rc::clone(material)),
+                        None => mesh,
+                    })
+                })
+                .collect();
+            Self::from_meshes(meshes, materials.clone())
+        };
+        Ok(ratios.iter().map(|&ratio| model(ratio)).collect())
+    }
+
+    /// Model of `meshes`, bounded by their bounds.
+    pub(crate) fn from_meshes(
+        meshes: Vec<Mesh<ModelVertex>>,
+        materials: Vec<Rc<Material>>,
+    ) -> Self {
+        let bounds = meshes
+            .iter()
+            .filter_map(Mesh::bounds)
+            .reduce(|a, b| a.union(&b));
+        let bounding_sphere = meshes
+            .iter()
+            .filter_map(Mesh::bounding_sphere)
+            .reduce(|a, b| a.union(&b));
+        Self {
+            meshes,
+            materials,
+            bounds,
+            bounding_sphere,
+        }
+    }
+
+    /// Object space bounds of all meshes.
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.bounds
+    }
+
+    /// Object space sphere around all meshes.
+    pub fn bounding_sphere(&self) -> Option<Sphere> {
+        self.bounding_sphere
+    }
+
+    /// Whether the model placed with `model` may be inside `frustum`.
+    pub fn is_visible(&self, frustum: &Frustum, model: &Matrix4<f32>) -> bool {
+        let outside_sphere = self
+            .bounding_sphere
+            .is_some_and(|sphere| !frustum.intersects_sphere(&sphere.transformed(model)));
+        !outside_sphere
+            && self
+                .bounds
+                .is_none_or(|bounds| frustum.intersects_aabb(&bounds.transformed(model)))
+    }
+
+    /// Distance along `ray` to the nearest mesh's bounds, placed with
+    /// `model`, with the mesh's index.
+    pub fn intersects_ray(&self, ray: &Ray, model: &Matrix4<f32>) -> Option<(usize, f32)> {
+        self.meshes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, mesh)| Some((index, mesh.intersects_ray(ray, model)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Draws every mesh with `program`, [binding](Material::bind_with) the
+    /// mesh's material first. The MTL textures go to the
+    /// `material.diffuse`, `material.specular` and `material.normal`
+    /// samplers, the colors to `material.ambientColor`,
+    /// `material.diffuseColor` and `material.specularColor`, and the
+    /// exponent to `material.shininess`.
+    pub fn draw(&self, program: &ShaderProgram) -> Result<(), GLWError> {
+        for mesh in &self.meshes {
+            if let Some(material) = mesh.material() {
+                material.bind_with(program)?;
+            }
+            program.use_program();
+            mesh.draw();
+        }
+        Ok(())
+    }
+}
+
+fn load_material(
+    material: &tobj::Material,
+    directory: &Path,
+    textures: &mut HashMap<PathBuf, Rc<Texture2D>>,
+) -> Result<Material, GLWError> {
+    let mut texture = |file: &str, srgb: bool| -> Result<_, GLWError> {
+        match file.is_empty() {
+            true => Ok(None),
+            false => cached_texture(&directory.join(file), srgb, textures).map(Some),
+        }
+    };
+
+    let mut model_material = Material::without_program()
+        .with_name(&material.name)
+        .with_param("material.ambientColor", material.ambient)
+        .with_param("material.diffuseColor", material.diffuse)
+        .with_param("material.specularColor", material.specular)
+        .with_param("material.shininess", material.shininess);
+    let slots = [
+        ("material.diffuse", &material.diffuse_texture, true),
+        ("material.specular", &material.specular_texture, false),
+        ("material.normal", &material.normal_texture, false),
+    ];
+    for (slot, file, srgb) in slots {
+        if let Some(texture) = texture(file, srgb)? {
+            model_material.set_texture(slot, texture);
+        }
+    }
+    Ok(model_material)
+}
+
+/// Texture at `path` from `textures`, loaded into it on first use. Color
+/// maps are `srgb`, the others hold linear data.
+pub(crate) fn cached_texture(
+    path: &Path,
+    srgb: bool,
+    textures: &mut HashMap<PathBuf, Rc<Texture2D>>,
+) -> Result<Rc<Texture2D>, GLWError> {
+    if let Some(texture) = textures.get(path) {
+        return Ok(texture.clone());
+    }
+    let texture = Rc::new(match srgb {
+        true => Texture2D::from_path(path)?,
+        false => Texture2D::from_path_linear(path)?,
+    });
+    textures.insert(path.to_path_buf(), texture.clone());
+    Ok(texture)
+}
+
+fn vertices_of(mesh: &tobj::Mesh) -> Vec<ModelVertex> {
+    let positions: Vec<[f32; 3]> = mesh
+        .positions
+        .chunks_exact(3)
+        .map(|p| [p[0], p[1], p[2]])
+        .collect();
+    let normals = match mesh.normals.len() == mesh.positions.len() {
+        true => mesh
+            .normals
+            .chunks_exact(3)
+            .map(|n| [n[0], n[1], n[2]])
+            .collect(),
+        false => normals::smooth_normals(&positions, &mesh.indices),
+    };
+    let tex_coords: Vec<[f32; 2]> = (0..positions.len())
+        .map(|i| match mesh.texcoords.get(i * 2..i * 2 + 2) {
+            Some(&[u, v]) => [u, v],
+            _ => [0.0, 0.0],
+        })
+        .collect();
+    let tangents = normals::tangents(&positions, &normals, &tex_coords, &mesh.indices);
+    (0..positions.len())
+        .map(|i| ModelVertex {
+            position: positions[i],
+            normal: normals[i],
+            tex_coords: tex_coords[i],
+            tangent: tangents[i],
+        })
+        .collect()
+}
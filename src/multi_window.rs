@@ -0,0 +1,228 @@
+use std::sync::mpsc::Receiver;
+
+use glfw::Context;
+
+use crate::{
+    app::{self, AppConfig},
+    context,
+    errors::{GLWError, GLWErrorKind},
+    input::Input,
+};
+
+/// Index of a window in a [`WindowSet`]. Ids aren't reused after a window
+/// is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WindowId(usize);
+
+/// A window of a [`WindowSet`] with its own events and input state.
+pub struct SharedWindow {
+    pub window: glfw::Window,
+    /// Input state, up to date with the events of the last
+    /// [`WindowSet::poll_events`].
+    pub input: Input,
+    events: Receiver<(f64, glfw::WindowEvent)>,
+}
+
+/// Several windows whose contexts share one set of GL objects.
+///
+/// Buffers, textures, renderbuffers, shaders, programs and syncs created in
+/// any window can be used in all of them. Container objects aren't shared:
+/// vertex arrays, framebuffers, program pipelines and transform feedbacks
+/// only exist in the context they were created in, so every window needs
+/// its own.
+///
+/// Everything runs on the main thread, which GLFW requires. Exactly one
+/// context is current at a time; [`make_current`](Self::make_current) or
+/// [`render`](Self::render) a window before issuing GL calls for it. The
+/// first window is current after creating the set.
+pub struct WindowSet {
+    // dropped before glfw terminates
+    windows: Vec<Option<SharedWindow>>,
+    current: Option<WindowId>,
+    glfw: glfw::Glfw,
+}
+
+impl WindowSet {
+    /// Creates the first window as described by `config` and loads GL with
+    /// its context. Display mode and icon are ignored.
+    pub fn new(config: &AppConfig) -> Result<(Self, WindowId), GLWError> {
+        let mut glfw = glfw::init(glfw::LOG_ERRORS)?;
+        app::context_hints(&mut glfw, config);
+
+        let (mut window, events) = glfw
+            .create_window(
+                config.width,
+                config.height,
+                &config.title,
+                glfw::WindowMode::Windowed,
+            )
+            .ok_or(GLWErrorKind::WindowCreationFailed)?;
+        window.make_current();
+        window.set_all_polling(true);
+        glfw.set_swap_interval(match config.vsync {
+            true => glfw::SwapInterval::Sync(1),
+            false => glfw::SwapInterval::None,
+        });
+        context::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+
+        let id = WindowId(0);
+        let set = Self {
+            windows: vec![Some(SharedWindow {
+                window,
+                input: Input::new(),
+                events,
+            })],
+            current: Some(id),
+            glfw,
+        };
+        set.reset_viewport(id);
+        Ok((set, id))
+    }
+
+    /// Opens another window sharing objects with the others. The hints of
+    /// the first window are reused, and the current context is left as is.
+    pub fn create_window(
+        &mut self,
+        width: u32,
+        height: u32,
+        title: &str,
+    ) -> Result<WindowId, GLWError> {
+        let share = self
+            .windows
+            .iter()
+            .flatten()
+            .next()
+            .ok_or(GLWErrorKind::WindowCreationFailed)?;
+        let (mut window, events) = share
+            .window
+            .create_shared(width, height, title, glfw::WindowMode::Windowed)
+            .ok_or(GLWErrorKind::WindowCreationFailed)?;
+        window.set_all_polling(true);
+
+        self.windows.push(Some(SharedWindow {
+            window,
+            input: Input::new(),
+            events,
+        }));
+        Ok(WindowId(self.windows.len() - 1))
+    }
+
+    /// Makes `id`'s context current on this thread.
+    ///
+    /// # Panics
+    /// If the window was closed.
+    pub fn make_current(&mut self, id: WindowId) {
+        if self.current == Some(id) {
+            return;
+        }
+        self.get_mut(id)
+            .expect("window was closed")
+            .window
+            .make_current();
+        self.current = Some(id);
+    }
+
+    /// The window whose context is current, if any.
+    pub fn current(&self) -> Option<WindowId> {
+        self.current
+    }
+
+    /// Makes `id` current, runs `f` and swaps its buffers.
+    pub fn render<R>(&mut self, id: WindowId, f: impl FnOnce(&mut SharedWindow) -> R) -> R {
+        self.make_current(id);
+        let window = self.get_mut(id).expect("window was closed");
+        let result = f(window);
+        window.window.swap_buffers();
+        result
+    }
+
+    /// Polls events and routes them to their window's input state. Resizes
+    /// update the viewport of the window's context, leaving the current
+    /// context unchanged. Returns every event with the window it's for.
+    pub fn poll_events(&mut self) -> Vec<(WindowId, glfw::WindowEvent)> {
+        self.glfw.poll_events();
+        let mut events = Vec::new();
+        let mut resized = Vec::new();
+        for (index, window) in self.windows.iter_mut().enumerate() {
+            let Some(window) = window else {
+                continue;
+            };
+            window.input.begin_frame();
+            for (_, event) in glfw::flush_messages(&window.events) {
+                window.input.handle_event(&event);
+                if let glfw::WindowEvent::FramebufferSize(..) = event {
+                    resized.push(WindowId(index));
+                }
+                events.push((WindowId(index), event));
+            }
+        }
+
+        if !resized.is_empty() {
+            let current = self.current;
+            for id in resized {
+                self.make_current(id);
+                self.reset_viewport(id);
+            }
+            if let Some(current) = current {
+                self.make_current(current);
+            }
+        }
+        events
+    }
+
+    /// Destroys the windows that were asked to close, returning their ids.
+    /// GL objects made in them, other than container objects, live on in
+    /// the remaining windows. If the current window closes, no context is
+    /// current until the next [`make_current`](Self::make_current).
+    pub fn close_requested(&mut self) -> Vec<WindowId> {
+        let mut closed = Vec::new();
+        for (index, window) in self.windows.iter_mut().enumerate() {
+            if window.as_ref().is_some_and(|w| w.window.should_close()) {
+                *window = None;
+                closed.push(WindowId(index));
+            }
+        }
+        if self
+            .current
+            .is_some_and(|current| closed.contains(&current))
+        {
+            self.current = None;
+        }
+        closed
+    }
+
+    pub fn get(&self, id: WindowId) -> Option<&SharedWindow> {
+        self.windows.get(id.0)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: WindowId) -> Option<&mut SharedWindow> {
+        self.windows.get_mut(id.0)?.as_mut()
+    }
+
+    /// Ids of the open windows.
+    pub fn ids(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.windows
+            .iter()
+            .enumerate()
+            .filter(|(_, window)| window.is_some())
+            .map(|(index, _)| WindowId(index))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.iter().all(Option::is_none)
+    }
+
+    pub fn glfw(&mut self) -> &mut glfw::Glfw {
+        &mut self.glfw
+    }
+
+    /// Sets the viewport of the current context to `id`'s framebuffer.
+    fn reset_viewport(&self, id: WindowId) {
+        if let Some(window) = self.get(id) {
+            let (width, height) = window.window.get_framebuffer_size();
+            unsafe {
+                gl::Viewport(0, 0, width, height);
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::errors::{GLWError, GLWErrorKind};
+use crate::texture::{self, TextureFormat};
+
+/// Largest sample count multisample textures of `format` can have.
+pub fn max_samples(format: TextureFormat) -> u32 {
+    let name = if format.is_depth() {
+        gl::MAX_DEPTH_TEXTURE_SAMPLES
+    } else {
+        gl::MAX_COLOR_TEXTURE_SAMPLES
+    };
+    let mut samples = 0;
+    unsafe {
+        gl::GetIntegerv(name, &mut samples);
+    }
+    samples as u32
+}
+
+/// Multisampled 2D texture, used as a color or depth attachment of an MSAA
+/// framebuffer and read with a `sampler2DMS` or resolved by blitting.
+pub struct Texture2DMultisample {
+    texture_id: u32,
+    width: u32,
+    height: u32,
+    samples: u32,
+    format: TextureFormat,
+}
+
+impl Texture2DMultisample {
+    /// Allocates the texture with `samples` samples per pixel, all at the
+    /// same locations in every pixel. Needs GL 3.2 or GLES 3.1.
+    pub fn new(
+        width: u32,
+        height: u32,
+        samples: u32,
+        format: TextureFormat,
+    ) -> Result<Self, GLWError> {
+        if !gl::TexImage2DMultisample::is_loaded() {
+            Err(GLWErrorKind::ExtensionNotSupported(
+                "GL_ARB_texture_multisample".to_string(),
+            ))?
+        }
+        let max = max_samples(format);
+        if samples == 0 || samples > max {
+            Err(GLWErrorKind::UnsupportedSampleCount {
+                requested: samples,
+                max,
+            })?
+        }
+        if let TextureFormat::Compressed { .. } = format {
+            Err(GLWErrorKind::UnsupportedTextureFormat(
+                "compressed formats can't be multisampled".to_string(),
+            ))?
+        }
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D_MULTISAMPLE, texture_id);
+            gl::TexImage2DMultisample(
+                gl::TEXTURE_2D_MULTISAMPLE,
+                samples as i32,
+                u32::from(format),
+                width as i32,
+                height as i32,
+                gl::TRUE,
+            );
+            gl::BindTexture(gl::TEXTURE_2D_MULTISAMPLE, 0);
+        }
+
+        Ok(Self {
+            texture_id,
+            width,
+            height,
+            samples,
+            format,
+        })
+    }
+
+    pub fn bind(&self, unit: u32) {
+        texture::bind_to_unit(gl::TEXTURE_2D_MULTISAMPLE, self.texture_id, unit);
+    }
+
+    /// Attaches the texture to `attachment` (e.g. `gl::COLOR_ATTACHMENT0`)
+    /// of the bound draw framebuffer.
+    pub fn attach(&self, attachment: u32) {
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::DRAW_FRAMEBUFFER,
+                attachment,
+                gl::TEXTURE_2D_MULTISAMPLE,
+                self.texture_id,
+                0,
+            );
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn id(&self) -> u32 {
+        self.texture_id
+    }
+}
+
+impl Drop for Texture2DMultisample {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture_id);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use cgmath::{InnerSpace, Point3, Vector2, Vector3};
+
+/// Vertex normals averaged from the triangles around each vertex, weighted
+/// by their area. Vertices split along seams get the normals of their own
+/// side only.
+pub fn smooth_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let position = |i: u32| Point3::from(positions[i as usize]);
+    let mut normals = vec![Vector3::new(0.0f32, 0.0, 0.0); positions.len()];
+    for face in indices.chunks_exact(3) {
+        let (a, b, c) = (position(face[0]), position(face[1]), position(face[2]));
+        // the cross product's length is twice the area
+        let normal = (b - a).cross(c - a);
+        for &index in face {
+            normals[index as usize] += normal;
+        }
+    }
+    normals
+        .into_iter()
+        .map(|normal| normalized(normal).into())
+        .collect()
+}
+
+/// Per-vertex tangents for normal mapping, in the MikkTSpace convention:
+/// `xyz` along increasing `u`, orthogonal to the normal, and `w` the sign
+/// of the bitangent, see [`bitangent`].
+///
+/// Triangle tangents are summed around each vertex, triangles without UV
+/// area are skipped. Vertices left without a tangent get an arbitrary
+/// one perpendicular to the normal.
+pub fn tangents(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    tex_coords: &[[f32; 2]],
+    indices: &[u32],
+) -> Vec<[f32; 4]> {
+    let zero = Vector3::new(0.0f32, 0.0, 0.0);
+    let mut tangents = vec![zero; positions.len()];
+    let mut bitangents = vec![zero; positions.len()];
+    for face in indices.chunks_exact(3) {
+        let [a, b, c] = [face[0], face[1], face[2]].map(|i| i as usize);
+        let edge1 = Point3::from(positions[b]) - Point3::from(positions[a]);
+        let edge2 = Point3::from(positions[c]) - Point3::from(positions[a]);
+        let uv = |i: usize| Vector2::from(tex_coords.get(i).copied().unwrap_or_default());
+        let (duv1, duv2) = (uv(b) - uv(a), uv(c) - uv(a));
+        let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+        if determinant.abs() <= f32::EPSILON {
+            continue;
+        }
+        // the edges written in terms of the UV axes, solved for the axes
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) / determinant;
+        let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / determinant;
+        for index in [a, b, c] {
+            tangents[index] += tangent;
+            bitangents[index] += bitangent;
+        }
+    }
+
+    (0..positions.len())
+        .map(|i| {
+            let normal = normalized(Vector3::from(normals[i]));
+            // Gram-Schmidt against the normal
+            let mut tangent = tangents[i] - normal * normal.dot(tangents[i]);
+            if tangent.magnitude2() <= f32::EPSILON * f32::EPSILON {
+                tangent = any_perpendicular(normal);
+            }
+            let tangent = normalized(tangent);
+            let handedness = match normal.cross(tangent).dot(bitangents[i]) < 0.0 {
+                true => -1.0,
+                false => 1.0,
+            };
+            [tangent.x, tangent.y, tangent.z, handedness]
+        })
+        .collect()
+}
+
+/// Bitangent of a vertex from its normal and a tangent from [`tangents`],
+/// as shaders reconstruct it.
+pub fn bitangent(normal: [f32; 3], tangent: [f32; 4]) -> [f32; 3] {
+    let [x, y, z, w] = tangent;
+    (Vector3::from(normal).cross(Vector3::new(x, y, z)) * w).into()
+}
+
+fn normalized(vector: Vector3<f32>) -> Vector3<f32> {
+    match vector.magnitude2() > 0.0 {
+        true => vector.normalize(),
+        false => vector,
+    }
+}
+
+fn any_perpendicular(normal: Vector3<f32>) -> Vector3<f32> {
+    let axis = match normal.x.abs() < 0.9 {
+        true => Vector3::unit_x(),
+        false => Vector3::unit_y(),
+    };
+    axis - normal * normal.dot(axis)
+}
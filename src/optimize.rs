@@ -0,0 +1,340 @@
+use std::{collections::HashMap, hash::Hash};
+
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3};
+
+/// Vertices simulated in the post-transform cache, about what GPUs keep.
+const CACHE_SIZE: usize = 32;
+
+/// Merges vertices with equal `key`s, returning the unique vertices and
+/// the triangles indexing them. The key should cover every attribute, e.g.
+/// [`ModelVertex::to_bits`](crate::model::ModelVertex::to_bits).
+pub fn weld<V: Copy, K: Hash + Eq>(
+    vertices: &[V],
+    indices: &[u32],
+    key: impl Fn(&V) -> K,
+) -> (Vec<V>, Vec<u32>) {
+    let mut unique = Vec::new();
+    let mut remap = vec![0; vertices.len()];
+    let mut seen: HashMap<K, u32> = HashMap::new();
+    for (index, vertex) in vertices.iter().enumerate() {
+        remap[index] = *seen.entry(key(vertex)).or_insert_with(|| {
+            unique.push(*vertex);
+            unique.len() as u32 - 1
+        });
+    }
+    let indices = indices.iter().map(|&index| remap[index as usize]).collect();
+    (unique, indices)
+}
+
+/// Index buffer for unindexed triangles, sharing vertices with equal keys
+/// as [`weld`] does.
+pub fn generate_indices<V: Copy, K: Hash + Eq>(
+    vertices: &[V],
+    key: impl Fn(&V) -> K,
+) -> (Vec<V>, Vec<u32>) {
+    let indices: Vec<u32> = (0..vertices.len() as u32).collect();
+    weld(vertices, &indices, key)
+}
+
+/// Reorders triangles so vertices are reused while still in the GPU's
+/// post-transform cache, with Tom Forsyth's linear-speed algorithm.
+pub fn optimize_vertex_cache(indices: &mut [u32], vertex_count: usize) {
+    let triangle_count = indices.len() / 3;
+    let mut triangles_of = vec![Vec::new(); vertex_count];
+    for (triangle, face) in indices.chunks_exact(3).enumerate() {
+        for &vertex in face {
+            triangles_of[vertex as usize].push(triangle);
+        }
+    }
+    let mut remaining: Vec<usize> = triangles_of.iter().map(Vec::len).collect();
+    let mut cache_position: Vec<Option<usize>> = vec![None; vertex_count];
+    let score = |position: Option<usize>, remaining: usize| -> f32 {
+        if remaining == 0 {
+            return -1.0;
+        }
+        let cache = match position {
+            // the last triangle's vertices score the same, whichever order
+            // they were added in
+            Some(position) if position < 3 => 0.75,
+            Some(position) => (1.0 - (position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+            None => 0.0,
+        };
+        cache + 2.0 * (remaining as f32).powf(-0.5)
+    };
+    let mut vertex_scores: Vec<f32> = (0..vertex_count)
+        .map(|vertex| score(None, remaining[vertex]))
+        .collect();
+    let triangle_score = |face: &[u32], vertex_scores: &[f32]| -> f32 {
+        face.iter()
+            .map(|&vertex| vertex_scores[vertex as usize])
+            .sum()
+    };
+
+    let mut emitted = vec![false; triangle_count];
+    let mut output = Vec::with_capacity(indices.len());
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut next_unemitted = 0;
+    for _ in 0..triangle_count {
+        // best triangle touching the cache, or the next one left
+        let best = cache
+            .iter()
+            .flat_map(|&vertex| &triangles_of[vertex as usize])
+            .filter(|&&triangle| !emitted[triangle])
+            .map(|&triangle| {
+                let face = &indices[triangle * 3..triangle * 3 + 3];
+                (triangle, triangle_score(face, &vertex_scores))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(triangle, _)| triangle);
+        let triangle = match best {
+            Some(triangle) => triangle,
+            None => {
+                while emitted[next_unemitted] {
+                    next_unemitted += 1;
+                }
+                next_unemitted
+            }
+        };
+
+        emitted[triangle] = true;
+        let face = [
+            indices[triangle * 3],
+            indices[triangle * 3 + 1],
+            indices[triangle * 3 + 2],
+        ];
+        output.extend(face);
+        for vertex in face {
+            remaining[vertex as usize] -= 1;
+            cache.retain(|&cached| cached != vertex);
+        }
+        cache.splice(0..0, face);
+        for evicted in cache.drain(CACHE_SIZE.min(cache.len())..) {
+            cache_position[evicted as usize] = None;
+            vertex_scores[evicted as usize] = score(None, remaining[evicted as usize]);
+        }
+        for (position, &vertex) in cache.iter().enumerate() {
+            cache_position[vertex as usize] = Some(position);
+            vertex_scores[vertex as usize] = score(Some(position), remaining[vertex as usize]);
+        }
+    }
+    indices[..output.len()].copy_from_slice(&output);
+}
+
+/// Reorders clusters of triangles so ones facing outwards from the mesh's
+/// center come first, occluding more of what's drawn after them. Clusters
+/// end where the [cache optimized](optimize_vertex_cache) order restarts
+/// on new vertices, so the cache efficiency is mostly kept; run this after
+/// it.
+pub fn optimize_overdraw(indices: &mut [u32], positions: &[[f32; 3]]) {
+    let position = |index: u32| Point3::from(positions[index as usize]);
+    let faces: Vec<[u32; 3]> = indices
+        .chunks_exact(3)
+        .map(|face| [face[0], face[1], face[2]])
+        .collect();
+    if faces.is_empty() {
+        return;
+    }
+    let center = Point3::centroid(&positions.iter().map(|&p| p.into()).collect::<Vec<_>>());
+
+    // a cluster starts on a triangle none of whose vertices are cached
+    let mut clusters: Vec<(usize, usize)> = Vec::new();
+    let mut cache: Vec<u32> = Vec::new();
+    let mut start = 0;
+    for (triangle, face) in faces.iter().enumerate() {
+        let misses = face.iter().filter(|vertex| !cache.contains(vertex)).count();
+        if misses == 3 && triangle > start {
+            clusters.push((start, triangle));
+            start = triangle;
+        }
+        for &vertex in face {
+            if !cache.contains(&vertex) {
+                cache.insert(0, vertex);
+                cache.truncate(CACHE_SIZE);
+            }
+        }
+    }
+    clusters.push((start, faces.len()));
+
+    let facing = |&(start, end): &(usize, usize)| {
+        let (mut area_normal, mut centroid) =
+            (Vector3::new(0.0f32, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0));
+        for face in &faces[start..end] {
+            let (a, b, c) = (position(face[0]), position(face[1]), position(face[2]));
+            area_normal += (b - a).cross(c - a);
+            centroid += (a.to_vec() + b.to_vec() + c.to_vec()) / 3.0;
+        }
+        if area_normal.magnitude2() == 0.0 {
+            return 0.0;
+        }
+        let centroid = centroid / (end - start) as f32;
+        (centroid - center.to_vec()).dot(area_normal.normalize())
+    };
+    let mut sorted: Vec<((usize, usize), f32)> = clusters
+        .iter()
+        .map(|cluster| (*cluster, facing(cluster)))
+        .collect();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let reordered: Vec<u32> = sorted
+        .iter()
+        .flat_map(|&((start, end), _)| faces[start..end].iter().flatten().copied())
+        .collect();
+    indices[..reordered.len()].copy_from_slice(&reordered);
+}
+
+/// Reorders the vertices by their first use in `indices`, so fetching them
+/// goes through memory in order, and drops unused ones.
+pub fn optimize_vertex_fetch<V: Copy>(vertices: &mut Vec<V>, indices: &mut [u32]) {
+    let mut remap = vec![None; vertices.len()];
+    let mut reordered = Vec::with_capacity(vertices.len());
+    for index in indices.iter_mut() {
+        *index = *remap[*index as usize].get_or_insert_with(|| {
+            reordered.push(vertices[*index as usize]);
+            reordered.len() as u32 - 1
+        });
+    }
+    *vertices = reordered;
+}
+
+/// [Welds](weld) the vertices by `key`, then runs the [vertex
+/// cache](optimize_vertex_cache), [overdraw](optimize_overdraw) and
+/// [vertex fetch](optimize_vertex_fetch) passes. `position` reads a
+/// vertex's position.
+pub fn optimize<V: Copy, K: Hash + Eq>(
+    vertices: &mut Vec<V>,
+    indices: &mut Vec<u32>,
+    key: impl Fn(&V) -> K,
+    position: impl Fn(&V) -> [f32; 3],
+) {
+    let (welded, mut welded_indices) = weld(vertices, indices, key);
+    optimize_vertex_cache(&mut welded_indices, welded.len());
+    let positions: Vec<_> = welded.iter().map(position).collect();
+    optimize_overdraw(&mut welded_indices, &positions);
+    *vertices = welded;
+    optimize_vertex_fetch(vertices, &mut welded_indices);
+    *indices = welded_indices;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Indices of a `size` x `size` quad grid, row by row.
+    fn grid(size: u32) -> Vec<u32> {
+        let mut indices = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let corner = y * (size + 1) + x;
+                let (right, up) = (corner + 1, corner + size + 1);
+                indices.extend([corner, right, up, right, up + 1, up]);
+            }
+        }
+        indices
+    }
+
+    /// Vertices transformed per triangle with a FIFO cache of `size`.
+    fn acmr(indices: &[u32], size: usize) -> f32 {
+        let mut cache = std::collections::VecDeque::new();
+        let mut misses = 0;
+        for &index in indices {
+            if !cache.contains(&index) {
+                misses += 1;
+                cache.push_back(index);
+                if cache.len() > size {
+                    cache.pop_front();
+                }
+            }
+        }
+        misses as f32 / (indices.len() / 3) as f32
+    }
+
+    fn sorted_faces(indices: &[u32]) -> Vec<[u32; 3]> {
+        let mut faces: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|face| [face[0], face[1], face[2]])
+            .collect();
+        faces.sort();
+        faces
+    }
+
+    #[test]
+    fn weld_merges_equal_keys() {
+        let vertices = [1, 2, 1, 3, 2];
+        let (unique, indices) = weld(&vertices, &[0, 1, 3, 2, 4, 3], |&v| v);
+        assert_eq!(unique, [1, 2, 3]);
+        assert_eq!(indices, [0, 1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn weld_keeps_vertices_the_key_tells_apart() {
+        let vertices = [(1, 'a'), (1, 'b')];
+        let (unique, _) = weld(&vertices, &[0, 1, 0], |&(v, _)| v);
+        assert_eq!(unique, [(1, 'a')]);
+        let (unique, _) = weld(&vertices, &[0, 1, 0], |&v| v);
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn generate_indices_shares_vertices() {
+        let (unique, indices) = generate_indices(&[5, 6, 7, 7, 6, 8], |&v| v);
+        assert_eq!(unique, [5, 6, 7, 8]);
+        assert_eq!(indices, [0, 1, 2, 2, 1, 3]);
+    }
+
+    #[test]
+    fn vertex_cache_keeps_the_triangles() {
+        let original = grid(8);
+        let mut indices = original.clone();
+        optimize_vertex_cache(&mut indices, 81);
+        assert_eq!(sorted_faces(&indices), sorted_faces(&original));
+    }
+
+    #[test]
+    fn vertex_cache_reduces_misses() {
+        let original = grid(32);
+        let mut indices = original.clone();
+        optimize_vertex_cache(&mut indices, 33 * 33);
+        assert!(acmr(&indices, 16) < acmr(&original, 16));
+    }
+
+    #[test]
+    fn vertex_fetch_orders_by_first_use_and_drops_unused() {
+        let mut vertices = vec!['a', 'b', 'c', 'd'];
+        let mut indices = [3, 1, 3, 1, 0, 3];
+        optimize_vertex_fetch(&mut vertices, &mut indices);
+        assert_eq!(vertices, ['d', 'b', 'a']);
+        assert_eq!(indices, [0, 1, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn optimize_keeps_the_mesh() {
+        let size = 6;
+        let positions: Vec<[f32; 3]> = (0..(size + 1) * (size + 1))
+            .map(|i| [(i % (size + 1)) as f32, (i / (size + 1)) as f32, 0.0])
+            .collect();
+        // unindexed, so every vertex is duplicated across its triangles
+        let mut vertices: Vec<[f32; 3]> = grid(size)
+            .iter()
+            .map(|&index| positions[index as usize])
+            .collect();
+        let mut indices: Vec<u32> = (0..vertices.len() as u32).collect();
+        let to_bits = |p: &[f32; 3]| p.map(f32::to_bits);
+        let faces = |vertices: &[[f32; 3]], indices: &[u32]| {
+            let mut faces: Vec<_> = indices
+                .chunks_exact(3)
+                .map(|face| {
+                    face.iter()
+                        .map(|&i| to_bits(&vertices[i as usize]))
+                        .collect()
+                })
+                .collect::<Vec<Vec<_>>>();
+            faces.sort();
+            faces
+        };
+        let before = faces(&vertices, &indices);
+
+        optimize(&mut vertices, &mut indices, to_bits, |p| *p);
+        assert_eq!(vertices.len(), positions.len());
+        assert_eq!(faces(&vertices, &indices), before);
+    }
+}
@@ -0,0 +1,173 @@
+use std::marker::PhantomData;
+
+use gl::types::GLsizeiptr;
+
+use crate::{
+    buffer::BufferTarget,
+    errors::{GLWError, GLWErrorKind},
+    sync::Fence,
+};
+
+/// Buffer mapped for writing for its whole lifetime (`glBufferStorage` with
+/// `MAP_PERSISTENT | MAP_COHERENT`), so per-frame data can be written
+/// without mapping or copying through the driver.
+///
+/// Writes are visible to the GPU right away, synchronizing with draw calls
+/// still reading the old contents is up to the caller, see
+/// [`PersistentRing`].
+pub struct PersistentBuffer<T> {
+    buffer_id: u32,
+    target: BufferTarget,
+    ptr: *mut T,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> PersistentBuffer<T> {
+    /// Whether the context provides `glBufferStorage` (GL 4.4 or
+    /// `ARB_buffer_storage`).
+    pub fn supported() -> bool {
+        gl::BufferStorage::is_loaded()
+    }
+
+    pub fn new(target: BufferTarget, data: &[T]) -> Result<Self, GLWError> {
+        let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+        let size = std::mem::size_of_val(data) as GLsizeiptr;
+
+        let mut buffer_id = 0;
+        let ptr = unsafe {
+            gl::GenBuffers(1, &mut buffer_id);
+            gl::BindBuffer(target.into(), buffer_id);
+            gl::BufferStorage(target.into(), size, data.as_ptr().cast(), flags);
+            gl::MapBufferRange(target.into(), 0, size, flags) as *mut T
+        };
+        if ptr.is_null() {
+            unsafe {
+                gl::DeleteBuffers(1, &buffer_id);
+            }
+            Err(GLWErrorKind::BufferMapFailed)?;
+        }
+
+        Ok(Self {
+            buffer_id,
+            target,
+            ptr,
+            len: data.len(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// The mapped contents. Writing to parts the GPU is still reading from
+    /// is a data race on the GPU side, not undefined behavior in Rust.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the mapping lives as long as self and was initialized with
+        // `data` in new
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<T> PersistentBuffer<T> {
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindBuffer(self.target.into(), self.buffer_id);
+        }
+    }
+
+    pub fn bind_base(&self, binding_point: u32) {
+        unsafe {
+            gl::BindBufferBase(self.target.into(), binding_point, self.buffer_id);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn id(&self) -> u32 {
+        self.buffer_id
+    }
+}
+
+impl<T> Drop for PersistentBuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            gl::BindBuffer(self.target.into(), self.buffer_id);
+            gl::UnmapBuffer(self.target.into());
+            gl::DeleteBuffers(1, &self.buffer_id);
+        }
+    }
+}
+
+/// A [`PersistentBuffer`] split into sections written in turn, each guarded
+/// by a fence so the CPU never overwrites a section the GPU is still reading.
+///
+/// ```ignore
+/// let section = ring.section_mut()?;
+/// section.copy_from_slice(&frame_data);
+/// // draw using the elements starting at ring.offset()
+/// ring.finish_section();
+/// ```
+pub struct PersistentRing<T> {
+    buffer: PersistentBuffer<T>,
+    section_len: usize,
+    fences: Vec<Option<Fence>>,
+    current: usize,
+}
+
+impl<T: Copy> PersistentRing<T> {
+    /// Creates a ring of `sections` sections, each initialized with
+    /// `initial`.
+    ///
+    /// # Panics
+    /// If `sections` is 0.
+    pub fn new(target: BufferTarget, initial: &[T], sections: usize) -> Result<Self, GLWError> {
+        assert!(sections > 0, "a ring needs at least one section");
+        Ok(Self {
+            buffer: PersistentBuffer::new(target, &initial.repeat(sections))?,
+            section_len: initial.len(),
+            fences: (0..sections).map(|_| None).collect(),
+            current: 0,
+        })
+    }
+
+    /// Three sections, enough for the CPU to never wait on a GPU that is at
+    /// most one frame behind.
+    pub fn triple(target: BufferTarget, initial: &[T]) -> Result<Self, GLWError> {
+        Self::new(target, initial, 3)
+    }
+
+    /// Waits for the GPU to be done with the current section and returns it.
+    pub fn section_mut(&mut self) -> Result<&mut [T], GLWError> {
+        if let Some(fence) = self.fences[self.current].take() {
+            fence.wait()?;
+        }
+        let offset = self.offset();
+        Ok(&mut self.buffer.as_mut_slice()[offset..offset + self.section_len])
+    }
+}
+
+impl<T> PersistentRing<T> {
+    /// Element offset of the current section in the buffer.
+    pub fn offset(&self) -> usize {
+        self.current * self.section_len
+    }
+
+    /// Fences the current section after the draw calls reading it and moves
+    /// on to the next one.
+    pub fn finish_section(&mut self) {
+        self.fences[self.current] = Some(Fence::new());
+        self.current = (self.current + 1) % self.fences.len();
+    }
+
+    pub fn section_len(&self) -> usize {
+        self.section_len
+    }
+
+    pub fn buffer(&self) -> &PersistentBuffer<T> {
+        &self.buffer
+    }
+}
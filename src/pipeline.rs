@@ -0,0 +1,145 @@
+use std::ops::BitOr;
+
+use gl::types::{GLbitfield, GLsizei};
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    shader::{ShaderProgram, ShaderType},
+};
+
+/// Set of shader stages, combinable with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShaderStages(GLbitfield);
+
+impl ShaderStages {
+    pub const VERTEX: Self = Self(gl::VERTEX_SHADER_BIT);
+    pub const FRAGMENT: Self = Self(gl::FRAGMENT_SHADER_BIT);
+    pub const GEOMETRY: Self = Self(gl::GEOMETRY_SHADER_BIT);
+    pub const TESS_CONTROL: Self = Self(gl::TESS_CONTROL_SHADER_BIT);
+    pub const TESS_EVALUATION: Self = Self(gl::TESS_EVALUATION_SHADER_BIT);
+    pub const COMPUTE: Self = Self(gl::COMPUTE_SHADER_BIT);
+    pub const ALL: Self = Self(gl::ALL_SHADER_BITS);
+
+    pub fn bits(self) -> GLbitfield {
+        self.0
+    }
+}
+
+impl BitOr for ShaderStages {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl From<ShaderType> for ShaderStages {
+    fn from(val: ShaderType) -> Self {
+        match val {
+            ShaderType::VertexShader => Self::VERTEX,
+            ShaderType::FragmentShader => Self::FRAGMENT,
+            ShaderType::GeometryShader => Self::GEOMETRY,
+            ShaderType::TessControlShader => Self::TESS_CONTROL,
+            ShaderType::TessEvaluationShader => Self::TESS_EVALUATION,
+            ShaderType::ComputeShader => Self::COMPUTE,
+        }
+    }
+}
+
+/// Combines stages of separable programs without linking them together.
+///
+/// A program bound with [`ShaderProgram::use_program`] takes precedence over
+/// the pipeline, and the uniform setters bind their program, so call
+/// [`bind`](Self::bind) again after updating uniforms.
+pub struct ProgramPipeline {
+    pipeline_id: u32,
+}
+
+impl ProgramPipeline {
+    pub fn new() -> Self {
+        let mut pipeline_id = 0;
+        unsafe {
+            gl::GenProgramPipelines(1, &mut pipeline_id);
+        }
+        Self { pipeline_id }
+    }
+
+    /// Uses `stages` of `program` for this pipeline.
+    pub fn use_stages(
+        &self,
+        stages: impl Into<ShaderStages>,
+        program: &ShaderProgram,
+    ) -> Result<(), GLWError> {
+        if !program.is_separable() {
+            Err(GLWErrorKind::ProgramNotSeparable)?;
+        }
+        unsafe {
+            gl::UseProgramStages(
+                self.pipeline_id,
+                stages.into().bits(),
+                program.shader_program_id,
+            );
+        }
+        Ok(())
+    }
+
+    pub fn clear_stages(&self, stages: impl Into<ShaderStages>) {
+        unsafe {
+            gl::UseProgramStages(self.pipeline_id, stages.into().bits(), 0);
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::UseProgram(0);
+            gl::BindProgramPipeline(self.pipeline_id);
+        }
+    }
+
+    pub fn unbind() {
+        unsafe {
+            gl::BindProgramPipeline(0);
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), GLWError> {
+        unsafe {
+            gl::ValidateProgramPipeline(self.pipeline_id);
+
+            let mut status = 0;
+            gl::GetProgramPipelineiv(self.pipeline_id, gl::VALIDATE_STATUS, &mut status);
+            if status == gl::TRUE as i32 {
+                return Ok(());
+            }
+
+            let mut length = 0;
+            gl::GetProgramPipelineiv(self.pipeline_id, gl::INFO_LOG_LENGTH, &mut length);
+            let mut info_log = vec![0u8; length.max(1) as usize];
+            gl::GetProgramPipelineInfoLog(
+                self.pipeline_id,
+                length,
+                &mut length as *mut i32 as *mut GLsizei,
+                info_log.as_mut_ptr() as *mut gl::types::GLchar,
+            );
+
+            Err(GLWError::new(
+                GLWErrorKind::ShaderProgramValidationFailed,
+                String::from_utf8_lossy(&info_log[..length as usize]).to_string(),
+            ))
+        }
+    }
+}
+
+impl Default for ProgramPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ProgramPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgramPipelines(1, &self.pipeline_id);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{
+    buffer::{Buffer, BufferTarget, BufferUsage},
+    errors::GLWError,
+};
+
+/// Reads RGBA8 pixels of the bound read framebuffer through two pixel pack
+/// buffers, so `glReadPixels` returns immediately and the data is picked up
+/// a frame later, once the GPU has written it.
+pub struct PixelReader {
+    buffers: [Buffer<u8>; 2],
+    current: usize,
+    pending: [bool; 2],
+    width: u32,
+    height: u32,
+}
+
+impl PixelReader {
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize * 4;
+        let buffer = || Buffer::with_len(BufferTarget::PixelPack, len, BufferUsage::StreamRead);
+        let reader = Self {
+            buffers: [buffer(), buffer()],
+            current: 0,
+            pending: [false; 2],
+            width,
+            height,
+        };
+        // allocating leaves the last buffer bound
+        reader.buffers[1].unbind();
+        reader
+    }
+
+    /// Starts reading the `width` x `height` rectangle at `(x, y)` and
+    /// returns the pixels of the previous call, bottom row first, or `None`
+    /// on the first call.
+    pub fn read(&mut self, x: i32, y: i32) -> Result<Option<Vec<u8>>, GLWError> {
+        let buffer = &self.buffers[self.current];
+        buffer.bind();
+        unsafe {
+            gl::ReadPixels(
+                x,
+                y,
+                self.width as i32,
+                self.height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null_mut(),
+            );
+        }
+        buffer.unbind();
+        self.pending[self.current] = true;
+
+        self.current = 1 - self.current;
+        if !std::mem::take(&mut self.pending[self.current]) {
+            return Ok(None);
+        }
+        let mapped = self.buffers[self.current].map_read()?;
+        let pixels = mapped.to_vec();
+        mapped.unmap()?;
+        self.buffers[self.current].unbind();
+        Ok(Some(pixels))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Stages texture uploads in two pixel unpack buffers used in turn, so
+/// writing the next upload doesn't wait for the previous one.
+///
+/// After [`stage`](Self::stage) the buffer stays bound and a
+/// `glTexSubImage2D` with a null data pointer reads from it, until
+/// [`unbind`](Self::unbind).
+pub struct PixelWriter {
+    buffers: [Buffer<u8>; 2],
+    current: usize,
+}
+
+impl PixelWriter {
+    /// Each upload can be up to `size` bytes.
+    pub fn new(size: usize) -> Self {
+        let buffer = || Buffer::with_len(BufferTarget::PixelUnpack, size, BufferUsage::Stream);
+        let writer = Self {
+            buffers: [buffer(), buffer()],
+            current: 0,
+        };
+        // allocating leaves the last buffer bound, which would redirect
+        // regular texture uploads
+        writer.buffers[1].unbind();
+        writer
+    }
+
+    /// Copies `data` into the next buffer and binds it as the source of
+    /// texture uploads.
+    ///
+    /// # Panics
+    /// If `data` is larger than the size given to [`new`](Self::new).
+    pub fn stage(&mut self, data: &[u8]) {
+        self.current = 1 - self.current;
+        let buffer = &mut self.buffers[self.current];
+        // orphaning avoids waiting for an upload still reading the buffer
+        buffer.orphan();
+        buffer.update(0, data);
+    }
+
+    pub fn unbind(&self) {
+        self.buffers[self.current].unbind();
+    }
+
+    pub fn size(&self) -> usize {
+        self.buffers[0].len()
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    context::GlApi,
+    errors::{GLWError, GLWErrorExt, GLWErrorKind},
+};
+
+/// Shader source with all `#include` directives expanded.
+pub struct Source {
+    pub code: String,
+    /// Files that make up the source, indexed by the GLSL source string
+    /// number used in the emitted `#line` directives. The root file comes
+    /// first, embedded sources have no files at all.
+    pub files: Vec<PathBuf>,
+}
+
+impl Source {
+    /// Source that doesn't come from a file, e.g. `attach_shader_source`.
+    pub(crate) fn embedded(code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            files: vec![],
+        }
+    }
+
+    pub fn includes(&self) -> &[PathBuf] {
+        self.files.get(1..).unwrap_or_default()
+    }
+
+    /// Rewrites the `<source string>:<line>` / `<source string>(<line>)`
+    /// prefixes drivers put in info logs to the name of the originating file.
+    pub fn remap_log(&self, log: &str) -> String {
+        log.lines()
+            .map(|line| self.remap_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn remap_line(&self, line: &str) -> String {
+        let prefix_len = ["ERROR: ", "WARNING: "]
+            .iter()
+            .find(|prefix| line.starts_with(*prefix))
+            .map_or(0, |prefix| prefix.len());
+        let (prefix, rest) = line.split_at(prefix_len);
+
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (index, rest) = rest.split_at(digits);
+        let file = index.parse::<usize>().ok().and_then(|i| self.files.get(i));
+
+        match (file, rest.as_bytes()) {
+            (Some(file), [b':' | b'(', next, ..]) if next.is_ascii_digit() => {
+                format!("{prefix}{}{rest}", file.display())
+            }
+            _ => line.to_string(),
+        }
+    }
+}
+
+/// GLSL dialect sources are rewritten for, see [`retarget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlslTarget {
+    /// Desktop GLSL, e.g. `Core(330)` for `#version 330 core`.
+    Core(u32),
+    /// GLSL ES, e.g. `Es(300)` for `#version 300 es`.
+    Es(u32),
+}
+
+impl GlslTarget {
+    /// Target matching the current context's [`GlApi`].
+    pub fn current() -> Self {
+        GlApi::current().glsl_target()
+    }
+
+    fn version_directive(&self) -> String {
+        match self {
+            Self::Core(version) => format!("#version {version} core"),
+            Self::Es(version) => format!("#version {version} es"),
+        }
+    }
+}
+
+/// Replaces the `#version` directive (adding one if missing) with the one
+/// for `target`. GLSL ES has no default float precision in fragment shaders,
+/// so `highp` defaults are added for ES sources that don't declare their own,
+/// after any leading `#extension` directives.
+/// Desktop GLSL ignores precision qualifiers, so they are left alone.
+pub fn retarget(code: &str, target: GlslTarget) -> String {
+    let version_line = code
+        .lines()
+        .position(|line| line.trim_start().starts_with("#version"));
+    let needs_precision = matches!(target, GlslTarget::Es(_))
+        && !code
+            .lines()
+            .any(|line| line.trim_start().starts_with("precision"));
+
+    let split = version_line.map_or(0, |n| n + 1);
+    // extensions must come before any declaration, so precision statements
+    // go after the ones leading the shader
+    let extensions_end = code
+        .lines()
+        .enumerate()
+        .skip(split)
+        .take_while(|(_, line)| {
+            let line = line.trim_start();
+            line.is_empty() || line.starts_with("//") || line.starts_with("#extension")
+        })
+        .filter(|(_, line)| line.trim_start().starts_with("#extension"))
+        .last()
+        .map_or(split, |(n, _)| n + 1);
+
+    let mut out = String::with_capacity(code.len());
+    for line in code.lines().take(split.saturating_sub(1)) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    writeln!(out, "{}", target.version_directive()).unwrap();
+    for line in code.lines().take(extensions_end).skip(split) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    if needs_precision {
+        out.push_str("precision highp float;\nprecision highp int;\n");
+    }
+    if needs_precision || version_line.is_none() {
+        writeln!(out, "#line {} 0", extensions_end + 1).unwrap();
+    }
+    for line in code.lines().skip(extensions_end) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// [`resolve_includes`] followed by [`inject_defines`].
+pub fn preprocess(
+    path: impl AsRef<Path>,
+    defines: &[(String, String)],
+) -> Result<Source, GLWError> {
+    let mut source = resolve_includes(path)?;
+    source.code = inject_defines(&source.code, defines);
+    Ok(source)
+}
+
+/// Inserts `#define name value` lines right after the `#version` directive
+/// (or at the very top if there is none), followed by a `#line` directive
+/// so compiler messages keep referring to the original line numbers.
+pub fn inject_defines(code: &str, defines: &[(String, String)]) -> String {
+    if defines.is_empty() {
+        return code.to_string();
+    }
+
+    let version_line = code
+        .lines()
+        .position(|line| line.trim_start().starts_with("#version"));
+    let split = version_line.map_or(0, |n| n + 1);
+
+    let mut out = String::with_capacity(code.len());
+    for line in code.lines().take(split) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    for (name, value) in defines {
+        writeln!(out, "#define {name} {value}").unwrap();
+    }
+    writeln!(out, "#line {} 0", split + 1).unwrap();
+    for line in code.lines().skip(split) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Reads the shader at `path`, recursively replacing `#include "file"`
+/// lines with the contents of `file`, resolved relative to the including
+/// file.
+pub fn resolve_includes(path: impl AsRef<Path>) -> Result<Source, GLWError> {
+    let mut source = Source {
+        code: String::new(),
+        files: vec![],
+    };
+    expand(path.as_ref(), &mut vec![], &mut source)?;
+    Ok(source)
+}
+
+fn expand(path: &Path, stack: &mut Vec<PathBuf>, source: &mut Source) -> Result<(), GLWError> {
+    let canonical = fs::canonicalize(path).info(format!("{}", path.display()))?;
+    if stack.contains(&canonical) {
+        Err(GLWErrorKind::ShaderIncludeCycle(path.to_path_buf()))?;
+    }
+
+    let code = fs::read_to_string(&canonical).info(format!("{}", path.display()))?;
+    let index = source.files.len();
+    source.files.push(path.to_path_buf());
+    stack.push(canonical);
+
+    if index != 0 {
+        writeln!(source.code, "#line 1 {index}").unwrap();
+    }
+
+    for (n, line) in code.lines().enumerate() {
+        let Some(directive) = line.trim_start().strip_prefix("#include") else {
+            source.code.push_str(line);
+            source.code.push('\n');
+            continue;
+        };
+
+        let include = directive
+            .trim()
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+            .ok_or_else(|| {
+                GLWError::new(
+                    GLWErrorKind::InvalidIncludeDirective(line.to_string()),
+                    format!("{}:{}", path.display(), n + 1),
+                )
+            })?;
+
+        let parent = path.parent().unwrap_or(Path::new(""));
+        expand(&parent.join(include), stack, source)?;
+        // resume numbering of the includer right after the directive
+        writeln!(source.code, "#line {} {index}", n + 2).unwrap();
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `files` into a fresh directory named after `test`.
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("learngl-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (name, code) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, code).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn resolves_nested_includes() {
+        let dir = write_files(
+            "nested-includes",
+            &[
+                ("main.glsl", "a\n#include \"lib/b.glsl\"\nd\n"),
+                ("lib/b.glsl", "b\n#include \"c.glsl\"\n"),
+                ("lib/c.glsl", "c\n"),
+            ],
+        );
+        let source = resolve_includes(dir.join("main.glsl")).unwrap();
+        assert_eq!(
+            source.code,
+            "a\n#line 1 1\nb\n#line 1 2\nc\n#line 3 1\n#line 3 0\nd\n"
+        );
+        assert_eq!(
+            source.includes(),
+            [dir.join("lib/b.glsl"), dir.join("lib/c.glsl")]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_include_cycles() {
+        let dir = write_files(
+            "include-cycles",
+            &[
+                ("a.glsl", "#include \"b.glsl\"\n"),
+                ("b.glsl", "#include \"a.glsl\"\n"),
+            ],
+        );
+        let err = resolve_includes(dir.join("a.glsl")).err().unwrap();
+        assert!(matches!(err.kind(), GLWErrorKind::ShaderIncludeCycle(_)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_malformed_includes() {
+        let dir = write_files("malformed-includes", &[("a.glsl", "#include <b.glsl>\n")]);
+        let err = resolve_includes(dir.join("a.glsl")).err().unwrap();
+        assert!(matches!(
+            err.kind(),
+            GLWErrorKind::InvalidIncludeDirective(_)
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn remaps_source_string_numbers_to_files() {
+        let source = Source {
+            code: String::new(),
+            files: vec!["main.frag".into(), "lib.glsl".into()],
+        };
+        let log = "ERROR: 1:4: 'x' : undeclared identifier\n0(7) : warning C7050\n3:1: other";
+        assert_eq!(
+            source.remap_log(log),
+            "ERROR: lib.glsl:4: 'x' : undeclared identifier\nmain.frag(7) : warning C7050\n3:1: other"
+        );
+    }
+
+    #[test]
+    fn injects_defines_after_the_version() {
+        let defines = [("LIGHTS".to_string(), "4".to_string())];
+        assert_eq!(
+            inject_defines("#version 330 core\nvoid main() {}\n", &defines),
+            "#version 330 core\n#define LIGHTS 4\n#line 2 0\nvoid main() {}\n"
+        );
+        assert_eq!(
+            inject_defines("void main() {}\n", &defines),
+            "#define LIGHTS 4\n#line 1 0\nvoid main() {}\n"
+        );
+        assert_eq!(inject_defines("void main() {}", &[]), "void main() {}");
+    }
+
+    #[test]
+    fn retargets_the_version_directive() {
+        assert_eq!(
+            retarget("#version 450 core\nvoid main() {}\n", GlslTarget::Core(330)),
+            "#version 330 core\nvoid main() {}\n"
+        );
+        assert_eq!(
+            retarget("void main() {}\n", GlslTarget::Core(330)),
+            "#version 330 core\n#line 1 0\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn adds_es_precision_defaults() {
+        assert_eq!(
+            retarget("#version 330 core\nout vec4 color;\n", GlslTarget::Es(300)),
+            "#version 300 es\nprecision highp float;\nprecision highp int;\n#line 2 0\nout vec4 color;\n"
+        );
+        let declared = "#version 330 core\nprecision mediump float;\n";
+        assert_eq!(
+            retarget(declared, GlslTarget::Es(300)),
+            "#version 300 es\nprecision mediump float;\n"
+        );
+    }
+
+    #[test]
+    fn keeps_extensions_before_precision_defaults() {
+        let code = "// header\n#version 330 core\n#extension GL_EXT_a : require\n\n#extension GL_EXT_b : enable\nout vec4 color;\n";
+        assert_eq!(
+            retarget(code, GlslTarget::Es(300)),
+            "// header\n#version 300 es\n#extension GL_EXT_a : require\n\n#extension GL_EXT_b : enable\nprecision highp float;\nprecision highp int;\n#line 6 0\nout vec4 color;\n"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Projection matrices for GL's clip space: right-handed view space looking
+//! down `-Z`, and normalized depth from -1 at the near plane to 1 at the far
+//! one. With glam use the `_gl` constructors, e.g.
+//! `Mat4::perspective_rh_gl`, the others map depth to 0..1.
+//!
+//! The `reverse_z` variants are for [`DepthMode::ReverseZ`], mapping the
+//! near plane to 1 and the far one to 0.
+//!
+//! [`DepthMode::ReverseZ`]: crate::context::DepthMode::ReverseZ
+
+use cgmath::{Matrix4, Rad};
+
+/// Perspective projection with a vertical field of view of `fov_y`, e.g.
+/// `Deg(45.0)`, for a viewport of `aspect` width over height.
+pub fn perspective(fov_y: impl Into<Rad<f32>>, aspect: f32, near: f32, far: f32) -> Matrix4<f32> {
+    let Rad(fov_y) = fov_y.into();
+    let f = 1.0 / (fov_y / 2.0).tan();
+    let depth = near - far;
+
+    #[rustfmt::skip]
+    let projection = Matrix4::new(
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, (far + near) / depth, -1.0,
+        0.0, 0.0, 2.0 * far * near / depth, 0.0,
+    );
+    projection
+}
+
+/// [`perspective`] with the far plane at infinity, so nothing far away is
+/// clipped.
+pub fn infinite_perspective(fov_y: impl Into<Rad<f32>>, aspect: f32, near: f32) -> Matrix4<f32> {
+    let Rad(fov_y) = fov_y.into();
+    let f = 1.0 / (fov_y / 2.0).tan();
+
+    #[rustfmt::skip]
+    let projection = Matrix4::new(
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, -1.0, -1.0,
+        0.0, 0.0, -2.0 * near, 0.0,
+    );
+    projection
+}
+
+/// Orthographic projection of the box from `left`, `bottom`, `-near` to
+/// `right`, `top`, `-far` in view space.
+pub fn orthographic(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+) -> Matrix4<f32> {
+    let (width, height, depth) = (right - left, top - bottom, far - near);
+
+    #[rustfmt::skip]
+    let projection = Matrix4::new(
+        2.0 / width, 0.0, 0.0, 0.0,
+        0.0, 2.0 / height, 0.0, 0.0,
+        0.0, 0.0, -2.0 / depth, 0.0,
+        -(right + left) / width, -(top + bottom) / height, -(far + near) / depth, 1.0,
+    );
+    projection
+}
+
+/// [`perspective`] for 0..1 clip space depth, reversed.
+pub fn perspective_reverse_z(
+    fov_y: impl Into<Rad<f32>>,
+    aspect: f32,
+    near: f32,
+    far: f32,
+) -> Matrix4<f32> {
+    let Rad(fov_y) = fov_y.into();
+    let f = 1.0 / (fov_y / 2.0).tan();
+    let depth = far - near;
+
+    #[rustfmt::skip]
+    let projection = Matrix4::new(
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, near / depth, -1.0,
+        0.0, 0.0, far * near / depth, 0.0,
+    );
+    projection
+}
+
+/// [`infinite_perspective`] for 0..1 clip space depth, reversed. The usual
+/// choice with reverse-Z, infinity landing exactly on 0.
+pub fn infinite_perspective_reverse_z(
+    fov_y: impl Into<Rad<f32>>,
+    aspect: f32,
+    near: f32,
+) -> Matrix4<f32> {
+    let Rad(fov_y) = fov_y.into();
+    let f = 1.0 / (fov_y / 2.0).tan();
+
+    #[rustfmt::skip]
+    let projection = Matrix4::new(
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, 0.0, -1.0,
+        0.0, 0.0, near, 0.0,
+    );
+    projection
+}
+
+/// [`orthographic`] for 0..1 clip space depth, reversed.
+pub fn orthographic_reverse_z(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+) -> Matrix4<f32> {
+    let (width, height, depth) = (right - left, top - bottom, far - near);
+
+    #[rustfmt::skip]
+    let projection = Matrix4::new(
+        2.0 / width, 0.0, 0.0, 0.0,
+        0.0, 2.0 / height, 0.0, 0.0,
+        0.0, 0.0, 1.0 / depth, 0.0,
+        -(right + left) / width, -(top + bottom) / height, far / depth, 1.0,
+    );
+    projection
+}
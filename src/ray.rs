@@ -0,0 +1,232 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+
+use crate::{
+    bounds::{Aabb, Sphere},
+    context::DepthMode,
+};
+
+/// Half-line from `origin` along `direction`, for picking objects with the
+/// mouse. Hits are reported as the distance `t` along the ray, in units of
+/// `direction`'s length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    /// Normalized by the constructors.
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// World space ray through `position` on a `viewport` sized window,
+    /// both from the top left in the same units, e.g. the cursor position
+    /// and window size in screen coordinates. Starts on the near plane.
+    /// `None` if `projection * view` can't be inverted.
+    pub fn from_screen(
+        position: (f64, f64),
+        viewport: (f64, f64),
+        view: &Matrix4<f32>,
+        projection: &Matrix4<f32>,
+    ) -> Option<Self> {
+        Self::from_screen_with_depth(position, viewport, view, projection, DepthMode::Standard)
+    }
+
+    /// [`from_screen`](Self::from_screen) for a projection made for
+    /// `depth_mode`.
+    pub fn from_screen_with_depth(
+        position: (f64, f64),
+        viewport: (f64, f64),
+        view: &Matrix4<f32>,
+        projection: &Matrix4<f32>,
+        depth_mode: DepthMode,
+    ) -> Option<Self> {
+        let x = (2.0 * position.0 / viewport.0 - 1.0) as f32;
+        let y = (1.0 - 2.0 * position.1 / viewport.1) as f32;
+        let inverse = (projection * view).invert()?;
+        let unproject = |z| Point3::from_homogeneous(inverse * Vector4::new(x, y, z, 1.0));
+        // any depth beyond the near plane gives the direction, short of the
+        // far plane, which an infinite projection puts at infinity
+        let near = unproject(depth_mode.near_ndc());
+        let beyond = unproject(depth_mode.near_ndc() * 0.5);
+        Some(Self::new(near, beyond - near))
+    }
+
+    pub fn at(&self, t: f32) -> Point3<f32> {
+        self.origin + self.direction * t
+    }
+
+    /// Where the ray enters `aabb`, 0 when it starts inside.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let (mut near, mut far) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            // infinite for axis-parallel rays, which the comparisons handle
+            let inverse = 1.0 / self.direction[axis];
+            let t0 = (aabb.min[axis] - self.origin[axis]) * inverse;
+            let t1 = (aabb.max[axis] - self.origin[axis]) * inverse;
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        (near <= far).then_some(near)
+    }
+
+    /// Where the ray enters `sphere`, 0 when it starts inside.
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        let to_center = sphere.center - self.origin;
+        let along = to_center.dot(self.direction);
+        let miss_squared = to_center.magnitude2() - along * along;
+        let radius_squared = sphere.radius * sphere.radius;
+        if miss_squared > radius_squared {
+            return None;
+        }
+        let half_chord = (radius_squared - miss_squared).sqrt();
+        let (t0, t1) = (along - half_chord, along + half_chord);
+        match (t0 >= 0.0, t1 >= 0.0) {
+            (true, _) => Some(t0),
+            (false, true) => Some(0.0),
+            _ => None,
+        }
+    }
+
+    /// Where the ray hits the triangle `a`, `b`, `c` from either side, with
+    /// the Möller-Trumbore test.
+    pub fn intersects_triangle(
+        &self,
+        a: Point3<f32>,
+        b: Point3<f32>,
+        c: Point3<f32>,
+    ) -> Option<f32> {
+        const EPSILON: f32 = 1e-7;
+        let (ab, ac) = (b - a, c - a);
+        let p = self.direction.cross(ac);
+        let determinant = ab.dot(p);
+        if determinant.abs() < EPSILON {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = to_origin.cross(ab);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = ac.dot(q) * inverse;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// Nearest hit with the triangles of `positions`, taken three at a time
+    /// or as indexed by `indices`.
+    pub fn intersects_triangles(
+        &self,
+        positions: &[Point3<f32>],
+        indices: Option<&[u32]>,
+    ) -> Option<f32> {
+        let triangle = |i: usize| match indices {
+            Some(indices) => indices[i] as usize,
+            None => i,
+        };
+        let count = indices.map_or(positions.len(), <[u32]>::len) / 3;
+        (0..count)
+            .filter_map(|i| {
+                let [a, b, c] = [0, 1, 2].map(|corner| positions[triangle(i * 3 + corner)]);
+                self.intersects_triangle(a, b, c)
+            })
+            .min_by(f32::total_cmp)
+    }
+
+    /// The ray in the space `transform` maps to, e.g. with an inverted
+    /// model matrix to test against object space geometry. The direction
+    /// is renormalized, so distances differ from the original ray's.
+    pub fn transformed(&self, transform: &Matrix4<f32>) -> Ray {
+        let origin = transform * self.origin.to_homogeneous();
+        let direction = transform * self.direction.extend(0.0);
+        Ray::new(Point3::from_homogeneous(origin), direction.truncate())
+    }
+}
+
+impl Default for Ray {
+    fn default() -> Self {
+        Self::new(Point3::origin(), -Vector3::unit_z())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: Option<f32>, b: f32) -> bool {
+        a.is_some_and(|a| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn hits_aabb_from_outside_and_inside() {
+        let aabb = Aabb::new(Point3::new(-1.0, -1.0, -6.0), Point3::new(1.0, 1.0, -4.0));
+        assert!(approx(Ray::default().intersects_aabb(&aabb), 4.0));
+        let inside = Ray::new(Point3::new(0.0, 0.0, -5.0), Vector3::unit_x());
+        assert!(approx(inside.intersects_aabb(&aabb), 0.0));
+        let beside = Ray::new(Point3::new(2.0, 0.0, 0.0), -Vector3::unit_z());
+        assert_eq!(beside.intersects_aabb(&aabb), None);
+        let away = Ray::new(Point3::origin(), Vector3::unit_z());
+        assert_eq!(away.intersects_aabb(&aabb), None);
+    }
+
+    #[test]
+    fn hits_sphere_from_outside_and_inside() {
+        let sphere = Sphere::new(Point3::new(0.0, 0.0, -5.0), 1.0);
+        assert!(approx(Ray::default().intersects_sphere(&sphere), 4.0));
+        let inside = Ray::new(Point3::new(0.0, 0.0, -5.0), Vector3::unit_y());
+        assert!(approx(inside.intersects_sphere(&sphere), 0.0));
+        let beside = Ray::new(Point3::new(0.0, 1.5, 0.0), -Vector3::unit_z());
+        assert_eq!(beside.intersects_sphere(&sphere), None);
+        let away = Ray::new(Point3::origin(), Vector3::unit_z());
+        assert_eq!(away.intersects_sphere(&sphere), None);
+    }
+
+    #[test]
+    fn hits_triangles_from_either_side() {
+        let [a, b, c] = [
+            Point3::new(-1.0, -1.0, -3.0),
+            Point3::new(1.0, -1.0, -3.0),
+            Point3::new(0.0, 1.0, -3.0),
+        ];
+        assert!(approx(Ray::default().intersects_triangle(a, b, c), 3.0));
+        assert!(approx(Ray::default().intersects_triangle(a, c, b), 3.0));
+        let beside = Ray::new(Point3::new(1.0, 1.0, 0.0), -Vector3::unit_z());
+        assert_eq!(beside.intersects_triangle(a, b, c), None);
+        let parallel = Ray::new(Point3::new(0.0, 0.0, -3.0), Vector3::unit_x());
+        assert_eq!(parallel.intersects_triangle(a, b, c), None);
+    }
+
+    #[test]
+    fn picks_nearest_triangle() {
+        let quad = |z| {
+            [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| Point3::new(x, y, z))
+        };
+        let positions = [quad(-8.0), quad(-2.0)].concat();
+        let indices = [0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7];
+        assert!(approx(
+            Ray::default().intersects_triangles(&positions, Some(&indices)),
+            2.0
+        ));
+        assert!(approx(
+            Ray::default().intersects_triangles(&positions[..3], None),
+            8.0
+        ));
+    }
+
+    #[test]
+    fn transforms_into_object_space() {
+        let model = Matrix4::from_translation(Vector3::new(0.0, 0.0, -5.0));
+        let ray = Ray::default().transformed(&model.invert().unwrap());
+        assert_eq!(ray.origin, Point3::new(0.0, 0.0, 5.0));
+        assert_eq!(ray.direction, -Vector3::unit_z());
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread::JoinHandle,
+};
+
+use gif::SetParameter;
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    pixel_buffer::PixelReader,
+    trace,
+};
+
+/// How a [`Recorder`] encodes the frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// Looping GIF with `delay` hundredths of a second between frames.
+    Gif { delay: u16 },
+    /// Raw frames piped to `ffmpeg`, which has to be on the `PATH` and picks
+    /// the container and codec from the file extension.
+    Ffmpeg { fps: u32 },
+}
+
+/// Records the default framebuffer frame by frame. Frames are read back
+/// through pixel buffers, so capturing doesn't stall the GPU, and encoded
+/// on a worker thread. Encoding errors are returned by
+/// [`finish`](Self::finish), a recorder dropped without it only traces them.
+pub struct Recorder {
+    reader: PixelReader,
+    sender: Option<Sender<Vec<u8>>>,
+    worker: Option<JoinHandle<Result<(), GLWError>>>,
+}
+
+impl Recorder {
+    /// Starts recording the `width` x `height` bottom left corner of the
+    /// default framebuffer to `path`. GIFs fail with
+    /// [`GifTooLarge`](GLWErrorKind::GifTooLarge) past 65535 pixels a side.
+    pub fn new(
+        path: impl AsRef<Path>,
+        width: u32,
+        height: u32,
+        format: RecordingFormat,
+    ) -> Result<Self, GLWError> {
+        let path = path.as_ref().to_path_buf();
+        let (sender, frames) = mpsc::channel();
+        let worker = match format {
+            RecordingFormat::Gif { delay } => {
+                if width > u16::MAX as u32 || height > u16::MAX as u32 {
+                    Err(GLWErrorKind::GifTooLarge { width, height })?;
+                }
+                let file = BufWriter::new(File::create(&path)?);
+                std::thread::spawn(move || encode_gif(file, width, height, delay, frames))
+            }
+            RecordingFormat::Ffmpeg { fps } => {
+                std::thread::spawn(move || pipe_to_ffmpeg(path, width, height, fps, frames))
+            }
+        };
+
+        Ok(Self {
+            reader: PixelReader::new(width, height),
+            sender: Some(sender),
+            worker: Some(worker),
+        })
+    }
+
+    /// Captures the frame just rendered, call it before swapping buffers.
+    /// A frame is only read back on the call after its own.
+    pub fn capture_frame(&mut self) -> Result<(), GLWError> {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+        if let Some(pixels) = self.reader.read(0, 0)? {
+            self.send(pixels);
+        }
+        Ok(())
+    }
+
+    /// Picks up the last captured frame and waits for encoding to finish.
+    pub fn finish(mut self) -> Result<(), GLWError> {
+        if let Some(pixels) = self.reader.read(0, 0)? {
+            self.send(pixels);
+        }
+        match self.stop() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    fn send(&self, pixels: Vec<u8>) {
+        if let Some(sender) = &self.sender {
+            // a failed worker reports its error from `finish`
+            let _ = sender.send(pixels);
+        }
+    }
+
+    /// Closes the frame channel and joins the worker, `Err` if it panicked.
+    fn stop(&mut self) -> std::thread::Result<Result<(), GLWError>> {
+        drop(self.sender.take());
+        self.worker.take().map_or(Ok(Ok(())), JoinHandle::join)
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // resuming a worker panic here could abort a thread already
+        // unwinding
+        match self.stop() {
+            Ok(Ok(())) => {}
+            Ok(Err(_err)) => trace::event!(ERROR, "recording failed: {_err}"),
+            Err(_) => trace::event!(ERROR, "recording worker panicked"),
+        }
+    }
+}
+
+fn encode_gif(
+    file: BufWriter<File>,
+    width: u32,
+    height: u32,
+    delay: u16,
+    frames: Receiver<Vec<u8>>,
+) -> Result<(), GLWError> {
+    let (width, height) = (width as u16, height as u16);
+    let mut encoder = gif::Encoder::new(file, width, height, &[])?;
+    encoder.set(gif::Repeat::Infinite)?;
+
+    let row_len = width as usize * 4;
+    for pixels in frames {
+        // GL rows are bottom to top
+        let mut pixels: Vec<u8> = pixels
+            .chunks_exact(row_len)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+        let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+fn pipe_to_ffmpeg(
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    fps: u32,
+    frames: Receiver<Vec<u8>>,
+) -> Result<(), GLWError> {
+    let mut ffmpeg = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-loglevel",
+            "error",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "rgba",
+        ])
+        .args(["-s", &format!("{width}x{height}"), "-r", &fps.to_string()])
+        .args(["-i", "-", "-vf", "vflip"])
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = ffmpeg.stdin.take().expect("stdin is piped");
+    for pixels in frames {
+        stdin.write_all(&pixels)?;
+    }
+    drop(stdin);
+
+    let status = ffmpeg.wait()?;
+    if !status.success() {
+        Err(io::Error::other(format!("ffmpeg exited with {status}")))?
+    }
+    Ok(())
+}
@@ -0,0 +1,451 @@
+use std::rc::Rc;
+
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::{
+    bounds::{Aabb, Sphere},
+    errors::{GLWError, GLWErrorKind},
+    frustum::Frustum,
+    lod::LodView,
+    material::{skip_missing, AlphaMode, Material},
+    mesh::Mesh,
+    ray::Ray,
+    shader::ShaderProgram,
+    transform::Transform,
+};
+
+/// Geometry a scene [`Node`] draws, whatever its vertex type.
+pub trait Drawable {
+    fn draw(&self);
+
+    /// Draws as seen from `view`, placed with `model`, picking a level of
+    /// detail for a [`Lod`](crate::lod::Lod).
+    fn draw_lod(&self, _view: &LodView, _model: &Matrix4<f32>) {
+        self.draw();
+    }
+
+    /// Object space bounds.
+    fn bounds(&self) -> Option<Aabb> {
+        None
+    }
+
+    fn bounding_sphere(&self) -> Option<Sphere> {
+        None
+    }
+
+    /// Material used when the node has none of its own.
+    fn material(&self) -> Option<&Rc<Material>> {
+        None
+    }
+}
+
+impl<T> Drawable for Mesh<T> {
+    fn draw(&self) {
+        Mesh::draw(self);
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Mesh::bounds(self)
+    }
+
+    fn bounding_sphere(&self) -> Option<Sphere> {
+        Mesh::bounding_sphere(self)
+    }
+
+    fn material(&self) -> Option<&Rc<Material>> {
+        Mesh::material(self)
+    }
+}
+
+/// Handle of a node in the [`Scene`] that added it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+pub struct Node {
+    pub name: Option<String>,
+    /// Relative to the parent node.
+    pub transform: Transform,
+    pub mesh: Option<Rc<dyn Drawable>>,
+    /// Overrides the mesh's own material.
+    pub material: Option<Rc<Material>>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl Node {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            name: None,
+            transform,
+            mesh: None,
+            material: None,
+            parent: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_mesh(mut self, mesh: Rc<dyn Drawable>) -> Self {
+        self.mesh = Some(mesh);
+        self
+    }
+
+    pub fn with_material(mut self, material: Rc<Material>) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    /// The node's material, or its mesh's.
+    pub fn effective_material(&self) -> Option<&Rc<Material>> {
+        self.material
+            .as_ref()
+            .or_else(|| self.mesh.as_ref()?.material())
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self::new(Transform::identity())
+    }
+}
+
+/// Hierarchy of nodes, each placed relative to its parent.
+///
+/// World matrices are worked out while iterating, so transforms can change
+/// freely between frames.
+/// ```ignore
+/// let mut scene = Scene::new();
+/// let car = scene.add_root(Node::new(Transform::identity()).with_mesh(body));
+/// scene.add_child(car, Node::new(wheel_offset).with_mesh(wheel));
+/// scene.draw(&program)?;
+/// ```
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Node>,
+    roots: Vec<NodeId>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_root(&mut self, node: Node) -> NodeId {
+        self.add(node, None)
+    }
+
+    pub fn add_child(&mut self, parent: NodeId, node: Node) -> NodeId {
+        self.add(node, Some(parent))
+    }
+
+    /// Adds `node` under `parent`, or as a root without one. Any children
+    /// `node` lists are dropped, add them after it.
+    pub fn add(&mut self, mut node: Node, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        node.parent = parent;
+        node.children.clear();
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        self.nodes.push(node);
+        id
+    }
+
+    /// Moves `id` under `parent`, or to the roots without one, keeping its
+    /// local transform. Fails with
+    /// [`InvalidSceneParent`](GLWErrorKind::InvalidSceneParent) if `parent`
+    /// is `id` or one of its descendants.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), GLWError> {
+        let mut ancestor = parent;
+        while let Some(node) = ancestor {
+            if node == id {
+                Err(GLWErrorKind::InvalidSceneParent)?;
+            }
+            ancestor = self.nodes[node.0].parent;
+        }
+
+        let siblings = match self.nodes[id.0].parent {
+            Some(old) => &mut self.nodes[old.0].children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|&sibling| sibling != id);
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        self.nodes[id.0].parent = parent;
+        Ok(())
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.0]
+    }
+
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// First node called `name`, in the order they were added.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.name.as_deref() == Some(name))
+            .map(NodeId)
+    }
+
+    /// Sets the local transforms of `nodes` from `pose`, matched up by
+    /// index, e.g. to apply an [`Animator`](crate::animation::Animator)'s
+    /// pose to nodes from `GltfModel::instantiate`. `None` entries are
+    /// skipped.
+    pub fn set_pose(&mut self, nodes: &[Option<NodeId>], pose: &[Transform]) {
+        for (id, transform) in nodes.iter().zip(pose) {
+            if let Some(id) = id {
+                self.nodes[id.0].transform = *transform;
+            }
+        }
+    }
+
+    /// Transform from `id`'s space to world space.
+    pub fn world_matrix(&self, id: NodeId) -> Matrix4<f32> {
+        let mut matrix = self.nodes[id.0].transform.matrix();
+        let mut parent = self.nodes[id.0].parent;
+        while let Some(node) = parent {
+            matrix = self.nodes[node.0].transform.matrix() * matrix;
+            parent = self.nodes[node.0].parent;
+        }
+        matrix
+    }
+
+    /// World matrix of every node, indexed by [`NodeId::index`].
+    pub fn world_matrices(&self) -> Vec<Matrix4<f32>> {
+        let mut matrices = vec![Matrix4::identity(); self.nodes.len()];
+        for (id, _, world) in self.iter() {
+            matrices[id.0] = world;
+        }
+        matrices
+    }
+
+    /// World space bounds of `id`'s mesh, following the transforms of the
+    /// node and its ancestors.
+    pub fn world_bounds(&self, id: NodeId) -> Option<Aabb> {
+        let bounds = self.nodes[id.0].mesh.as_ref()?.bounds()?;
+        Some(bounds.transformed(&self.world_matrix(id)))
+    }
+
+    pub fn world_bounding_sphere(&self, id: NodeId) -> Option<Sphere> {
+        let sphere = self.nodes[id.0].mesh.as_ref()?.bounding_sphere()?;
+        Some(sphere.transformed(&self.world_matrix(id)))
+    }
+
+    /// World space bounds of every mesh in the scene.
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.iter()
+            .filter_map(|(_, node, world)| Some(node.mesh.as_ref()?.bounds()?.transformed(&world)))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Nodes with a mesh that may be inside `frustum`, in iteration order.
+    /// Meshes without bounds always are.
+    pub fn visible(&self, frustum: &Frustum) -> Vec<NodeId> {
+        self.iter()
+            .filter(|(_, node, world)| {
+                node.mesh
+                    .as_ref()
+                    .is_some_and(|mesh| is_visible(mesh.as_ref(), frustum, world))
+            })
+            .map(|(id, _, _)| id)
+            .collect()
+    }
+
+    /// Nearest node whose mesh's world bounds `ray` hits, with the distance
+    /// along it.
+    pub fn pick(&self, ray: &Ray) -> Option<(NodeId, f32)> {
+        self.iter()
+            .filter_map(|(id, node, world)| {
+                let bounds = node.mesh.as_ref()?.bounds()?.transformed(&world);
+                Some((id, ray.intersects_aabb(&bounds)?))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Nodes depth first, parents before their children and siblings in the
+    /// order they were added, with their world matrices.
+    pub fn iter(&self) -> SceneIter<'_> {
+        SceneIter {
+            scene: self,
+            stack: self
+                .roots
+                .iter()
+                .rev()
+                .map(|&root| (root, Matrix4::identity()))
+                .collect(),
+        }
+    }
+
+    /// Draws every node with a mesh with `program`, setting its `model`
+    /// uniform to the node's world matrix and [binding](Material::bind_with)
+    /// the node's material. Blended nodes come after the others, in
+    /// iteration order.
+    pub fn draw(&self, program: &ShaderProgram) -> Result<(), GLWError> {
+        self.draw_nodes(|_| Ok(program), None)
+    }
+
+    /// Like [`draw`](Self::draw), picking each mesh's level of detail as
+    /// seen from `view`.
+    pub fn draw_lod(&self, program: &ShaderProgram, view: &LodView) -> Result<(), GLWError> {
+        self.draw_nodes(|_| Ok(program), Some(view))
+    }
+
+    /// Like [`draw`](Self::draw), with each node's material's own program.
+    /// Fails with [`MaterialWithoutProgram`](GLWErrorKind::MaterialWithoutProgram)
+    /// for a mesh without a material or one without a program.
+    pub fn draw_with_materials(&self) -> Result<(), GLWError> {
+        self.draw_nodes(program_of_material, None)
+    }
+
+    pub fn draw_with_materials_lod(&self, view: &LodView) -> Result<(), GLWError> {
+        self.draw_nodes(program_of_material, Some(view))
+    }
+
+    fn draw_nodes<'a>(
+        &'a self,
+        program_of: impl Fn(&'a Node) -> Result<&'a ShaderProgram, GLWError>,
+        view: Option<&LodView>,
+    ) -> Result<(), GLWError> {
+        let is_blended = |node: &Node| {
+            node.effective_material()
+                .is_some_and(|material| material.alpha_mode == AlphaMode::Blend)
+        };
+        let drawn = self.iter().filter(|(_, node, _)| node.mesh.is_some());
+        let (blended, opaque): (Vec<_>, Vec<_>) = drawn.partition(|(_, node, _)| is_blended(node));
+
+        for (_, node, world) in opaque.into_iter().chain(blended) {
+            let program = program_of(node)?;
+            if let Some(material) = node.effective_material() {
+                material.bind_with(program)?;
+            }
+            skip_missing(program.set_mat4("model", world))?;
+            program.use_program();
+            match (&node.mesh, view) {
+                (Some(mesh), Some(view)) => mesh.draw_lod(view, &world),
+                (Some(mesh), None) => mesh.draw(),
+                (None, _) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn program_of_material(node: &Node) -> Result<&ShaderProgram, GLWError> {
+    let material = node.effective_material();
+    match material.and_then(|material| material.program()) {
+        Some(program) => Ok(program.as_ref()),
+        None => Err(GLWErrorKind::MaterialWithoutProgram(
+            material.and_then(|material| material.name.clone()),
+        ))?,
+    }
+}
+
+fn is_visible(mesh: &dyn Drawable, frustum: &Frustum, world: &Matrix4<f32>) -> bool {
+    let outside_sphere = mesh
+        .bounding_sphere()
+        .is_some_and(|sphere| !frustum.intersects_sphere(&sphere.transformed(world)));
+    !outside_sphere
+        && mesh
+            .bounds()
+            .is_none_or(|bounds| frustum.intersects_aabb(&bounds.transformed(world)))
+}
+
+/// Iterator of [`Scene::iter`].
+pub struct SceneIter<'a> {
+    scene: &'a Scene,
+    /// Nodes left to visit with their parents' world matrices, next on top.
+    stack: Vec<(NodeId, Matrix4<f32>)>,
+}
+
+impl<'a> Iterator for SceneIter<'a> {
+    type Item = (NodeId, &'a Node, Matrix4<f32>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, parent) = self.stack.pop()?;
+        let node = &self.scene.nodes[id.0];
+        let world = parent * node.transform.matrix();
+        self.stack
+            .extend(node.children.iter().rev().map(|&child| (child, world)));
+        Some((id, node, world))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Vector3;
+
+    use super::*;
+
+    fn chain(scene: &mut Scene) -> [NodeId; 3] {
+        let offset = |x| Node::new(Transform::from_translation(Vector3::new(x, 0.0, 0.0)));
+        let root = scene.add_root(offset(1.0));
+        let child = scene.add_child(root, offset(2.0));
+        let grandchild = scene.add_child(child, offset(4.0));
+        [root, child, grandchild]
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let mut scene = Scene::new();
+        let [root, child, grandchild] = chain(&mut scene);
+        for (id, parent) in [(root, root), (root, grandchild), (child, grandchild)] {
+            let err = scene.set_parent(id, Some(parent)).unwrap_err();
+            assert!(matches!(err.kind(), GLWErrorKind::InvalidSceneParent));
+        }
+        assert_eq!(scene.roots(), [root]);
+        assert_eq!(scene.node(child).parent(), Some(root));
+        assert_eq!(scene.node(grandchild).parent(), Some(child));
+    }
+
+    #[test]
+    fn reparents_keeping_local_transform() {
+        let mut scene = Scene::new();
+        let [root, child, grandchild] = chain(&mut scene);
+        scene.set_parent(grandchild, Some(root)).unwrap();
+        assert_eq!(scene.node(child).children(), []);
+        assert_eq!(scene.node(root).children(), [child, grandchild]);
+        assert_eq!(scene.world_matrix(grandchild).w.x, 5.0);
+
+        scene.set_parent(child, None).unwrap();
+        assert_eq!(scene.roots(), [root, child]);
+        assert_eq!(scene.node(child).parent(), None);
+        assert_eq!(scene.world_matrix(child).w.x, 2.0);
+    }
+}
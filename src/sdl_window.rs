@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::Keycode,
+    mouse::MouseButton,
+    video::{GLContext, GLProfile, SwapInterval, Window},
+    EventPump, Sdl, VideoSubsystem,
+};
+
+use crate::{
+    context,
+    errors::{GLWError, GLWErrorKind},
+};
+
+/// An SDL2 window with a current GL context, the `sdl2` feature's
+/// counterpart of the GLFW window `run` creates.
+pub struct SdlWindow {
+    // fields drop in order, the context has to go before the window
+    _context: GLContext,
+    pub window: Window,
+    /// Input state, up to date with the events of the last
+    /// [`poll_events`](Self::poll_events).
+    pub input: SdlInput,
+    event_pump: EventPump,
+    pub video: VideoSubsystem,
+    pub sdl: Sdl,
+}
+
+impl SdlWindow {
+    /// Opens a resizable window with a core profile context of
+    /// `gl_version`, makes it current and loads GL.
+    pub fn new(
+        title: &str,
+        width: u32,
+        height: u32,
+        gl_version: (u8, u8),
+        vsync: bool,
+    ) -> Result<Self, GLWError> {
+        let sdl = sdl2::init().map_err(creation_failed)?;
+        let video = sdl.video().map_err(creation_failed)?;
+        let gl_attr = video.gl_attr();
+        gl_attr.set_context_profile(GLProfile::Core);
+        gl_attr.set_context_version(gl_version.0, gl_version.1);
+        #[cfg(target_os = "macos")]
+        gl_attr.set_context_flags().forward_compatible().set();
+
+        let window = video
+            .window(title, width, height)
+            .opengl()
+            .resizable()
+            .build()
+            .map_err(|err| creation_failed(err.to_string()))?;
+        let context = window.gl_create_context().map_err(creation_failed)?;
+        context::load_with(|symbol| video.gl_get_proc_address(symbol) as *const _);
+        // not every platform lets the interval be changed, which is harmless
+        let _ = video.gl_set_swap_interval(match vsync {
+            true => SwapInterval::VSync,
+            false => SwapInterval::Immediate,
+        });
+
+        let (width, height) = window.drawable_size();
+        unsafe {
+            gl::Viewport(0, 0, width as i32, height as i32);
+        }
+        let event_pump = sdl.event_pump().map_err(creation_failed)?;
+        Ok(Self {
+            _context: context,
+            window,
+            input: SdlInput::default(),
+            event_pump,
+            video,
+            sdl,
+        })
+    }
+
+    /// Takes the pending events, updating [`input`](Self::input) and the
+    /// viewport on resizes, and returns them.
+    pub fn poll_events(&mut self) -> Vec<Event> {
+        self.input.begin_frame();
+        let events: Vec<_> = self.event_pump.poll_iter().collect();
+        for event in &events {
+            self.input.handle_event(event);
+            if let Event::Window {
+                win_event: WindowEvent::SizeChanged(..),
+                ..
+            } = event
+            {
+                let (width, height) = self.window.drawable_size();
+                unsafe {
+                    gl::Viewport(0, 0, width as i32, height as i32);
+                }
+            }
+        }
+        events
+    }
+
+    pub fn swap_buffers(&self) {
+        self.window.gl_swap_window();
+    }
+
+    /// Hides the cursor and reports relative motion for unbounded mouse
+    /// look, or releases it.
+    pub fn capture_cursor(&mut self, captured: bool) {
+        self.sdl.mouse().set_relative_mouse_mode(captured);
+        self.input.cursor = None;
+    }
+}
+
+/// Keyboard and mouse state built from SDL events, with the same queries as
+/// the GLFW backend's `Input`.
+#[derive(Debug, Default, Clone)]
+pub struct SdlInput {
+    held: HashSet<Keycode>,
+    pressed: HashSet<Keycode>,
+    released: HashSet<Keycode>,
+    buttons_held: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    /// Last cursor position, `None` until the first move.
+    cursor: Option<(f64, f64)>,
+    mouse_delta: (f64, f64),
+    scroll_delta: (f64, f64),
+}
+
+impl SdlInput {
+    /// Forgets the presses and releases of the previous frame, call it
+    /// before handling the frame's events.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.mouse_delta = (0.0, 0.0);
+        self.scroll_delta = (0.0, 0.0);
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            // repeats aren't presses, as with GLFW's `Action::Repeat`
+            Event::KeyDown {
+                keycode: Some(key),
+                repeat: false,
+                ..
+            } => {
+                self.held.insert(key);
+                self.pressed.insert(key);
+            }
+            Event::KeyUp {
+                keycode: Some(key), ..
+            } => {
+                self.held.remove(&key);
+                self.released.insert(key);
+            }
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                self.buttons_held.insert(mouse_btn);
+                self.buttons_pressed.insert(mouse_btn);
+            }
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                self.buttons_held.remove(&mouse_btn);
+                self.buttons_released.insert(mouse_btn);
+            }
+            // SDL reports relative motion itself, which keeps working with a
+            // captured cursor
+            Event::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => {
+                if self.cursor.is_some() {
+                    self.mouse_delta.0 += xrel as f64;
+                    self.mouse_delta.1 += yrel as f64;
+                }
+                self.cursor = Some((x as f64, y as f64));
+            }
+            Event::MouseWheel {
+                precise_x,
+                precise_y,
+                ..
+            } => {
+                self.scroll_delta.0 += precise_x as f64;
+                self.scroll_delta.1 += precise_y as f64;
+            }
+            // keys released while unfocused never report it
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                self.held.clear();
+                self.buttons_held.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `key` is held down.
+    pub fn is_pressed(&self, key: Keycode) -> bool {
+        self.held.contains(&key)
+    }
+
+    /// Whether `key` went down this frame.
+    pub fn just_pressed(&self, key: Keycode) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    /// Whether `key` went up this frame.
+    pub fn just_released(&self, key: Keycode) -> bool {
+        self.released.contains(&key)
+    }
+
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_held.contains(&button)
+    }
+
+    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Cursor position in window coordinates from the top left, once the
+    /// cursor moved.
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor
+    }
+
+    /// How far the cursor moved this frame, `y` growing downwards.
+    pub fn mouse_delta(&self) -> (f64, f64) {
+        self.mouse_delta
+    }
+
+    /// Scrolling this frame, `y` positive when scrolling up.
+    pub fn scroll_delta(&self) -> (f64, f64) {
+        self.scroll_delta
+    }
+}
+
+fn creation_failed(err: String) -> GLWError {
+    GLWError::new(GLWErrorKind::WindowCreationFailed, err)
+}
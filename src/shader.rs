@@ -1,18 +1,30 @@
 use std::{
+    cell::RefCell,
+    collections::HashMap,
     ffi::CString,
-    fs,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use crate::{
+    binary_cache::ProgramBinaryCache,
     errors::{GLWError, GLWErrorKind},
+    hot_reload::ShaderWatcher,
+    label,
+    preprocessor::{self, GlslTarget, Source},
+    trace,
+    transform_feedback::TransformFeedbackMode,
     utils,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ShaderType {
     VertexShader,
     FragmentShader,
+    GeometryShader,
+    TessControlShader,
+    TessEvaluationShader,
+    ComputeShader,
 }
 
 impl ShaderType {
@@ -28,14 +40,54 @@ impl ShaderType {
 
     pub fn from_ext(ext: &str) -> std::io::Result<Self> {
         match ext {
-            "fs" => Ok(ShaderType::FragmentShader),
-            "vs" => Ok(ShaderType::VertexShader),
+            "fs" | "frag" => Ok(ShaderType::FragmentShader),
+            "vs" | "vert" => Ok(ShaderType::VertexShader),
+            "gs" | "geom" => Ok(ShaderType::GeometryShader),
+            "tcs" | "tesc" => Ok(ShaderType::TessControlShader),
+            "tes" | "tese" => Ok(ShaderType::TessEvaluationShader),
+            "cs" | "comp" => Ok(ShaderType::ComputeShader),
             ext => Err(std::io::Error::new(
                 std::io::ErrorKind::Unsupported,
                 format!("\"{}\" extension not supported.", ext),
             )),
         }
     }
+
+    /// Like [`from_path`](Self::from_path), but generic `.glsl` files get
+    /// their stage from a `//#stage <name>` pragma in `source`.
+    pub fn detect(path: impl AsRef<Path>, source: &str) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext == "glsl") {
+            return Self::from_pragma(source).ok_or(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "No //#stage pragma found in .glsl shader.",
+            ));
+        }
+
+        Self::from_path(path)
+    }
+
+    pub fn from_pragma(source: &str) -> Option<Self> {
+        source.lines().find_map(|line| {
+            let stage = line.trim().strip_prefix("//#stage")?;
+            Self::from_stage_name(stage.trim()).ok()
+        })
+    }
+
+    pub fn from_stage_name(name: &str) -> std::io::Result<Self> {
+        match name {
+            "vertex" => Ok(ShaderType::VertexShader),
+            "fragment" => Ok(ShaderType::FragmentShader),
+            "geometry" => Ok(ShaderType::GeometryShader),
+            "tess_control" => Ok(ShaderType::TessControlShader),
+            "tess_evaluation" => Ok(ShaderType::TessEvaluationShader),
+            "compute" => Ok(ShaderType::ComputeShader),
+            name => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("\"{}\" shader stage not supported.", name),
+            )),
+        }
+    }
 }
 
 impl TryFrom<u32> for ShaderType {
@@ -45,6 +97,10 @@ impl TryFrom<u32> for ShaderType {
         match value {
             gl::VERTEX_SHADER => Ok(Self::VertexShader),
             gl::FRAGMENT_SHADER => Ok(Self::FragmentShader),
+            gl::GEOMETRY_SHADER => Ok(Self::GeometryShader),
+            gl::TESS_CONTROL_SHADER => Ok(Self::TessControlShader),
+            gl::TESS_EVALUATION_SHADER => Ok(Self::TessEvaluationShader),
+            gl::COMPUTE_SHADER => Ok(Self::ComputeShader),
             _ => Err(std::io::Error::new(
                 std::io::ErrorKind::Unsupported,
                 "This type of shader is not supported.",
@@ -58,13 +114,53 @@ impl From<ShaderType> for u32 {
         match val {
             ShaderType::FragmentShader => gl::FRAGMENT_SHADER,
             ShaderType::VertexShader => gl::VERTEX_SHADER,
+            ShaderType::GeometryShader => gl::GEOMETRY_SHADER,
+            ShaderType::TessControlShader => gl::TESS_CONTROL_SHADER,
+            ShaderType::TessEvaluationShader => gl::TESS_EVALUATION_SHADER,
+            ShaderType::ComputeShader => gl::COMPUTE_SHADER,
         }
     }
 }
 
+/// Sets the number of vertices per patch used by `gl::PATCHES` draw calls
+/// feeding a tessellation stage.
+pub fn set_patch_vertices(count: u32) {
+    unsafe {
+        gl::PatchParameteri(gl::PATCH_VERTICES, count as i32);
+    }
+}
+
+pub fn max_patch_vertices() -> u32 {
+    let mut max = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_PATCH_VERTICES, &mut max);
+    }
+    max as u32
+}
+
 pub struct Shader {
     pub shader_id: u32,
     pub shader_type: ShaderType,
+    pub path: Option<PathBuf>,
+    /// Files pulled in through `#include` directives.
+    pub includes: Vec<PathBuf>,
+    /// What the file was preprocessed with, reused on reload.
+    pub(crate) options: CompileOptions,
+}
+
+/// Defines and GLSL target a file backed shader was preprocessed with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CompileOptions {
+    pub(crate) defines: Vec<(String, String)>,
+    pub(crate) glsl_target: Option<GlslTarget>,
+}
+
+/// File a program was built from, recompiled on reload.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceFile {
+    pub(crate) path: PathBuf,
+    pub(crate) shader_type: ShaderType,
+    pub(crate) options: CompileOptions,
 }
 
 impl Shader {
@@ -82,38 +178,79 @@ impl Shader {
         Ok(Self {
             shader_id,
             shader_type,
+            path: None,
+            includes: vec![],
+            options: CompileOptions::default(),
         })
     }
 
     pub fn from_path(path: impl AsRef<Path>) -> Result<Self, GLWError> {
         let path = path.as_ref();
-        let shader_str = CString::new(fs::read(path)?)?;
-        let shader_type = ShaderType::from_path(path)?;
+        let source = preprocessor::resolve_includes(path)?;
+        let shader_type = ShaderType::detect(path, &source.code)?;
+
+        Self::from_preprocessed(&source, shader_type)
+    }
 
+    /// Compiles the file at `path` as `shader_type`, whatever its extension.
+    pub fn from_path_as(path: impl AsRef<Path>, shader_type: ShaderType) -> Result<Self, GLWError> {
+        Self::from_preprocessed(&preprocessor::resolve_includes(path)?, shader_type)
+    }
+
+    pub(crate) fn from_preprocessed(
+        source: &Source,
+        shader_type: ShaderType,
+    ) -> Result<Self, GLWError> {
+        let shader = Self::compile_unchecked(source, shader_type)?;
+        shader.check_compiled(source)?;
+        Ok(shader)
+    }
+
+    /// Issues the compile without waiting for its result, which drivers with
+    /// `KHR_parallel_shader_compile` use to compile in the background.
+    pub(crate) fn compile_unchecked(
+        source: &Source,
+        shader_type: ShaderType,
+    ) -> Result<Self, GLWError> {
+        let shader_str = CString::new(source.code.as_str())?;
+
+        let _span = trace::span!("compile_shader", ty = ?shader_type, path = ?source.files.first());
         let shader_id = unsafe {
             let shader_id = gl::CreateShader(shader_type.into());
             gl::ShaderSource(shader_id, 1, &shader_str.as_ptr(), std::ptr::null());
             gl::CompileShader(shader_id);
-            Self::check_succes(shader_id, Some(path))?;
             shader_id
         };
+        trace::event!(TRACE, shader_id, "created shader");
+        if let Some(path) = source.files.first() {
+            label::set_path_label(gl::SHADER, shader_id, path);
+        }
 
         Ok(Self {
             shader_id,
             shader_type,
+            path: source.files.first().cloned(),
+            includes: source.includes().to_vec(),
+            options: CompileOptions::default(),
         })
     }
 
-    pub fn get_uniform_location(&self, name: impl AsRef<str>) -> Result<i32, GLWError> {
-        // TODO: copying?
-        let c_name = CString::new(name.as_ref())?;
-
-        let location = unsafe { gl::GetUniformLocation(self.shader_id, c_name.as_ptr()) };
-        if location == -1 {
-            Err(GLWErrorKind::UniformNotFound(name.as_ref().to_string()))?;
+    pub(crate) fn check_compiled(&self, source: &Source) -> Result<(), GLWError> {
+        let _span = trace::span!("check_compiled", shader_id = self.shader_id);
+        let warnings = unsafe {
+            utils::check_shader_succes(self.shader_id, gl::COMPILE_STATUS).map_err(|info| {
+                let info = source.remap_log(&info);
+                trace::event!(ERROR, path = ?self.path, "shader compilation failed: {info}");
+                GLWError::new(
+                    GLWErrorKind::ShaderCompilationFailed(self.path.clone()),
+                    info,
+                )
+            })?
+        };
+        if let Some(_warnings) = warnings {
+            trace::event!(WARN, path = ?self.path, "shader compiled with: {}", source.remap_log(&_warnings));
         }
-
-        Ok(location)
+        Ok(())
     }
 
     /// # Safety
@@ -124,20 +261,51 @@ impl Shader {
                 GLWErrorKind::ShaderCompilationFailed(path.map(|p| p.to_path_buf())),
                 info,
             )
-        })
+        })?;
+        Ok(())
+    }
+
+    /// The compiler's messages, e.g. warnings, `None` if there are none.
+    pub fn info_log(&self) -> Option<String> {
+        let info_log = unsafe { utils::shader_info_log(self.shader_id) };
+        Some(info_log).filter(|log| !log.trim().is_empty())
     }
 }
 
 impl Drop for Shader {
     fn drop(&mut self) {
+        trace::event!(TRACE, shader_id = self.shader_id, "deleting shader");
         unsafe {
             gl::DeleteShader(self.shader_id);
         }
     }
 }
 
+/// Source of the program generations, unique across programs and reloads.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
 pub struct ShaderProgram {
-    shader_program_id: u32,
+    pub(crate) shader_program_id: u32,
+    /// Changes with every link, unlike the id, which GL reuses once a
+    /// program is deleted.
+    pub(crate) generation: u64,
+    /// Files the program was built from, recompiled on reload.
+    pub(crate) source_paths: Vec<SourceFile>,
+    /// Shaders attached without a backing file, reattached as-is on reload.
+    pub(crate) retained_shader_ids: Vec<u32>,
+    /// Files included by the retained shaders, which reloads don't re-read.
+    pub(crate) retained_includes: Vec<PathBuf>,
+    /// Files included by the shaders, watched alongside `source_paths`.
+    pub(crate) include_paths: Vec<PathBuf>,
+    pub(crate) link_options: LinkOptions,
+    pub(crate) watcher: Option<ShaderWatcher>,
+    pub(crate) uniform_locations: RefCell<HashMap<String, i32>>,
+    /// Texture unit of every sampler bound with `bind_texture`.
+    pub(crate) texture_units: RefCell<HashMap<String, u32>>,
 }
 
 impl ShaderProgram {
@@ -145,6 +313,36 @@ impl ShaderProgram {
         ShaderProgramBuilder::new()
     }
 
+    /// Runs `glValidateProgram`, checking the program can execute in the
+    /// current GL state.
+    pub fn validate(&self) -> Result<(), GLWError> {
+        unsafe {
+            gl::ValidateProgram(self.shader_program_id);
+            utils::check_program_success(self.shader_program_id, gl::VALIDATE_STATUS)
+                .map_err(|info| GLWError::new(GLWErrorKind::ShaderProgramValidationFailed, info))?;
+        }
+        Ok(())
+    }
+
+    /// The messages of the last link or validation, e.g. warnings, `None`
+    /// if there are none.
+    pub fn info_log(&self) -> Option<String> {
+        let info_log = unsafe { utils::program_info_log(self.shader_program_id) };
+        Some(info_log).filter(|log| !log.trim().is_empty())
+    }
+
+    pub fn is_separable(&self) -> bool {
+        let mut separable = 0;
+        unsafe {
+            gl::GetProgramiv(
+                self.shader_program_id,
+                gl::PROGRAM_SEPARABLE,
+                &mut separable,
+            );
+        }
+        separable == gl::TRUE as i32
+    }
+
     pub fn use_program(&self) {
         // SAFETY: this can be only done after shader program is created
         // by the builder, so self.shader_program_id is valid
@@ -156,6 +354,11 @@ impl ShaderProgram {
 
 impl Drop for ShaderProgram {
     fn drop(&mut self) {
+        trace::event!(
+            TRACE,
+            program_id = self.shader_program_id,
+            "deleting program"
+        );
         // SAFETY: ShaderProgram can only be created by the builder,
         // so self.shader_program_id is valid
         unsafe {
@@ -165,20 +368,48 @@ impl Drop for ShaderProgram {
 }
 
 pub struct ShaderProgramBuilder<'a> {
-    shader_paths: Vec<PathBuf>,
+    pub(crate) shader_paths: Vec<(PathBuf, Option<ShaderType>)>,
+    shader_sources: Vec<(&'a str, ShaderType)>,
     shaders: Vec<&'a Shader>,
+    binary_cache: Option<PathBuf>,
+    validate: bool,
+    separable: bool,
+    transform_feedback: Option<(Vec<String>, TransformFeedbackMode)>,
+    pub(crate) defines: Vec<(String, String)>,
+    pub(crate) glsl_target: Option<GlslTarget>,
 }
 
 impl<'a> ShaderProgramBuilder<'a> {
     pub fn new() -> Self {
         Self {
             shader_paths: vec![],
+            shader_sources: vec![],
             shaders: vec![],
+            binary_cache: None,
+            validate: false,
+            separable: false,
+            transform_feedback: None,
+            defines: vec![],
+            glsl_target: None,
         }
     }
 
-    pub fn attach_shader_path(mut self, path: impl AsRef<Path>) -> Self {
-        self.shader_paths.push(path.as_ref().to_path_buf());
+    /// Compiles the file at `path` as part of [`build`](Self::build). The
+    /// stage is detected from the file unless `shader_type` is given.
+    pub fn attach_shader_path(
+        mut self,
+        path: impl AsRef<Path>,
+        shader_type: impl Into<Option<ShaderType>>,
+    ) -> Self {
+        self.shader_paths
+            .push((path.as_ref().to_path_buf(), shader_type.into()));
+        self
+    }
+
+    /// Compiles `source` as part of [`build`](Self::build), e.g. for shaders
+    /// embedded with `include_str!`.
+    pub fn attach_shader_source(mut self, source: &'a str, shader_type: ShaderType) -> Self {
+        self.shader_sources.push((source, shader_type));
         self
     }
 
@@ -187,32 +418,471 @@ impl<'a> ShaderProgramBuilder<'a> {
         self
     }
 
+    /// Stores the linked program binary in `dir`, keyed by the shader sources
+    /// and the driver, and loads it instead of compiling on later builds.
+    ///
+    /// Programs loaded from the cache only recompile their file backed
+    /// stages on [`reload`](ShaderProgram::reload).
+    pub fn binary_cache(mut self, dir: impl AsRef<Path>) -> Self {
+        self.binary_cache = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Adds `#define name value` to every shader compiled by the builder.
+    /// Shaders attached with [`attach_shader`](Self::attach_shader) are
+    /// already compiled and left untouched.
+    pub fn define(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.defines.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// Rewrites the `#version` directive of every shader compiled by the
+    /// builder for `target`, see [`preprocessor::retarget`]. Use
+    /// [`GlslTarget::current`] to run the same files on desktop and GLES.
+    pub fn glsl_target(mut self, target: GlslTarget) -> Self {
+        self.glsl_target = Some(target);
+        self
+    }
+
+    /// Links the program with `GL_PROGRAM_SEPARABLE`, so its stages can be
+    /// combined with other programs in a
+    /// [`ProgramPipeline`](crate::pipeline::ProgramPipeline). Separable
+    /// programs don't need to provide a complete pipeline.
+    pub fn separable(mut self, separable: bool) -> Self {
+        self.separable = separable;
+        self
+    }
+
+    /// Outputs of the last vertex processing stage to capture into transform
+    /// feedback buffers, see [`TransformFeedback`](crate::transform_feedback::TransformFeedback).
+    /// Programs capturing transform feedback don't need a fragment shader.
+    pub fn transform_feedback_varyings(
+        mut self,
+        varyings: &[&str],
+        mode: TransformFeedbackMode,
+    ) -> Self {
+        self.transform_feedback = Some((
+            varyings.iter().map(|varying| varying.to_string()).collect(),
+            mode,
+        ));
+        self
+    }
+
+    /// Also run [`ShaderProgram::validate`] after linking.
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    fn check_stages(&self, path_types: &[ShaderType]) -> Result<(), GLWError> {
+        let stages: Vec<ShaderType> = self
+            .shaders
+            .iter()
+            .map(|shader| shader.shader_type)
+            .chain(path_types.iter().copied())
+            .chain(
+                self.shader_sources
+                    .iter()
+                    .map(|&(_, shader_type)| shader_type),
+            )
+            .collect();
+
+        if self.separable || stages.contains(&ShaderType::ComputeShader) {
+            return Ok(());
+        }
+        // capture-only programs usually run with rasterization disabled
+        let required: &[ShaderType] = match self.transform_feedback {
+            Some(_) => &[ShaderType::VertexShader],
+            None => &[ShaderType::VertexShader, ShaderType::FragmentShader],
+        };
+        for &required in required {
+            if !stages.contains(&required) {
+                Err(GLWErrorKind::MissingShaderStage(required))?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn build(self) -> Result<ShaderProgram, GLWError> {
-        let owned_shaders: Vec<Shader> = self
+        let _span = trace::span!("build_program", paths = ?self.shader_paths);
+        let path_sources = self
             .shader_paths
-            .into_iter()
-            .map(Shader::from_path)
+            .iter()
+            .map(|(path, shader_type)| {
+                preprocess_path(path, *shader_type, &self.defines, self.glsl_target)
+            })
             .collect::<Result<_, _>>()?;
+        let prepared = self.prepare(path_sources)?;
+        let (cache, link_options) = self.link_setup(&prepared)?;
+
+        if let Some(shader_program_id) = cache.as_ref().and_then(ProgramBinaryCache::load) {
+            trace::event!(
+                DEBUG,
+                program_id = shader_program_id,
+                "loaded cached program binary"
+            );
+            // reloads relink the embedded shaders, which the binary lacks
+            let embedded = prepared.compile_embedded().inspect_err(|_| unsafe {
+                gl::DeleteProgram(shader_program_id);
+            })?;
+            self.attach_to_binary(shader_program_id, &embedded);
+            return self.finish(shader_program_id, &prepared, embedded, link_options);
+        }
+
+        let owned_shaders = prepared.compile_unchecked()?;
+        prepared.check_compiled(&owned_shaders)?;
+        let shader_program_id = link_program(self.shader_ids(&owned_shaders), &link_options)?;
+        if let Some(cache) = &cache {
+            // the cache is an optimization, failing to write it is not a
+            // reason to fail the build
+            let _ = cache.store(shader_program_id);
+        }
 
-        let shader_program_id = unsafe {
-            let shader_program_id = gl::CreateProgram();
-            self.shaders
-                .into_iter()
-                .chain(owned_shaders.iter())
-                .for_each(|shader| gl::AttachShader(shader_program_id, shader.shader_id));
+        self.finish(shader_program_id, &prepared, owned_shaders, link_options)
+    }
+
+    /// Applies the GLSL target and defines to embedded sources and checks
+    /// the stages.
+    pub(crate) fn prepare(
+        &self,
+        path_sources: Vec<(Source, ShaderType)>,
+    ) -> Result<PreparedSources, GLWError> {
+        let embedded_sources = self
+            .shader_sources
+            .iter()
+            .map(|&(source, shader_type)| {
+                let source = match self.glsl_target {
+                    Some(target) => preprocessor::retarget(source, target),
+                    None => source.to_string(),
+                };
+                (
+                    preprocessor::inject_defines(&source, &self.defines),
+                    shader_type,
+                )
+            })
+            .collect();
+        self.check_stages(
+            &path_sources
+                .iter()
+                .map(|&(_, shader_type)| shader_type)
+                .collect::<Vec<_>>(),
+        )?;
+
+        Ok(PreparedSources {
+            path_sources,
+            embedded_sources,
+        })
+    }
+
+    pub(crate) fn link_setup(
+        &self,
+        prepared: &PreparedSources,
+    ) -> Result<(Option<ProgramBinaryCache>, LinkOptions), GLWError> {
+        let transform_feedback = match &self.transform_feedback {
+            Some((varyings, mode)) => Some((
+                varyings
+                    .iter()
+                    .map(|varying| CString::new(varying.as_str()))
+                    .collect::<Result<_, _>>()?,
+                *mode,
+            )),
+            None => None,
+        };
+        let mut link_options = LinkOptions {
+            binary_retrievable: false,
+            separable: self.separable,
+            transform_feedback,
+        };
 
-            gl::LinkProgram(shader_program_id);
+        let cache = self
+            .binary_cache
+            .as_deref()
+            .filter(|_| ProgramBinaryCache::supported())
+            .map(|dir| {
+                // SAFETY: attached shaders are alive for 'a
+                let attached: Vec<(ShaderType, String)> = self
+                    .shaders
+                    .iter()
+                    .map(|shader| {
+                        (shader.shader_type, unsafe {
+                            utils::shader_source(shader.shader_id)
+                        })
+                    })
+                    .collect();
+                let stages = attached
+                    .iter()
+                    .map(|(shader_type, source)| (*shader_type, source.as_str()))
+                    .chain(prepared.stages());
+                ProgramBinaryCache::with_link_options(dir, stages, &link_options)
+            });
+        link_options.binary_retrievable = cache.is_some();
+
+        Ok((cache, link_options))
+    }
+
+    /// Ids of the attached shaders followed by the ones compiled by the builder.
+    pub(crate) fn shader_ids<'s>(
+        &'s self,
+        owned_shaders: &'s [Shader],
+    ) -> impl Iterator<Item = u32> + 's {
+        self.shaders
+            .iter()
+            .copied()
+            .chain(owned_shaders)
+            .map(|shader| shader.shader_id)
+    }
 
-            utils::check_program_success(shader_program_id, gl::LINK_STATUS)
-                .map_err(|info| GLWError::new(GLWErrorKind::ShaderProgramLinkingFailed, info))?;
+    /// Attaches the shaders to a program loaded from a binary, which keeps
+    /// the ones without a file alive for reloads to relink, as linking does.
+    pub(crate) fn attach_to_binary(&self, shader_program_id: u32, owned_shaders: &[Shader]) {
+        self.shader_ids(owned_shaders)
+            .for_each(|shader_id| unsafe { gl::AttachShader(shader_program_id, shader_id) });
+    }
 
-            shader_program_id
+    pub(crate) fn finish(
+        self,
+        shader_program_id: u32,
+        prepared: &PreparedSources,
+        owned_shaders: Vec<Shader>,
+        link_options: LinkOptions,
+    ) -> Result<ShaderProgram, GLWError> {
+        let options = CompileOptions {
+            defines: self.defines.clone(),
+            glsl_target: self.glsl_target,
         };
+        let source_paths = self
+            .shaders
+            .iter()
+            .filter_map(|shader| {
+                Some(SourceFile {
+                    path: shader.path.clone()?,
+                    shader_type: shader.shader_type,
+                    options: shader.options.clone(),
+                })
+            })
+            .chain(
+                prepared
+                    .path_sources
+                    .iter()
+                    .map(|(source, shader_type)| SourceFile {
+                        path: source.files[0].clone(),
+                        shader_type: *shader_type,
+                        options: options.clone(),
+                    }),
+            )
+            .collect();
+        let include_paths = self
+            .shaders
+            .iter()
+            .flat_map(|shader| shader.includes.iter().cloned())
+            .chain(
+                prepared
+                    .path_sources
+                    .iter()
+                    .flat_map(|(source, _)| source.includes().to_vec()),
+            )
+            .collect();
+        // shaders stay alive while attached, even after their owner deletes
+        // them, so they can be attached again on reload
+        let retained: Vec<&Shader> = self
+            .shaders
+            .iter()
+            .copied()
+            .chain(owned_shaders.iter())
+            .filter(|shader| shader.path.is_none())
+            .collect();
+        let retained_shader_ids = retained.iter().map(|shader| shader.shader_id).collect();
+        let retained_includes = retained
+            .iter()
+            .flat_map(|shader| shader.includes.iter().cloned())
+            .collect();
+
+        let program = ShaderProgram {
+            shader_program_id,
+            generation: next_generation(),
+            source_paths,
+            retained_shader_ids,
+            retained_includes,
+            include_paths,
+            link_options,
+            watcher: None,
+            uniform_locations: RefCell::default(),
+            texture_units: RefCell::default(),
+        };
+        program.cache_uniform_locations();
+        if self.validate {
+            program.validate()?;
+        }
 
-        Ok(ShaderProgram { shader_program_id })
+        Ok(program)
     }
 }
 
+/// Reads and preprocesses a shader file, detecting its stage unless given.
+pub(crate) fn preprocess_path(
+    path: &Path,
+    shader_type: Option<ShaderType>,
+    defines: &[(String, String)],
+    glsl_target: Option<GlslTarget>,
+) -> Result<(Source, ShaderType), GLWError> {
+    let mut source = preprocessor::resolve_includes(path)?;
+    if let Some(target) = glsl_target {
+        source.code = preprocessor::retarget(&source.code, target);
+    }
+    source.code = preprocessor::inject_defines(&source.code, defines);
+    let shader_type = match shader_type {
+        Some(shader_type) => shader_type,
+        None => ShaderType::detect(path, &source.code)?,
+    };
+    Ok((source, shader_type))
+}
+
+/// Sources of the shaders a builder compiles itself, ready for compilation.
+pub(crate) struct PreparedSources {
+    path_sources: Vec<(Source, ShaderType)>,
+    embedded_sources: Vec<(String, ShaderType)>,
+}
+
+impl PreparedSources {
+    fn stages(&self) -> impl Iterator<Item = (ShaderType, &str)> {
+        self.path_sources
+            .iter()
+            .map(|(source, shader_type)| (*shader_type, source.code.as_str()))
+            .chain(
+                self.embedded_sources
+                    .iter()
+                    .map(|(source, shader_type)| (*shader_type, source.as_str())),
+            )
+    }
+
+    /// Starts compiling every shader, file backed ones first.
+    pub(crate) fn compile_unchecked(&self) -> Result<Vec<Shader>, GLWError> {
+        self.path_sources
+            .iter()
+            .map(|(source, shader_type)| Shader::compile_unchecked(source, *shader_type))
+            .chain(self.embedded_sources.iter().map(|(code, shader_type)| {
+                Shader::compile_unchecked(&Source::embedded(code), *shader_type)
+            }))
+            .collect()
+    }
+
+    /// Compiles the shaders without a backing file only, e.g. to retain
+    /// them for reloads of a program loaded from a binary.
+    pub(crate) fn compile_embedded(&self) -> Result<Vec<Shader>, GLWError> {
+        self.embedded_sources
+            .iter()
+            .map(|(code, shader_type)| {
+                Shader::from_preprocessed(&Source::embedded(code), *shader_type)
+            })
+            .collect()
+    }
+
+    /// Checks the result of [`compile_unchecked`](Self::compile_unchecked).
+    pub(crate) fn check_compiled(&self, shaders: &[Shader]) -> Result<(), GLWError> {
+        let embedded: Vec<Source> = self
+            .embedded_sources
+            .iter()
+            .map(|(code, _)| Source::embedded(code))
+            .collect();
+        let sources = self
+            .path_sources
+            .iter()
+            .map(|(source, _)| source)
+            .chain(embedded.iter());
+
+        shaders
+            .iter()
+            .zip(sources)
+            .try_for_each(|(shader, source)| shader.check_compiled(source))
+    }
+}
+
+/// Program state that has to be set before linking, kept around so
+/// reloads link the same way.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LinkOptions {
+    pub(crate) binary_retrievable: bool,
+    pub(crate) separable: bool,
+    pub(crate) transform_feedback: Option<(Vec<CString>, TransformFeedbackMode)>,
+}
+
+impl LinkOptions {
+    /// # Safety
+    /// shader_program_id should be valid
+    unsafe fn apply(&self, shader_program_id: u32) {
+        if self.binary_retrievable {
+            gl::ProgramParameteri(
+                shader_program_id,
+                gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                gl::TRUE as i32,
+            );
+        }
+        if self.separable {
+            gl::ProgramParameteri(shader_program_id, gl::PROGRAM_SEPARABLE, gl::TRUE as i32);
+        }
+        if let Some((varyings, mode)) = &self.transform_feedback {
+            let varyings: Vec<_> = varyings.iter().map(|varying| varying.as_ptr()).collect();
+            gl::TransformFeedbackVaryings(
+                shader_program_id,
+                varyings.len() as i32,
+                varyings.as_ptr(),
+                (*mode).into(),
+            );
+        }
+    }
+}
+
+/// Creates a program from already compiled shaders and links it, deleting
+/// the program again if linking fails.
+pub(crate) fn link_program(
+    shader_ids: impl Iterator<Item = u32>,
+    options: &LinkOptions,
+) -> Result<u32, GLWError> {
+    let _span = trace::span!("link_program");
+    unsafe { check_linked(link_unchecked(shader_ids, options)) }
+}
+
+/// Issues the link without waiting for its result.
+///
+/// # Safety
+/// shader_ids should be valid compiled shaders
+pub(crate) unsafe fn link_unchecked(
+    shader_ids: impl Iterator<Item = u32>,
+    options: &LinkOptions,
+) -> u32 {
+    let shader_program_id = gl::CreateProgram();
+    shader_ids.for_each(|shader_id| gl::AttachShader(shader_program_id, shader_id));
+    options.apply(shader_program_id);
+
+    gl::LinkProgram(shader_program_id);
+    trace::event!(TRACE, program_id = shader_program_id, "created program");
+    shader_program_id
+}
+
+/// # Safety
+/// shader_program_id should be a program created by [`link_unchecked`]
+pub(crate) unsafe fn check_linked(shader_program_id: u32) -> Result<u32, GLWError> {
+    let warnings =
+        utils::check_program_success(shader_program_id, gl::LINK_STATUS).map_err(|info| {
+            trace::event!(
+                ERROR,
+                program_id = shader_program_id,
+                "program linking failed: {info}"
+            );
+            gl::DeleteProgram(shader_program_id);
+            GLWError::new(GLWErrorKind::ShaderProgramLinkingFailed, info)
+        })?;
+    if let Some(_warnings) = warnings {
+        trace::event!(
+            WARN,
+            program_id = shader_program_id,
+            "program linked with: {_warnings}"
+        );
+    }
+
+    Ok(shader_program_id)
+}
+
 impl Default for ShaderProgramBuilder<'_> {
     fn default() -> Self {
         Self::new()
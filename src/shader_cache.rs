@@ -0,0 +1,130 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    errors::GLWError,
+    preprocessor::{self, GlslTarget, Source},
+    shader::{self, CompileOptions, Shader, ShaderType},
+};
+
+/// File, stage and preprocessed source of a cached shader.
+type CacheKey = (Option<PathBuf>, ShaderType, String);
+
+/// Compiled shaders keyed by their file, preprocessed source and stage, so
+/// shaders shared between programs are only compiled once.
+///
+/// Handles are attached with
+/// [`ShaderProgramBuilder::attach_shader`](crate::shader::ShaderProgramBuilder::attach_shader).
+/// File backed shaders keep their path, so programs built from them still
+/// hot reload.
+#[derive(Default)]
+pub struct ShaderCache {
+    shaders: RefCell<HashMap<CacheKey, Rc<Shader>>>,
+    glsl_target: Option<GlslTarget>,
+}
+
+impl ShaderCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rewrites every shader compiled by the cache for `target`, see
+    /// [`preprocessor::retarget`].
+    pub fn with_glsl_target(target: GlslTarget) -> Self {
+        Self {
+            glsl_target: Some(target),
+            ..Self::default()
+        }
+    }
+
+    /// Compiles the file at `path`, or returns the shader compiled earlier
+    /// from the same source. The stage is detected unless `shader_type` is
+    /// given.
+    pub fn get(
+        &self,
+        path: impl AsRef<Path>,
+        shader_type: impl Into<Option<ShaderType>>,
+    ) -> Result<Rc<Shader>, GLWError> {
+        self.get_with_defines(path, shader_type, &[])
+    }
+
+    /// Like [`get`](Self::get), with `#define name value` lines added.
+    /// Different defines compile to different shaders.
+    pub fn get_with_defines(
+        &self,
+        path: impl AsRef<Path>,
+        shader_type: impl Into<Option<ShaderType>>,
+        defines: &[(String, String)],
+    ) -> Result<Rc<Shader>, GLWError> {
+        let (source, shader_type) =
+            shader::preprocess_path(path.as_ref(), shader_type.into(), defines, self.glsl_target)?;
+        let options = CompileOptions {
+            defines: defines.to_vec(),
+            glsl_target: self.glsl_target,
+        };
+        self.get_or_compile(&source, shader_type, options)
+    }
+
+    pub fn get_source(
+        &self,
+        source: &str,
+        shader_type: ShaderType,
+    ) -> Result<Rc<Shader>, GLWError> {
+        let code = match self.glsl_target {
+            Some(target) => preprocessor::retarget(source, target),
+            None => source.to_string(),
+        };
+        self.get_or_compile(
+            &Source::embedded(&code),
+            shader_type,
+            CompileOptions::default(),
+        )
+    }
+
+    fn get_or_compile(
+        &self,
+        source: &Source,
+        shader_type: ShaderType,
+        options: CompileOptions,
+    ) -> Result<Rc<Shader>, GLWError> {
+        // files with the same contents get a shader each, to be watched
+        let key = (
+            source.files.first().cloned(),
+            shader_type,
+            source.code.clone(),
+        );
+        if let Some(shader) = self.shaders.borrow().get(&key) {
+            return Ok(shader.clone());
+        }
+
+        let mut shader = Shader::from_preprocessed(source, shader_type)?;
+        shader.options = options;
+        let shader = Rc::new(shader);
+        self.shaders.borrow_mut().insert(key, shader.clone());
+        Ok(shader)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shaders.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shaders.borrow().is_empty()
+    }
+
+    /// Drops the shaders no handle outside the cache refers to anymore.
+    /// Programs they are attached to keep working.
+    pub fn purge_unused(&self) {
+        self.shaders
+            .borrow_mut()
+            .retain(|_, shader| Rc::strong_count(shader) > 1);
+    }
+
+    pub fn clear(&self) {
+        self.shaders.borrow_mut().clear();
+    }
+}
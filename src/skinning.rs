@@ -0,0 +1,255 @@
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    shader::ShaderProgram,
+    transform::Transform,
+    uniform_buffer::UniformBuffer,
+};
+
+/// Joints a [`JointPalette`] holds, 8 KiB of matrices, half the smallest
+/// uniform block size GL allows.
+pub const MAX_JOINTS: usize = 128;
+
+/// Name of the uniform block [`SKINNING_GLSL`] declares.
+pub const JOINTS_BLOCK: &str = "Joints";
+
+/// Vertex shader code declaring the [`JointPalette`] block and
+/// `skinMatrix`, blending the joint matrices of a vertex by their weights.
+/// Goes after the `#version` line:
+/// ```glsl
+/// layout (location = 4) in uvec4 aJoints;
+/// layout (location = 5) in vec4 aWeights;
+///
+/// void main() {
+///     mat4 skin = skinMatrix(aJoints, aWeights);
+///     gl_Position = projection * view * model * skin * vec4(aPos, 1.0);
+/// }
+/// ```
+pub const SKINNING_GLSL: &str = r#"#define MAX_JOINTS 128
+layout (std140) uniform Joints {
+    mat4 joints[MAX_JOINTS];
+};
+
+mat4 skinMatrix(uvec4 joint, vec4 weight) {
+    return weight.x * joints[joint.x]
+        + weight.y * joints[joint.y]
+        + weight.z * joints[joint.z]
+        + weight.w * joints[joint.w];
+}
+"#;
+
+/// Bone of a [`Skeleton`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Joint {
+    pub name: Option<String>,
+    /// Index of the parent joint.
+    pub parent: Option<usize>,
+    /// Rest transform, relative to the parent joint.
+    pub bind_pose: Transform,
+    /// Takes vertices from model space to the joint's space at rest.
+    pub inverse_bind: Matrix4<f32>,
+}
+
+/// Joint hierarchy skinned vertices are weighted to.
+///
+/// Poses are the local transforms of every joint, indexed like
+/// [`joints`](Self::joints), starting from [`bind_pose`](Self::bind_pose).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    /// Fails with [`InvalidJointParent`](GLWErrorKind::InvalidJointParent)
+    /// if a joint's parent is out of range or the parents form a cycle.
+    pub fn new(joints: Vec<Joint>) -> Result<Self, GLWError> {
+        for index in 0..joints.len() {
+            let mut ancestor = joints[index].parent;
+            for _ in 0..joints.len() {
+                match ancestor {
+                    Some(parent) if parent < joints.len() => ancestor = joints[parent].parent,
+                    _ => break,
+                }
+            }
+            if ancestor.is_some() {
+                Err(GLWErrorKind::InvalidJointParent(index))?;
+            }
+        }
+        Ok(Self { joints })
+    }
+
+    /// Skeleton whose inverse bind matrices are worked out from the bind
+    /// poses.
+    pub fn from_bind_pose(joints: Vec<Joint>) -> Result<Self, GLWError> {
+        let mut skeleton = Self::new(joints)?;
+        let world = skeleton.world_matrices(&skeleton.bind_pose());
+        for (joint, world) in skeleton.joints.iter_mut().zip(world) {
+            joint.inverse_bind = world.invert().unwrap_or(Matrix4::identity());
+        }
+        Ok(skeleton)
+    }
+
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    pub fn len(&self) -> usize {
+        self.joints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.joints.is_empty()
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.joints
+            .iter()
+            .position(|joint| joint.name.as_deref() == Some(name))
+    }
+
+    pub fn bind_pose(&self) -> Vec<Transform> {
+        self.joints.iter().map(|joint| joint.bind_pose).collect()
+    }
+
+    /// Model space matrix of every joint in `pose`. Joints missing from
+    /// `pose` keep their bind pose.
+    pub fn world_matrices(&self, pose: &[Transform]) -> Vec<Matrix4<f32>> {
+        let local = |index: usize| {
+            let joint = &self.joints[index];
+            pose.get(index).unwrap_or(&joint.bind_pose).matrix()
+        };
+        let mut world: Vec<Option<Matrix4<f32>>> = vec![None; self.joints.len()];
+        for index in 0..self.joints.len() {
+            // walks up to the nearest joint already placed, then back down
+            let mut chain = vec![index];
+            while let Some(parent) = self.joints[*chain.last().unwrap()].parent {
+                if world[parent].is_some() {
+                    break;
+                }
+                chain.push(parent);
+            }
+            for &joint in chain.iter().rev() {
+                if world[joint].is_some() {
+                    continue;
+                }
+                world[joint] = Some(match self.joints[joint].parent {
+                    Some(parent) => world[parent].unwrap() * local(joint),
+                    None => local(joint),
+                });
+            }
+        }
+        world.into_iter().flatten().collect()
+    }
+
+    /// Joint matrices skinning vertices into `pose`, for a
+    /// [`JointPalette`].
+    pub fn palette(&self, pose: &[Transform]) -> Vec<Matrix4<f32>> {
+        self.world_matrices(pose)
+            .into_iter()
+            .zip(&self.joints)
+            .map(|(world, joint)| world * joint.inverse_bind)
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct JointsBlock {
+    joints: [[[f32; 4]; 4]; MAX_JOINTS],
+}
+
+/// Uniform buffer of joint matrices, read by the [`SKINNING_GLSL`] block.
+pub struct JointPalette {
+    buffer: UniformBuffer<JointsBlock>,
+    block: JointsBlock,
+}
+
+impl JointPalette {
+    /// Palette of identity matrices, leaving vertices as they are.
+    pub fn new() -> Self {
+        let block = JointsBlock {
+            joints: [Matrix4::identity().into(); MAX_JOINTS],
+        };
+        Self {
+            buffer: UniformBuffer::new(&block),
+            block,
+        }
+    }
+
+    /// Uploads `matrices`, e.g. from [`Skeleton::palette`]. Fails with
+    /// [`TooManyJoints`](GLWErrorKind::TooManyJoints) for more than
+    /// [`MAX_JOINTS`].
+    pub fn update(&mut self, matrices: &[Matrix4<f32>]) -> Result<(), GLWError> {
+        if matrices.len() > MAX_JOINTS {
+            Err(GLWErrorKind::TooManyJoints {
+                count: matrices.len(),
+                max: MAX_JOINTS,
+            })?;
+        }
+        for (joint, matrix) in self.block.joints.iter_mut().zip(matrices) {
+            *joint = (*matrix).into();
+        }
+        self.buffer.update(&self.block);
+        Ok(())
+    }
+
+    pub fn bind_base(&self, binding_point: u32) {
+        self.buffer.bind_base(binding_point);
+    }
+
+    /// Binds the palette to `binding_point` and `program`'s
+    /// [`JOINTS_BLOCK`] to it.
+    pub fn bind(&self, program: &ShaderProgram, binding_point: u32) -> Result<(), GLWError> {
+        program.bind_uniform_block(JOINTS_BLOCK, binding_point)?;
+        self.bind_base(binding_point);
+        Ok(())
+    }
+}
+
+impl Default for JointPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Vector3;
+
+    use super::*;
+
+    fn joint(parent: Option<usize>) -> Joint {
+        Joint {
+            name: None,
+            parent,
+            bind_pose: Transform::from_translation(Vector3::unit_y()),
+            inverse_bind: Matrix4::identity(),
+        }
+    }
+
+    #[test]
+    fn rejects_bad_parents() {
+        for (parents, bad) in [
+            (vec![None, Some(2)], 1),
+            (vec![Some(0)], 0),
+            (vec![None, Some(2), Some(1)], 1),
+        ] {
+            let joints = parents.into_iter().map(joint).collect();
+            let err = Skeleton::new(joints).unwrap_err();
+            assert!(matches!(err.kind(), GLWErrorKind::InvalidJointParent(index) if *index == bad));
+        }
+    }
+
+    #[test]
+    fn binds_at_rest() {
+        let joints = [None, Some(0), Some(1), Some(0)];
+        let skeleton = Skeleton::from_bind_pose(joints.into_iter().map(joint).collect()).unwrap();
+        let heights = skeleton.world_matrices(&skeleton.bind_pose());
+        let heights: Vec<f32> = heights.iter().map(|world| world.w.y).collect();
+        assert_eq!(heights, [1.0, 2.0, 3.0, 2.0]);
+        for matrix in skeleton.palette(&skeleton.bind_pose()) {
+            assert_eq!(matrix, Matrix4::identity());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use cgmath::{Matrix3, Matrix4};
+
+use crate::{
+    context::DepthMode,
+    cubemap::Cubemap,
+    errors::GLWError,
+    mesh::Mesh,
+    shader::{ShaderProgram, ShaderType},
+};
+
+const VERTEX_SHADER: &str = r#"#version 330 core
+layout (location = 0) in vec3 aPos;
+
+out vec3 TexCoords;
+
+uniform mat4 projection;
+uniform mat4 view;
+// normalized depth of the far plane, 0 with reverse-Z
+uniform float farDepth;
+
+void main()
+{
+    TexCoords = aPos;
+    vec4 pos = projection * view * vec4(aPos, 1.0);
+    // at the far plane after the perspective divide, behind everything else
+    gl_Position = vec4(pos.xy, pos.w * farDepth, pos.w);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec3 TexCoords;
+
+out vec4 FragColor;
+
+uniform samplerCube skybox;
+
+void main()
+{
+    FragColor = texture(skybox, TexCoords);
+}
+"#;
+
+#[rustfmt::skip]
+const CUBE: [[f32; 3]; 36] = [
+    [-1.0,  1.0, -1.0], [-1.0, -1.0, -1.0], [ 1.0, -1.0, -1.0],
+    [ 1.0, -1.0, -1.0], [ 1.0,  1.0, -1.0], [-1.0,  1.0, -1.0],
+
+    [-1.0, -1.0,  1.0], [-1.0, -1.0, -1.0], [-1.0,  1.0, -1.0],
+    [-1.0,  1.0, -1.0], [-1.0,  1.0,  1.0], [-1.0, -1.0,  1.0],
+
+    [ 1.0, -1.0, -1.0], [ 1.0, -1.0,  1.0], [ 1.0,  1.0,  1.0],
+    [ 1.0,  1.0,  1.0], [ 1.0,  1.0, -1.0], [ 1.0, -1.0, -1.0],
+
+    [-1.0, -1.0,  1.0], [-1.0,  1.0,  1.0], [ 1.0,  1.0,  1.0],
+    [ 1.0,  1.0,  1.0], [ 1.0, -1.0,  1.0], [-1.0, -1.0,  1.0],
+
+    [-1.0,  1.0, -1.0], [ 1.0,  1.0, -1.0], [ 1.0,  1.0,  1.0],
+    [ 1.0,  1.0,  1.0], [-1.0,  1.0,  1.0], [-1.0,  1.0, -1.0],
+
+    [-1.0, -1.0, -1.0], [-1.0, -1.0,  1.0], [ 1.0, -1.0, -1.0],
+    [ 1.0, -1.0, -1.0], [-1.0, -1.0,  1.0], [ 1.0, -1.0,  1.0],
+];
+
+/// Draws a cubemap around the camera, behind all other geometry.
+pub struct Skybox {
+    cubemap: Cubemap,
+    cube: Mesh<[f32; 3]>,
+    program: ShaderProgram,
+}
+
+impl Skybox {
+    pub fn new(cubemap: Cubemap) -> Result<Self, GLWError> {
+        let program = ShaderProgram::builder()
+            .attach_shader_source(VERTEX_SHADER, ShaderType::VertexShader)
+            .attach_shader_source(FRAGMENT_SHADER, ShaderType::FragmentShader)
+            .build()?;
+        program.set_i32("skybox", 0)?;
+
+        Ok(Self {
+            cubemap,
+            cube: Mesh::from_vertices(&CUBE, None),
+            program,
+        })
+    }
+
+    /// Draws the skybox, best done after the opaque geometry so hidden
+    /// fragments are skipped by the depth test. The translation of `view` is
+    /// dropped so the skybox stays centered on the camera.
+    pub fn draw(&self, view: Matrix4<f32>, projection: Matrix4<f32>) -> Result<(), GLWError> {
+        let rotation = Matrix4::from(Matrix3::from_cols(
+            view.x.truncate(),
+            view.y.truncate(),
+            view.z.truncate(),
+        ));
+        self.program.set_mat4("view", rotation)?;
+        self.program.set_mat4("projection", projection)?;
+        let depth_mode = DepthMode::current();
+        self.program
+            .set_f32("farDepth", depth_mode.clear_depth() as f32)?;
+
+        unsafe {
+            // the skybox sits exactly at the far plane, which a strict test
+            // rejects against the cleared depth buffer
+            gl::DepthFunc(match depth_mode {
+                DepthMode::Standard => gl::LEQUAL,
+                DepthMode::ReverseZ => gl::GEQUAL,
+            });
+        }
+        self.cubemap.bind(0);
+        self.cube.draw();
+        unsafe {
+            gl::DepthFunc(depth_mode.depth_func());
+        }
+        Ok(())
+    }
+
+    pub fn cubemap(&self) -> &Cubemap {
+        &self.cubemap
+    }
+}
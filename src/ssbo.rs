@@ -0,0 +1,112 @@
+use std::ffi::CString;
+
+use crate::{
+    buffer::{Buffer, BufferTarget, BufferUsage},
+    compute::{self, MemoryBarrier},
+    errors::{GLWError, GLWErrorKind},
+    shader::ShaderProgram,
+};
+
+impl ShaderProgram {
+    pub fn shader_storage_block_index(&self, name: impl AsRef<str>) -> Result<u32, GLWError> {
+        let c_name = CString::new(name.as_ref())?;
+
+        let index = unsafe {
+            gl::GetProgramResourceIndex(
+                self.shader_program_id,
+                gl::SHADER_STORAGE_BLOCK,
+                c_name.as_ptr(),
+            )
+        };
+        if index == gl::INVALID_INDEX {
+            Err(GLWErrorKind::ShaderStorageBlockNotFound(
+                name.as_ref().to_string(),
+            ))?;
+        }
+
+        Ok(index)
+    }
+
+    /// Makes the buffer block `name` use the buffer bound to `binding_point`,
+    /// see [`SsboBuffer::bind_base`].
+    pub fn bind_shader_storage_block(
+        &self,
+        name: impl AsRef<str>,
+        binding_point: u32,
+    ) -> Result<(), GLWError> {
+        let index = self.shader_storage_block_index(name)?;
+        unsafe {
+            gl::ShaderStorageBlockBinding(self.shader_program_id, index, binding_point);
+        }
+        Ok(())
+    }
+}
+
+/// Shader storage buffer holding `len` values of `T`. `T` has to match the
+/// std430 layout of the block's array.
+pub struct SsboBuffer<T> {
+    buffer: Buffer<T>,
+}
+
+impl<T: Copy> SsboBuffer<T> {
+    pub fn new(data: &[T]) -> Self {
+        Self {
+            buffer: Buffer::new(BufferTarget::ShaderStorage, data, BufferUsage::DynamicCopy),
+        }
+    }
+
+    /// Buffer of `len` elements with undefined contents, e.g. for compute output.
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            buffer: Buffer::with_len(BufferTarget::ShaderStorage, len, BufferUsage::DynamicCopy),
+        }
+    }
+
+    /// Writes `data` at element `offset` through a mapping, see
+    /// [`Buffer::write`].
+    pub fn write(&self, offset: usize, data: &[T]) -> Result<(), GLWError> {
+        self.buffer.write(offset, data)
+    }
+
+    /// Reads the whole buffer back, after waiting for shader writes to land.
+    pub fn read(&self) -> Result<Vec<T>, GLWError> {
+        // mapping nothing is an error in GL
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        compute::memory_barrier(MemoryBarrier::BUFFER_UPDATE);
+        let mapped = self.buffer.map_read()?;
+        let data = mapped.to_vec();
+        // the data store got corrupted while mapped if this fails, so what
+        // was read can't be trusted either
+        mapped.unmap()?;
+        Ok(data)
+    }
+}
+
+impl<T> SsboBuffer<T> {
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn buffer(&self) -> &Buffer<T> {
+        &self.buffer
+    }
+
+    pub fn id(&self) -> u32 {
+        self.buffer.id()
+    }
+
+    pub fn bind_base(&self, binding_point: u32) {
+        self.buffer.bind_base(binding_point);
+    }
+
+    /// Makes shader writes to storage buffers visible to later shader reads.
+    pub fn barrier() {
+        compute::memory_barrier(MemoryBarrier::SHADER_STORAGE);
+    }
+}
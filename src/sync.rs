@@ -0,0 +1,58 @@
+use gl::types::GLsync;
+
+use crate::errors::{GLWError, GLWErrorKind};
+
+/// Fence signaled once the GPU has finished every command issued before it
+/// was created.
+pub struct Fence {
+    sync: GLsync,
+}
+
+impl Fence {
+    pub fn new() -> Self {
+        let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+        Self { sync }
+    }
+
+    pub fn is_signaled(&self) -> bool {
+        let mut status = 0;
+        unsafe {
+            gl::GetSynciv(
+                self.sync,
+                gl::SYNC_STATUS,
+                1,
+                std::ptr::null_mut(),
+                &mut status,
+            );
+        }
+        status == gl::SIGNALED as i32
+    }
+
+    /// Blocks until the fence is signaled.
+    pub fn wait(&self) -> Result<(), GLWError> {
+        // only the first wait needs to flush, later ones would just add
+        // round trips
+        let mut flags = gl::SYNC_FLUSH_COMMANDS_BIT;
+        loop {
+            match unsafe { gl::ClientWaitSync(self.sync, flags, 1_000_000) } {
+                gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => return Ok(()),
+                gl::WAIT_FAILED => Err(GLWErrorKind::FenceWaitFailed)?,
+                _ => flags = 0,
+            }
+        }
+    }
+}
+
+impl Default for Fence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSync(self.sync);
+        }
+    }
+}
@@ -0,0 +1,709 @@
+use std::path::Path;
+
+use image::{DynamicImage, GenericImage};
+
+use crate::{
+    compressed_texture::CompressedFormat,
+    context::GlApi,
+    errors::{GLWError, GLWErrorKind},
+    label, trace, utils,
+};
+
+// EXT_texture_filter_anisotropic, core in GL 4.6
+const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
+
+/// Highest anisotropic filtering level, or `None` without
+/// `EXT_texture_filter_anisotropic`.
+pub fn max_anisotropy() -> Option<f32> {
+    if !utils::has_extension("GL_EXT_texture_filter_anisotropic")
+        && !utils::has_extension("GL_ARB_texture_filter_anisotropic")
+    {
+        return None;
+    }
+    let mut max = 1.0;
+    unsafe {
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+    }
+    Some(max)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Samples outside the texture return the border color. Clamps to the
+    /// edge instead on GLES without `OES_texture_border_clamp`.
+    ClampToBorder,
+}
+
+impl From<WrapMode> for i32 {
+    fn from(val: WrapMode) -> Self {
+        (match val {
+            WrapMode::Repeat => gl::REPEAT,
+            WrapMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+            WrapMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+            WrapMode::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }) as i32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// Minification filter, the mipmap variants pick (`Nearest`) or blend
+/// (`Linear`) between mipmap levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinFilter {
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+impl From<Filter> for i32 {
+    fn from(val: Filter) -> Self {
+        (match val {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }) as i32
+    }
+}
+
+impl From<MinFilter> for i32 {
+    fn from(val: MinFilter) -> Self {
+        (match val {
+            MinFilter::Nearest => gl::NEAREST,
+            MinFilter::Linear => gl::LINEAR,
+            MinFilter::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+            MinFilter::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+            MinFilter::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+            MinFilter::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
+        }) as i32
+    }
+}
+
+/// Comparison of depth texture samples against a reference value, the
+/// result a `sampler2DShadow` returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareFunc {
+    Never,
+    Less,
+    LessEqual,
+    Equal,
+    Greater,
+    GreaterEqual,
+    NotEqual,
+    Always,
+}
+
+impl From<CompareFunc> for i32 {
+    fn from(val: CompareFunc) -> Self {
+        (match val {
+            CompareFunc::Never => gl::NEVER,
+            CompareFunc::Less => gl::LESS,
+            CompareFunc::LessEqual => gl::LEQUAL,
+            CompareFunc::Equal => gl::EQUAL,
+            CompareFunc::Greater => gl::GREATER,
+            CompareFunc::GreaterEqual => gl::GEQUAL,
+            CompareFunc::NotEqual => gl::NOTEQUAL,
+            CompareFunc::Always => gl::ALWAYS,
+        }) as i32
+    }
+}
+
+impl MinFilter {
+    fn uses_mipmaps(self) -> bool {
+        !matches!(self, Self::Nearest | Self::Linear)
+    }
+}
+
+/// Sized internal format textures are stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    /// sRGB encoded color, converted to linear when sampled.
+    Srgb8,
+    /// sRGB encoded color with linear alpha.
+    Srgb8Alpha8,
+    R16F,
+    Rgb16F,
+    Rgba16F,
+    R32F,
+    Rgb32F,
+    Rgba32F,
+    Depth24,
+    Depth32F,
+    Depth24Stencil8,
+    /// Block compressed data, only created from the data itself, see
+    /// [`CompressedImage`](crate::compressed_texture::CompressedImage).
+    Compressed {
+        format: CompressedFormat,
+        srgb: bool,
+    },
+}
+
+impl From<TextureFormat> for u32 {
+    fn from(val: TextureFormat) -> Self {
+        match val {
+            TextureFormat::R8 => gl::R8,
+            TextureFormat::Rg8 => gl::RG8,
+            TextureFormat::Rgb8 => gl::RGB8,
+            TextureFormat::Rgba8 => gl::RGBA8,
+            TextureFormat::Srgb8 => gl::SRGB8,
+            TextureFormat::Srgb8Alpha8 => gl::SRGB8_ALPHA8,
+            TextureFormat::R16F => gl::R16F,
+            TextureFormat::Rgb16F => gl::RGB16F,
+            TextureFormat::Rgba16F => gl::RGBA16F,
+            TextureFormat::R32F => gl::R32F,
+            TextureFormat::Rgb32F => gl::RGB32F,
+            TextureFormat::Rgba32F => gl::RGBA32F,
+            TextureFormat::Depth24 => gl::DEPTH_COMPONENT24,
+            TextureFormat::Depth32F => gl::DEPTH_COMPONENT32F,
+            TextureFormat::Depth24Stencil8 => gl::DEPTH24_STENCIL8,
+            TextureFormat::Compressed { format, srgb } => format.internal_format(srgb),
+        }
+    }
+}
+
+impl TextureFormat {
+    /// Pixel format and type of data matching the internal format, used
+    /// when allocating storage without data.
+    ///
+    /// # Panics
+    /// For compressed formats, which can't be allocated that way.
+    pub(crate) fn pixel_format(self) -> (u32, u32) {
+        match self {
+            Self::R8 => (gl::RED, gl::UNSIGNED_BYTE),
+            Self::Rg8 => (gl::RG, gl::UNSIGNED_BYTE),
+            Self::Rgb8 | Self::Srgb8 => (gl::RGB, gl::UNSIGNED_BYTE),
+            Self::Rgba8 | Self::Srgb8Alpha8 => (gl::RGBA, gl::UNSIGNED_BYTE),
+            Self::R16F | Self::R32F => (gl::RED, gl::FLOAT),
+            Self::Rgb16F | Self::Rgb32F => (gl::RGB, gl::FLOAT),
+            Self::Rgba16F | Self::Rgba32F => (gl::RGBA, gl::FLOAT),
+            Self::Depth24 | Self::Depth32F => (gl::DEPTH_COMPONENT, gl::FLOAT),
+            Self::Depth24Stencil8 => (gl::DEPTH_STENCIL, gl::UNSIGNED_INT_24_8),
+            Self::Compressed { .. } => {
+                panic!("compressed textures need their data to be allocated")
+            }
+        }
+    }
+
+    /// Whether the format holds depth (and possibly stencil) data.
+    pub fn is_depth(self) -> bool {
+        matches!(self, Self::Depth24 | Self::Depth32F | Self::Depth24Stencil8)
+    }
+
+    /// Size in bytes of one pixel of data in [`pixel_format`](Self::pixel_format).
+    pub(crate) fn pixel_size(self) -> usize {
+        let (format, ty) = self.pixel_format();
+        let channels = match format {
+            gl::RED | gl::DEPTH_COMPONENT => 1,
+            gl::RG => 2,
+            gl::RGB => 3,
+            // packed into a single u32
+            gl::DEPTH_STENCIL => return 4,
+            _ => 4,
+        };
+        let channel_size = match ty {
+            gl::UNSIGNED_BYTE => 1,
+            _ => 4,
+        };
+        channels * channel_size
+    }
+}
+
+/// Whether 8 bit color data is sRGB encoded, as color textures authored in
+/// image editors are, or linear, as data like normals or roughness is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+impl TextureFormat {
+    /// The sRGB or linear equivalent of an 8 bit color format, other formats
+    /// are returned as is.
+    pub fn in_color_space(self, color_space: ColorSpace) -> Self {
+        match (self, color_space) {
+            (Self::Rgb8, ColorSpace::Srgb) => Self::Srgb8,
+            (Self::Rgba8, ColorSpace::Srgb) => Self::Srgb8Alpha8,
+            (Self::Srgb8, ColorSpace::Linear) => Self::Rgb8,
+            (Self::Srgb8Alpha8, ColorSpace::Linear) => Self::Rgba8,
+            (Self::Compressed { format, .. }, color_space) => Self::Compressed {
+                format,
+                srgb: color_space == ColorSpace::Srgb && format.has_srgb_variant(),
+            },
+            (format, _) => format,
+        }
+    }
+}
+
+/// Makes writes to sRGB framebuffers (including the default one, if the
+/// window was created with an sRGB capable surface) convert linear shader
+/// output to sRGB. GLES always converts and lacks the switch.
+pub fn set_framebuffer_srgb(enabled: bool) {
+    if GlApi::current() == GlApi::OpenGlEs {
+        return;
+    }
+    unsafe {
+        if enabled {
+            gl::Enable(gl::FRAMEBUFFER_SRGB);
+        } else {
+            gl::Disable(gl::FRAMEBUFFER_SRGB);
+        }
+    }
+}
+
+/// Whether border colors can be set, which GLES needs
+/// `OES_texture_border_clamp` or `EXT_texture_border_clamp` for.
+fn border_clamp_supported() -> bool {
+    GlApi::current() == GlApi::OpenGl
+        || utils::has_extension("GL_OES_texture_border_clamp")
+        || utils::has_extension("GL_EXT_texture_border_clamp")
+}
+
+/// Sampling parameters shared by every texture type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureParams {
+    pub wrap: [WrapMode; 3],
+    pub border_color: [f32; 4],
+    pub min_filter: MinFilter,
+    pub mag_filter: Filter,
+    pub mipmaps: bool,
+    /// Depth comparison for shadow samplers, only valid on depth textures.
+    pub compare: Option<CompareFunc>,
+    /// Anisotropic filtering level, 1 for none. Clamped to what the driver
+    /// supports.
+    pub anisotropy: f32,
+}
+
+impl Default for TextureParams {
+    fn default() -> Self {
+        Self {
+            wrap: [WrapMode::Repeat; 3],
+            border_color: [0.0; 4],
+            min_filter: MinFilter::LinearMipmapLinear,
+            mag_filter: Filter::Linear,
+            mipmaps: true,
+            compare: None,
+            anisotropy: 1.0,
+        }
+    }
+}
+
+impl TextureParams {
+    /// Sets the parameters of the texture bound to `target`. Mipmaps are
+    /// generated here, so the base level must already be uploaded.
+    pub(crate) fn apply(&self, target: u32) {
+        // a mipmap filter without mipmaps makes the texture incomplete
+        let min_filter = match (self.mipmaps, self.min_filter.uses_mipmaps()) {
+            (false, true) => Filter::Linear.into(),
+            _ => self.min_filter.into(),
+        };
+
+        let border_clamp = border_clamp_supported();
+
+        unsafe {
+            for (param, wrap) in [gl::TEXTURE_WRAP_S, gl::TEXTURE_WRAP_T, gl::TEXTURE_WRAP_R]
+                .into_iter()
+                .zip(self.wrap)
+            {
+                let wrap = match wrap {
+                    WrapMode::ClampToBorder if !border_clamp => WrapMode::ClampToEdge,
+                    wrap => wrap,
+                };
+                gl::TexParameteri(target, param, wrap.into());
+            }
+            if border_clamp {
+                gl::TexParameterfv(target, gl::TEXTURE_BORDER_COLOR, self.border_color.as_ptr());
+            }
+            gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, min_filter);
+            gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, self.mag_filter.into());
+            match self.compare {
+                Some(func) => {
+                    gl::TexParameteri(
+                        target,
+                        gl::TEXTURE_COMPARE_MODE,
+                        gl::COMPARE_REF_TO_TEXTURE as i32,
+                    );
+                    gl::TexParameteri(target, gl::TEXTURE_COMPARE_FUNC, func.into());
+                }
+                None => gl::TexParameteri(target, gl::TEXTURE_COMPARE_MODE, gl::NONE as i32),
+            }
+            if self.anisotropy > 1.0 {
+                if let Some(max) = max_anisotropy() {
+                    gl::TexParameterf(target, TEXTURE_MAX_ANISOTROPY, self.anisotropy.min(max));
+                }
+            }
+            if self.mipmaps {
+                gl::GenerateMipmap(target);
+            }
+        }
+    }
+}
+
+/// Decodes the image at `path`.
+pub(crate) fn load_image(path: &Path) -> Result<DynamicImage, GLWError> {
+    Ok(
+        image::open(path).map_err(|source| GLWErrorKind::TextureLoadFailed {
+            path: path.to_path_buf(),
+            source,
+        })?,
+    )
+}
+
+/// Pixels decoded from an image file, ready to upload.
+pub(crate) struct ImageData {
+    pub width: u32,
+    pub height: u32,
+    pub format: u32,
+    pub internal_format: TextureFormat,
+    pub pixels: Vec<u8>,
+}
+
+impl ImageData {
+    /// Loads the image at `path`, flipped so the first row is the bottom one
+    /// as GL expects. Color images get an sRGB internal format unless
+    /// `color_space` is linear, single channel images are always linear.
+    pub fn load(path: &Path, flip: bool, color_space: ColorSpace) -> Result<Self, GLWError> {
+        let image = load_image(path)?;
+        let image = if flip { image.flipv() } else { image };
+        let (width, height) = image.dimensions();
+
+        let (format, internal_format, pixels) = match image {
+            DynamicImage::ImageLuma8(image) => (gl::RED, TextureFormat::R8, image.into_raw()),
+            DynamicImage::ImageRgb8(image) => (gl::RGB, TextureFormat::Rgb8, image.into_raw()),
+            image => (gl::RGBA, TextureFormat::Rgba8, image.to_rgba().into_raw()),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            format,
+            internal_format: internal_format.in_color_space(color_space),
+            pixels,
+        })
+    }
+}
+
+/// Uploads tightly packed rows, which RGB and single channel images
+/// usually aren't aligned to the default of 4 bytes.
+pub(crate) fn with_unpack_alignment_1(upload: impl FnOnce()) {
+    unsafe {
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    }
+    upload();
+    unsafe {
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+    }
+}
+
+/// Binds texture `texture_id` to `target` of texture unit `unit`.
+pub(crate) fn bind_to_unit(target: u32, texture_id: u32, unit: u32) {
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0 + unit);
+        gl::BindTexture(target, texture_id);
+    }
+}
+
+pub struct Texture2D {
+    pub(crate) texture_id: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) format: TextureFormat,
+}
+
+impl Texture2D {
+    pub fn builder() -> Texture2DBuilder {
+        Texture2DBuilder::new()
+    }
+
+    /// Loads the color (albedo) image at `path` as sRGB, with the default
+    /// parameters: repeating, trilinear filtering with mipmaps.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        Self::builder().build_from_path(path)
+    }
+
+    /// Like [`from_path`](Self::from_path) for data images, e.g. normal or
+    /// roughness maps, which are stored linear.
+    pub fn from_path_linear(path: impl AsRef<Path>) -> Result<Self, GLWError> {
+        Self::builder()
+            .color_space(ColorSpace::Linear)
+            .build_from_path(path)
+    }
+
+    /// Depth (`Depth24`, `Depth32F`) or depth-stencil (`Depth24Stencil8`)
+    /// texture to attach to a framebuffer.
+    ///
+    /// # Panics
+    /// If `format` isn't a depth format.
+    pub fn depth(width: u32, height: u32, format: TextureFormat) -> Self {
+        assert!(format.is_depth(), "{format:?} is not a depth format");
+        Self::builder()
+            .internal_format(format)
+            .wrap(WrapMode::ClampToEdge)
+            .min_filter(MinFilter::Nearest)
+            .mag_filter(Filter::Nearest)
+            .mipmaps(false)
+            .build_empty(width, height)
+    }
+
+    /// Depth texture sampled with a `sampler2DShadow`. Lookups return the
+    /// (bilinearly filtered) result of `reference <= depth`, and are lit
+    /// outside the map.
+    pub fn shadow_map(width: u32, height: u32) -> Self {
+        Self::builder()
+            .internal_format(TextureFormat::Depth24)
+            .border_color([1.0; 4])
+            .min_filter(MinFilter::Linear)
+            .mag_filter(Filter::Linear)
+            .mipmaps(false)
+            .compare(CompareFunc::LessEqual)
+            .build_empty(width, height)
+    }
+
+    /// Binds the texture to texture unit `unit`, the value of the sampler
+    /// uniform reading it.
+    pub fn bind(&self, unit: u32) {
+        bind_to_unit(gl::TEXTURE_2D, self.texture_id, unit);
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn id(&self) -> u32 {
+        self.texture_id
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        trace::event!(TRACE, texture_id = self.texture_id, "deleting texture");
+        unsafe {
+            gl::DeleteTextures(1, &self.texture_id);
+        }
+    }
+}
+
+pub struct Texture2DBuilder {
+    params: TextureParams,
+    format: Option<TextureFormat>,
+    color_space: ColorSpace,
+    flip: bool,
+}
+
+impl Texture2DBuilder {
+    pub fn new() -> Self {
+        Self {
+            params: TextureParams::default(),
+            format: None,
+            color_space: ColorSpace::Srgb,
+            flip: true,
+        }
+    }
+
+    /// Wrap mode along both axes.
+    pub fn wrap(mut self, mode: WrapMode) -> Self {
+        self.params.wrap = [mode; 3];
+        self
+    }
+
+    pub fn wrap_s(mut self, mode: WrapMode) -> Self {
+        self.params.wrap[0] = mode;
+        self
+    }
+
+    pub fn wrap_t(mut self, mode: WrapMode) -> Self {
+        self.params.wrap[1] = mode;
+        self
+    }
+
+    /// Clamps to `color` outside the texture.
+    pub fn border_color(mut self, color: [f32; 4]) -> Self {
+        self.params.wrap = [WrapMode::ClampToBorder; 3];
+        self.params.border_color = color;
+        self
+    }
+
+    pub fn min_filter(mut self, filter: MinFilter) -> Self {
+        self.params.min_filter = filter;
+        self
+    }
+
+    pub fn mag_filter(mut self, filter: Filter) -> Self {
+        self.params.mag_filter = filter;
+        self
+    }
+
+    /// Whether to generate mipmaps, on by default. Without them mipmap
+    /// minification filters fall back to [`MinFilter::Linear`].
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.params.mipmaps = mipmaps;
+        self
+    }
+
+    /// Anisotropic filtering level, e.g. 16, silently clamped to the
+    /// supported range and ignored without driver support.
+    pub fn anisotropy(mut self, level: f32) -> Self {
+        self.params.anisotropy = level;
+        self
+    }
+
+    /// Like [`anisotropy`](Self::anisotropy), but errors if `level` isn't
+    /// supported instead of clamping it.
+    pub fn anisotropy_checked(self, level: f32) -> Result<Self, GLWError> {
+        let max = max_anisotropy().unwrap_or(1.0);
+        if !(1.0..=max).contains(&level) {
+            Err(GLWErrorKind::UnsupportedAnisotropy {
+                requested: level,
+                max,
+            })?
+        }
+        Ok(self.anisotropy(level))
+    }
+
+    /// Compares lookups against a reference value, for depth textures read
+    /// with a shadow sampler.
+    pub fn compare(mut self, func: CompareFunc) -> Self {
+        self.params.compare = Some(func);
+        self
+    }
+
+    /// Overrides the internal format, which otherwise follows the
+    /// channels of the image.
+    pub fn internal_format(mut self, format: TextureFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Color space of uploaded 8 bit color data, sRGB by default. Ignored
+    /// when the internal format is given explicitly.
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Whether images are flipped on load so their top row ends up at
+    /// `v = 1`, on by default.
+    pub fn flip_vertically(mut self, flip: bool) -> Self {
+        self.flip = flip;
+        self
+    }
+
+    pub fn build_from_path(self, path: impl AsRef<Path>) -> Result<Texture2D, GLWError> {
+        let path = path.as_ref();
+        let image = ImageData::load(path, self.flip, self.color_space)?;
+        let texture = self.upload(
+            image.width,
+            image.height,
+            image.internal_format,
+            (image.format, gl::UNSIGNED_BYTE),
+            image.pixels.as_ptr(),
+        );
+        label::set_path_label(gl::TEXTURE, texture.id(), path);
+        Ok(texture)
+    }
+
+    /// Uploads tightly packed 8 bit per channel `pixels` in `format`, e.g.
+    /// `gl::RGBA`.
+    ///
+    /// # Panics
+    /// If `pixels` is too short for the dimensions.
+    pub fn build_from_data(self, width: u32, height: u32, format: u32, pixels: &[u8]) -> Texture2D {
+        let (channels, internal_format) = match format {
+            gl::RED => (1, TextureFormat::R8),
+            gl::RG => (2, TextureFormat::Rg8),
+            gl::RGB => (3, TextureFormat::Rgb8),
+            _ => (4, TextureFormat::Rgba8),
+        };
+        let internal_format = internal_format.in_color_space(self.color_space);
+        assert!(
+            pixels.len() >= (width * height * channels) as usize,
+            "not enough pixel data"
+        );
+        self.upload(
+            width,
+            height,
+            internal_format,
+            (format, gl::UNSIGNED_BYTE),
+            pixels.as_ptr(),
+        )
+    }
+
+    /// Allocates the texture without data, e.g. as a render target.
+    pub fn build_empty(self, width: u32, height: u32) -> Texture2D {
+        let format = self.format.unwrap_or(TextureFormat::Rgba8);
+        self.upload(
+            width,
+            height,
+            format,
+            format.pixel_format(),
+            std::ptr::null(),
+        )
+    }
+
+    fn upload(
+        self,
+        width: u32,
+        height: u32,
+        default_format: TextureFormat,
+        (format, ty): (u32, u32),
+        pixels: *const u8,
+    ) -> Texture2D {
+        let internal_format = self.format.unwrap_or(default_format);
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+        }
+        trace::event!(TRACE, texture_id, width, height, format = ?internal_format, "created texture");
+        with_unpack_alignment_1(|| unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                u32::from(internal_format) as i32,
+                width as i32,
+                height as i32,
+                0,
+                format,
+                ty,
+                pixels.cast(),
+            );
+        });
+        self.params.apply(gl::TEXTURE_2D);
+
+        Texture2D {
+            texture_id,
+            width,
+            height,
+            format: internal_format,
+        }
+    }
+}
+
+impl Default for Texture2DBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
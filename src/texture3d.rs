@@ -0,0 +1,106 @@
+use crate::texture::{self, TextureFormat, TextureParams};
+
+/// Volume texture sampled with a `sampler3D`, e.g. a color grading LUT.
+pub struct Texture3D {
+    texture_id: u32,
+    size: (u32, u32, u32),
+    format: TextureFormat,
+}
+
+impl Texture3D {
+    /// Uploads `data`, a flat array of `width * height * depth` pixels
+    /// ordered row by row and slice by slice, in the pixel layout of
+    /// `format`: `u8` channels for the 8 bit formats, `f32` otherwise.
+    ///
+    /// # Panics
+    /// If `data` doesn't match the dimensions.
+    pub fn from_data<T: Copy>(
+        (width, height, depth): (u32, u32, u32),
+        format: TextureFormat,
+        data: &[T],
+        params: &TextureParams,
+    ) -> Self {
+        assert_eq!(
+            std::mem::size_of_val(data),
+            width as usize * height as usize * depth as usize * format.pixel_size(),
+            "data size doesn't match the texture dimensions"
+        );
+        Self::allocate((width, height, depth), format, data.as_ptr().cast(), params)
+    }
+
+    /// Allocates the texture without data, e.g. for a compute shader to
+    /// write into.
+    pub fn empty(size: (u32, u32, u32), format: TextureFormat, params: &TextureParams) -> Self {
+        Self::allocate(size, format, std::ptr::null(), params)
+    }
+
+    fn allocate(
+        (width, height, depth): (u32, u32, u32),
+        format: TextureFormat,
+        data: *const u8,
+        params: &TextureParams,
+    ) -> Self {
+        let (pixel_format, ty) = format.pixel_format();
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_3D, texture_id);
+        }
+        texture::with_unpack_alignment_1(|| unsafe {
+            gl::TexImage3D(
+                gl::TEXTURE_3D,
+                0,
+                u32::from(format) as i32,
+                width as i32,
+                height as i32,
+                depth as i32,
+                0,
+                pixel_format,
+                ty,
+                data.cast(),
+            );
+        });
+        params.apply(gl::TEXTURE_3D);
+
+        Self {
+            texture_id,
+            size: (width, height, depth),
+            format,
+        }
+    }
+
+    /// Sets the sampling parameters, regenerating mipmaps if they are
+    /// enabled.
+    pub fn set_params(&self, params: &TextureParams) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_3D, self.texture_id);
+        }
+        params.apply(gl::TEXTURE_3D);
+    }
+
+    pub fn bind(&self, unit: u32) {
+        texture::bind_to_unit(gl::TEXTURE_3D, self.texture_id, unit);
+    }
+
+    /// Width, height and depth.
+    pub fn size(&self) -> (u32, u32, u32) {
+        self.size
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn id(&self) -> u32 {
+        self.texture_id
+    }
+}
+
+impl Drop for Texture3D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture_id);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::path::Path;
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    texture::{self, ColorSpace, ImageData, TextureFormat, TextureParams},
+};
+
+/// Stack of equally sized 2D layers sampled with a `sampler2DArray`, e.g.
+/// shadow map cascades or sprite frames.
+pub struct Texture2DArray {
+    texture_id: u32,
+    width: u32,
+    height: u32,
+    layers: u32,
+    format: TextureFormat,
+}
+
+impl Texture2DArray {
+    /// Allocates `layers` layers without data. Sampling parameters are set
+    /// with [`set_params`](Self::set_params) once the layers are uploaded.
+    pub fn new(width: u32, height: u32, layers: u32, format: TextureFormat) -> Self {
+        let (pixel_format, ty) = format.pixel_format();
+
+        let mut texture_id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture_id);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                u32::from(format) as i32,
+                width as i32,
+                height as i32,
+                layers as i32,
+                0,
+                pixel_format,
+                ty,
+                std::ptr::null(),
+            );
+        }
+        TextureParams {
+            mipmaps: false,
+            ..TextureParams::default()
+        }
+        .apply(gl::TEXTURE_2D_ARRAY);
+
+        Self {
+            texture_id,
+            width,
+            height,
+            layers,
+            format,
+        }
+    }
+
+    /// Creates an array with one layer per image, all of the size of the
+    /// first one, with the default parameters. Fails with
+    /// [`InvalidTextureFile`](GLWErrorKind::InvalidTextureFile) if `paths`
+    /// is empty.
+    pub fn from_paths<P: AsRef<Path>>(
+        paths: &[P],
+        color_space: ColorSpace,
+    ) -> Result<Self, GLWError> {
+        let images = paths
+            .iter()
+            .map(|path| ImageData::load(path.as_ref(), true, color_space))
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(first) = images.first() else {
+            Err(GLWErrorKind::InvalidTextureFile(
+                "a texture array needs at least one layer".to_string(),
+            ))?
+        };
+
+        let array = Self::new(
+            first.width,
+            first.height,
+            images.len() as u32,
+            first.internal_format,
+        );
+        for (layer, image) in (0..).zip(&images) {
+            array.upload_image(layer, image)?;
+        }
+        array.set_params(&TextureParams::default());
+        Ok(array)
+    }
+
+    /// Uploads tightly packed 8 bit per channel `pixels` in `format`, e.g.
+    /// `gl::RGBA`, to `layer`. Fails with
+    /// [`PixelDataSizeMismatch`](GLWErrorKind::PixelDataSizeMismatch) if
+    /// `pixels` doesn't hold exactly one layer.
+    ///
+    /// # Panics
+    /// If `layer` is out of range.
+    pub fn upload_layer(&self, layer: u32, format: u32, pixels: &[u8]) -> Result<(), GLWError> {
+        assert!(layer < self.layers, "layer out of range");
+        let channels = match format {
+            gl::RED => 1,
+            gl::RG => 2,
+            gl::RGB => 3,
+            _ => 4,
+        };
+        let expected = self.width as usize * self.height as usize * channels;
+        if pixels.len() != expected {
+            Err(GLWErrorKind::PixelDataSizeMismatch {
+                expected,
+                found: pixels.len(),
+            })?;
+        }
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.texture_id);
+        }
+        texture::with_unpack_alignment_1(|| unsafe {
+            gl::TexSubImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                0,
+                0,
+                layer as i32,
+                self.width as i32,
+                self.height as i32,
+                1,
+                format,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr().cast(),
+            );
+        });
+        Ok(())
+    }
+
+    /// Loads the image at `path` into `layer`. Mipmaps have to be
+    /// regenerated with [`set_params`](Self::set_params) afterwards.
+    pub fn load_layer(&self, layer: u32, path: impl AsRef<Path>) -> Result<(), GLWError> {
+        // the color space is part of the texture's format, not the layer's
+        let color_space = match self.format {
+            TextureFormat::Srgb8 | TextureFormat::Srgb8Alpha8 => ColorSpace::Srgb,
+            _ => ColorSpace::Linear,
+        };
+        self.upload_image(layer, &ImageData::load(path.as_ref(), true, color_space)?)
+    }
+
+    fn upload_image(&self, layer: u32, image: &ImageData) -> Result<(), GLWError> {
+        if (image.width, image.height) != (self.width, self.height) {
+            Err(GLWErrorKind::ImageSizeMismatch {
+                expected: (self.width, self.height),
+                found: (image.width, image.height),
+            })?;
+        }
+        self.upload_layer(layer, image.format, &image.pixels)
+    }
+
+    /// Sets the sampling parameters, generating mipmaps for the current
+    /// contents if they are enabled.
+    pub fn set_params(&self, params: &TextureParams) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.texture_id);
+        }
+        params.apply(gl::TEXTURE_2D_ARRAY);
+    }
+
+    pub fn bind(&self, unit: u32) {
+        texture::bind_to_unit(gl::TEXTURE_2D_ARRAY, self.texture_id, unit);
+    }
+
+    /// Attaches `layer` to `attachment` (e.g. `gl::DEPTH_ATTACHMENT`) of the
+    /// bound draw framebuffer, to render into that layer alone.
+    pub fn attach_layer(&self, attachment: u32, layer: u32) {
+        unsafe {
+            gl::FramebufferTextureLayer(
+                gl::DRAW_FRAMEBUFFER,
+                attachment,
+                self.texture_id,
+                0,
+                layer as i32,
+            );
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn id(&self) -> u32 {
+        self.texture_id
+    }
+}
+
+impl Drop for Texture2DArray {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture_id);
+        }
+    }
+}
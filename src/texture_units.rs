@@ -0,0 +1,98 @@
+use crate::{
+    atlas::TextureAtlas,
+    cubemap::Cubemap,
+    errors::{GLWError, GLWErrorKind},
+    multisample_texture::Texture2DMultisample,
+    shader::ShaderProgram,
+    texture::Texture2D,
+    texture3d::Texture3D,
+    texture_array::Texture2DArray,
+};
+
+/// A texture that can be bound to a texture unit.
+pub trait BindTexture {
+    fn bind(&self, unit: u32);
+
+    /// GL name of the texture.
+    fn id(&self) -> u32;
+}
+
+macro_rules! impl_bind_texture {
+    ($($ty:ty),*) => {
+        $(impl BindTexture for $ty {
+            fn bind(&self, unit: u32) {
+                <$ty>::bind(self, unit)
+            }
+
+            fn id(&self) -> u32 {
+                <$ty>::id(self)
+            }
+        })*
+    };
+}
+
+impl_bind_texture!(
+    Texture2D,
+    Cubemap,
+    Texture2DArray,
+    Texture3D,
+    Texture2DMultisample
+);
+
+impl<K: Eq + std::hash::Hash> BindTexture for TextureAtlas<K> {
+    fn bind(&self, unit: u32) {
+        TextureAtlas::bind(self, unit)
+    }
+
+    fn id(&self) -> u32 {
+        self.texture().id()
+    }
+}
+
+/// Number of texture units all stages can use together.
+pub fn max_texture_units() -> u32 {
+    let mut units = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut units);
+    }
+    units as u32
+}
+
+impl ShaderProgram {
+    /// Binds `texture` for the sampler uniform `name`. Every sampler of the
+    /// program gets its own unit the first time it's bound, and keeps it, so
+    /// textures bound for different samplers never replace each other.
+    pub fn bind_texture(
+        &self,
+        name: impl AsRef<str>,
+        texture: &impl BindTexture,
+    ) -> Result<u32, GLWError> {
+        let name = name.as_ref();
+        let location = self.uniform_location(name)?;
+
+        let assigned = self.texture_units.borrow().get(name).copied();
+        let unit = match assigned {
+            Some(unit) => unit,
+            None => {
+                let mut units = self.texture_units.borrow_mut();
+                let unit = units.len() as u32;
+                let max = max_texture_units();
+                if unit >= max {
+                    Err(GLWErrorKind::TextureUnitsExhausted(max))?
+                }
+                units.insert(name.to_string(), unit);
+                unit
+            }
+        };
+
+        texture.bind(unit);
+        self.set_at(location, &(unit as i32))?;
+        Ok(unit)
+    }
+
+    /// The unit assigned to sampler `name` by
+    /// [`bind_texture`](Self::bind_texture), if it was bound before.
+    pub fn texture_unit(&self, name: impl AsRef<str>) -> Option<u32> {
+        self.texture_units.borrow().get(name.as_ref()).copied()
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+
+/// Per-frame timing from a monotonic time in seconds, e.g.
+/// `glfw.get_time()`.
+#[derive(Debug, Clone)]
+pub struct FrameClock {
+    start: f64,
+    last: f64,
+    delta: f32,
+    smoothed_delta: f32,
+    frame_count: u64,
+    max_delta: f32,
+}
+
+impl FrameClock {
+    /// Weight of the newest frame in the smoothed delta.
+    const SMOOTHING: f32 = 0.1;
+
+    pub fn new(now: f64) -> Self {
+        Self {
+            start: now,
+            last: now,
+            delta: 0.0,
+            smoothed_delta: 0.0,
+            frame_count: 0,
+            max_delta: 0.25,
+        }
+    }
+
+    /// Deltas are clamped to `max_delta` seconds, so a stall (a breakpoint,
+    /// dragging the window) doesn't make the simulation jump. A quarter
+    /// second by default.
+    pub fn with_max_delta(mut self, max_delta: f32) -> Self {
+        self.max_delta = max_delta;
+        self
+    }
+
+    /// Starts a new frame at `now`, returning the time since the previous
+    /// one.
+    pub fn tick(&mut self, now: f64) -> f32 {
+        self.delta = ((now - self.last) as f32).min(self.max_delta);
+        self.last = now;
+        self.smoothed_delta = match self.frame_count {
+            0 => self.delta,
+            _ => self.smoothed_delta + (self.delta - self.smoothed_delta) * Self::SMOOTHING,
+        };
+        self.frame_count += 1;
+        self.delta
+    }
+
+    /// Seconds between the last two ticks.
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// Exponential moving average of the delta, steadier for display.
+    pub fn smoothed_delta(&self) -> f32 {
+        self.smoothed_delta
+    }
+
+    /// Seconds from creation to the last tick.
+    pub fn elapsed(&self) -> f64 {
+        self.last - self.start
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+}
+
+/// Frame time statistics over the last frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameReport {
+    pub fps: f32,
+    /// Frame times in milliseconds.
+    pub min_ms: f32,
+    pub avg_ms: f32,
+    pub max_ms: f32,
+}
+
+impl std::fmt::Display for FrameReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} fps, {:.2} ms (min {:.2}, max {:.2})",
+            self.fps, self.avg_ms, self.min_ms, self.max_ms
+        )
+    }
+}
+
+/// Collects frame times in a sliding window.
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    frame_times: VecDeque<f32>,
+    window: usize,
+    /// Time since the last report from [`report_every`](Self::report_every).
+    since_report: f64,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
+
+impl FrameStats {
+    /// Statistics over the last `window` frames.
+    pub fn new(window: usize) -> Self {
+        Self {
+            frame_times: VecDeque::with_capacity(window),
+            window: window.max(1),
+            since_report: 0.0,
+        }
+    }
+
+    /// Adds a frame that took `dt` seconds.
+    pub fn record(&mut self, dt: f32) {
+        if self.frame_times.len() == self.window {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(dt);
+        self.since_report += dt as f64;
+    }
+
+    /// Statistics of the window, `None` before the first frame.
+    pub fn report(&self) -> Option<FrameReport> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+        let total: f32 = self.frame_times.iter().sum();
+        let min = self
+            .frame_times
+            .iter()
+            .copied()
+            .fold(f32::INFINITY, f32::min);
+        let max = self.frame_times.iter().copied().fold(0.0, f32::max);
+        let avg = total / self.frame_times.len() as f32;
+
+        Some(FrameReport {
+            fps: if total > 0.0 { 1.0 / avg } else { 0.0 },
+            min_ms: min * 1000.0,
+            avg_ms: avg * 1000.0,
+            max_ms: max * 1000.0,
+        })
+    }
+
+    /// The report, once every `interval` seconds of recorded frames, e.g.
+    /// to print it or put it in the window title.
+    pub fn report_every(&mut self, interval: f64) -> Option<FrameReport> {
+        if self.since_report < interval {
+            return None;
+        }
+        self.since_report = 0.0;
+        self.report()
+    }
+}
@@ -0,0 +1,28 @@
+//! Instrumentation with `tracing`, compiled out without the `tracing`
+//! feature.
+
+/// Enters a `debug` span until the returned guard drops, taking the
+/// arguments of `tracing::debug_span!`.
+macro_rules! span {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!($($arg)+).entered();
+        #[cfg(not(feature = "tracing"))]
+        let span = $crate::trace::NoSpan;
+        span
+    }};
+}
+
+/// Emits an event at `tracing::Level::$level`, taking the remaining
+/// arguments of `tracing::event!`.
+macro_rules! event {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::event!(tracing::Level::$level, $($arg)+);
+    }};
+}
+
+pub(crate) use {event, span};
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
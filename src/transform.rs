@@ -0,0 +1,148 @@
+use std::ops::Mul;
+
+use cgmath::{
+    ElementWise, EuclideanSpace, InnerSpace, Matrix3, Matrix4, One, Point3, Quaternion, Vector3,
+};
+
+/// Position, orientation and size of an object, applied as scale, then
+/// rotation, then translation. Forward is `-Z`, as for GL cameras.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn from_translation(translation: Vector3<f32>) -> Self {
+        Self {
+            translation,
+            ..Self::identity()
+        }
+    }
+
+    pub fn from_rotation(rotation: Quaternion<f32>) -> Self {
+        Self {
+            rotation,
+            ..Self::identity()
+        }
+    }
+
+    pub fn from_scale(scale: f32) -> Self {
+        Self {
+            scale: Vector3::new(scale, scale, scale),
+            ..Self::identity()
+        }
+    }
+
+    /// Placed at `eye`, facing `target`, see [`look_at`](Self::look_at).
+    pub fn looking_at(eye: Point3<f32>, target: Point3<f32>, up: Vector3<f32>) -> Self {
+        let mut transform = Self::from_translation(eye.to_vec());
+        transform.look_at(target, up);
+        transform
+    }
+
+    pub fn with_translation(mut self, translation: Vector3<f32>) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Quaternion<f32>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vector3<f32>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Turns forward towards `target`, keeping `up` as close to up as
+    /// possible. `up` must not be parallel to the direction.
+    pub fn look_at(&mut self, target: Point3<f32>, up: Vector3<f32>) {
+        let forward = (target.to_vec() - self.translation).normalize();
+        let right = forward.cross(up).normalize();
+        let up = right.cross(forward);
+        self.rotation = Matrix3::from_cols(right, up, -forward).into();
+    }
+
+    /// Moves by `offset`, in parent space.
+    pub fn translate(&mut self, offset: Vector3<f32>) {
+        self.translation += offset;
+    }
+
+    /// Rotates by `rotation`, in parent space.
+    pub fn rotate(&mut self, rotation: Quaternion<f32>) {
+        self.rotation = (rotation * self.rotation).normalize();
+    }
+
+    /// Model matrix, `translation * rotation * scale`.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+
+    pub fn forward(&self) -> Vector3<f32> {
+        self.rotation * -Vector3::unit_z()
+    }
+
+    pub fn right(&self) -> Vector3<f32> {
+        self.rotation * Vector3::unit_x()
+    }
+
+    pub fn up(&self) -> Vector3<f32> {
+        self.rotation * Vector3::unit_y()
+    }
+
+    pub fn transform_point(&self, point: Point3<f32>) -> Point3<f32> {
+        Point3::from_vec(self.transform_vector(point.to_vec()) + self.translation)
+    }
+
+    /// Scales and rotates `vector`, ignoring the translation.
+    pub fn transform_vector(&self, vector: Vector3<f32>) -> Vector3<f32> {
+        self.rotation * vector.mul_element_wise(self.scale)
+    }
+
+    /// World transform of `child`, which is relative to `self`. Exact for
+    /// uniform scales, a parent scaling a rotated child non-uniformly
+    /// would shear it, which a `Transform` can't hold.
+    pub fn compose(&self, child: &Transform) -> Transform {
+        Transform {
+            translation: self
+                .transform_point(Point3::from_vec(child.translation))
+                .to_vec(),
+            rotation: (self.rotation * child.rotation).normalize(),
+            scale: self.scale.mul_element_wise(child.scale),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// `parent * child` is [`parent.compose(&child)`](Transform::compose).
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, child: Transform) -> Transform {
+        self.compose(&child)
+    }
+}
+
+impl From<Transform> for Matrix4<f32> {
+    fn from(transform: Transform) -> Self {
+        transform.matrix()
+    }
+}
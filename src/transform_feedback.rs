@@ -0,0 +1,166 @@
+use crate::{buffer::Buffer, draw::Primitive};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransformFeedbackMode {
+    /// All varyings are written to a single buffer.
+    Interleaved,
+    /// Each varying is written to its own buffer binding.
+    Separate,
+}
+
+impl From<TransformFeedbackMode> for u32 {
+    fn from(val: TransformFeedbackMode) -> Self {
+        match val {
+            TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
+            TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS,
+        }
+    }
+}
+
+/// Primitive mode of a capture, which has to match the primitives reaching
+/// the transform feedback stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackPrimitive {
+    Points,
+    Lines,
+    Triangles,
+}
+
+impl From<FeedbackPrimitive> for u32 {
+    fn from(val: FeedbackPrimitive) -> Self {
+        match val {
+            FeedbackPrimitive::Points => gl::POINTS,
+            FeedbackPrimitive::Lines => gl::LINES,
+            FeedbackPrimitive::Triangles => gl::TRIANGLES,
+        }
+    }
+}
+
+/// Disables rasterization, for passes that only capture vertex output.
+pub fn set_rasterizer_discard(discard: bool) {
+    unsafe {
+        if discard {
+            gl::Enable(gl::RASTERIZER_DISCARD);
+        } else {
+            gl::Disable(gl::RASTERIZER_DISCARD);
+        }
+    }
+}
+
+/// Transform feedback object together with a query counting the primitives
+/// written by the last capture.
+pub struct TransformFeedback {
+    transform_feedback_id: u32,
+    query_id: u32,
+}
+
+impl TransformFeedback {
+    pub fn new() -> Self {
+        let mut transform_feedback_id = 0;
+        let mut query_id = 0;
+        unsafe {
+            gl::GenTransformFeedbacks(1, &mut transform_feedback_id);
+            gl::GenQueries(1, &mut query_id);
+        }
+
+        Self {
+            transform_feedback_id,
+            query_id,
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, self.transform_feedback_id);
+        }
+    }
+
+    pub fn unbind() {
+        unsafe {
+            gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, 0);
+        }
+    }
+
+    /// Captures varying `index` (or everything, in interleaved mode with
+    /// index 0) into `buffer`.
+    pub fn bind_buffer<T>(&self, index: u32, buffer: &Buffer<T>) {
+        self.bind();
+        unsafe {
+            gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, index, buffer.id());
+        }
+    }
+
+    /// Starts capturing the output of subsequent draw calls until the
+    /// returned guard is dropped or [`ended`](TransformFeedbackCapture::end).
+    pub fn begin(&self, primitive: FeedbackPrimitive) -> TransformFeedbackCapture<'_> {
+        self.bind();
+        unsafe {
+            gl::BeginQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN, self.query_id);
+            gl::BeginTransformFeedback(primitive.into());
+        }
+        TransformFeedbackCapture { _feedback: self }
+    }
+
+    /// Number of primitives written by the last finished capture. Blocks
+    /// until the result is available.
+    pub fn primitives_written(&self) -> u32 {
+        let mut written = 0;
+        unsafe {
+            gl::GetQueryObjectuiv(self.query_id, gl::QUERY_RESULT, &mut written);
+        }
+        written
+    }
+
+    /// Draws the vertices captured by the last capture without reading the
+    /// vertex count back to the CPU.
+    pub fn draw(&self, primitive: Primitive) {
+        unsafe {
+            gl::DrawTransformFeedback(primitive.into(), self.transform_feedback_id);
+        }
+    }
+}
+
+impl Default for TransformFeedback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TransformFeedback {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteQueries(1, &self.query_id);
+            gl::DeleteTransformFeedbacks(1, &self.transform_feedback_id);
+        }
+    }
+}
+
+/// An active capture, ended on drop.
+pub struct TransformFeedbackCapture<'a> {
+    _feedback: &'a TransformFeedback,
+}
+
+impl TransformFeedbackCapture<'_> {
+    pub fn pause(&self) {
+        unsafe {
+            gl::PauseTransformFeedback();
+        }
+    }
+
+    pub fn resume(&self) {
+        unsafe {
+            gl::ResumeTransformFeedback();
+        }
+    }
+
+    pub fn end(self) {}
+}
+
+impl Drop for TransformFeedbackCapture<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            gl::EndTransformFeedback();
+            gl::EndQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN);
+        }
+    }
+}
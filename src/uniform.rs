@@ -0,0 +1,346 @@
+use std::{ffi::CString, marker::PhantomData};
+
+use cgmath::{Matrix3, Matrix4, Vector2, Vector3, Vector4};
+
+use crate::{
+    errors::{GLWError, GLWErrorKind},
+    shader::ShaderProgram,
+};
+
+/// Location of a uniform in the program that looked it up. Using it with any
+/// other program (or with the same program after a reload) is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniformLocation {
+    pub(crate) generation: u64,
+    pub(crate) location: i32,
+}
+
+impl UniformLocation {
+    pub fn location(&self) -> i32 {
+        self.location
+    }
+}
+
+impl ShaderProgram {
+    /// Fills the location cache with every active uniform so per-frame
+    /// lookups don't have to query the driver.
+    pub(crate) fn cache_uniform_locations(&self) {
+        let mut locations = self.uniform_locations.borrow_mut();
+        locations.clear();
+
+        for uniform in self.active_uniforms() {
+            if uniform.location == -1 {
+                continue;
+            }
+            // arrays are reported as `name[0]` but usually addressed as `name`
+            if let Some(base) = uniform.name.strip_suffix("[0]") {
+                locations.insert(base.to_string(), uniform.location);
+            }
+            locations.insert(uniform.name, uniform.location);
+        }
+    }
+
+    pub fn uniform_location(&self, name: impl AsRef<str>) -> Result<UniformLocation, GLWError> {
+        let name = name.as_ref();
+        let cached = self.uniform_locations.borrow().get(name).copied();
+        let location = match cached {
+            Some(location) => location,
+            // e.g. `lights[3]`, only the first element of an array is reported
+            // by introspection. Misses are cached as -1 as well.
+            None => {
+                let c_name = CString::new(name)?;
+                let location =
+                    unsafe { gl::GetUniformLocation(self.shader_program_id, c_name.as_ptr()) };
+                self.uniform_locations
+                    .borrow_mut()
+                    .insert(name.to_string(), location);
+                location
+            }
+        };
+
+        if location == -1 {
+            Err(GLWErrorKind::UniformNotFound(name.to_string()))?;
+        }
+        Ok(UniformLocation {
+            generation: self.generation,
+            location,
+        })
+    }
+
+    /// Binds the program and uploads `value` to `location`.
+    pub fn set_at<T: UniformValue + ?Sized>(
+        &self,
+        location: UniformLocation,
+        value: &T,
+    ) -> Result<(), GLWError> {
+        if location.generation != self.generation {
+            Err(GLWErrorKind::UniformLocationMismatch)?;
+        }
+        self.use_program();
+        value.upload(location.location);
+        Ok(())
+    }
+
+    pub fn set_bool(&self, name: impl AsRef<str>, value: bool) -> Result<(), GLWError> {
+        self.set(name, &value)
+    }
+
+    pub fn set_i32(&self, name: impl AsRef<str>, value: i32) -> Result<(), GLWError> {
+        self.set(name, &value)
+    }
+
+    pub fn set_f32(&self, name: impl AsRef<str>, value: f32) -> Result<(), GLWError> {
+        self.set(name, &value)
+    }
+
+    pub fn set_vec2(
+        &self,
+        name: impl AsRef<str>,
+        value: impl Into<[f32; 2]>,
+    ) -> Result<(), GLWError> {
+        self.set(name, &value.into())
+    }
+
+    pub fn set_vec3(
+        &self,
+        name: impl AsRef<str>,
+        value: impl Into<[f32; 3]>,
+    ) -> Result<(), GLWError> {
+        self.set(name, &value.into())
+    }
+
+    pub fn set_vec4(
+        &self,
+        name: impl AsRef<str>,
+        value: impl Into<[f32; 4]>,
+    ) -> Result<(), GLWError> {
+        self.set(name, &value.into())
+    }
+
+    /// `value` is expected in column-major order, as produced by cgmath.
+    /// glam matrices go through [`set`](Self::set).
+    pub fn set_mat3(
+        &self,
+        name: impl AsRef<str>,
+        value: impl Into<[[f32; 3]; 3]>,
+    ) -> Result<(), GLWError> {
+        self.set(name, &value.into())
+    }
+
+    /// `value` is expected in column-major order, as produced by cgmath.
+    /// glam matrices go through [`set`](Self::set).
+    pub fn set_mat4(
+        &self,
+        name: impl AsRef<str>,
+        value: impl Into<[[f32; 4]; 4]>,
+    ) -> Result<(), GLWError> {
+        self.set(name, &value.into())
+    }
+
+    pub fn set_i32_array(&self, name: impl AsRef<str>, values: &[i32]) -> Result<(), GLWError> {
+        self.set(name, values)
+    }
+
+    pub fn set_f32_array(&self, name: impl AsRef<str>, values: &[f32]) -> Result<(), GLWError> {
+        self.set(name, values)
+    }
+
+    pub fn set_vec2_array(
+        &self,
+        name: impl AsRef<str>,
+        values: &[[f32; 2]],
+    ) -> Result<(), GLWError> {
+        self.set(name, values)
+    }
+
+    pub fn set_vec3_array(
+        &self,
+        name: impl AsRef<str>,
+        values: &[[f32; 3]],
+    ) -> Result<(), GLWError> {
+        self.set(name, values)
+    }
+
+    pub fn set_vec4_array(
+        &self,
+        name: impl AsRef<str>,
+        values: &[[f32; 4]],
+    ) -> Result<(), GLWError> {
+        self.set(name, values)
+    }
+
+    pub fn set_mat3_array(
+        &self,
+        name: impl AsRef<str>,
+        values: &[[[f32; 3]; 3]],
+    ) -> Result<(), GLWError> {
+        self.set(name, values)
+    }
+
+    pub fn set_mat4_array(
+        &self,
+        name: impl AsRef<str>,
+        values: &[[[f32; 4]; 4]],
+    ) -> Result<(), GLWError> {
+        self.set(name, values)
+    }
+}
+
+/// Values that know which `glUniform*` call uploads them.
+pub trait UniformValue {
+    /// Uploads `self` to `location` of the currently bound program.
+    fn upload(&self, location: i32);
+
+    fn set_uniform(&self, program: &ShaderProgram, name: &str) -> Result<(), GLWError> {
+        program.set_at(program.uniform_location(name)?, self)
+    }
+}
+
+macro_rules! impl_uniform_value {
+    ($($ty:ty => |$value:ident, $location:ident| $upload:expr),* $(,)?) => {
+        $(
+            impl UniformValue for $ty {
+                fn upload(&self, $location: i32) {
+                    let $value = self;
+                    unsafe { $upload }
+                }
+            }
+        )*
+    };
+}
+
+impl_uniform_value! {
+    bool => |v, location| gl::Uniform1i(location, *v as i32),
+    i32 => |v, location| gl::Uniform1i(location, *v),
+    f32 => |v, location| gl::Uniform1f(location, *v),
+    [f32; 2] => |v, location| gl::Uniform2f(location, v[0], v[1]),
+    [f32; 3] => |v, location| gl::Uniform3f(location, v[0], v[1], v[2]),
+    [f32; 4] => |v, location| gl::Uniform4f(location, v[0], v[1], v[2], v[3]),
+    Vector2<f32> => |v, location| gl::Uniform2f(location, v.x, v.y),
+    Vector3<f32> => |v, location| gl::Uniform3f(location, v.x, v.y, v.z),
+    Vector4<f32> => |v, location| gl::Uniform4f(location, v.x, v.y, v.z, v.w),
+    [[f32; 3]; 3] => |v, location| {
+        gl::UniformMatrix3fv(location, 1, gl::FALSE, v.as_ptr().cast())
+    },
+    [[f32; 4]; 4] => |v, location| {
+        gl::UniformMatrix4fv(location, 1, gl::FALSE, v.as_ptr().cast())
+    },
+    Matrix3<f32> => |v, location| {
+        let columns: &[[f32; 3]; 3] = v.as_ref();
+        gl::UniformMatrix3fv(location, 1, gl::FALSE, columns.as_ptr().cast())
+    },
+    Matrix4<f32> => |v, location| {
+        let columns: &[[f32; 4]; 4] = v.as_ref();
+        gl::UniformMatrix4fv(location, 1, gl::FALSE, columns.as_ptr().cast())
+    },
+    [i32] => |v, location| gl::Uniform1iv(location, v.len() as i32, v.as_ptr()),
+    [f32] => |v, location| gl::Uniform1fv(location, v.len() as i32, v.as_ptr()),
+    [[f32; 2]] => |v, location| {
+        gl::Uniform2fv(location, v.len() as i32, v.as_ptr().cast())
+    },
+    [[f32; 3]] => |v, location| {
+        gl::Uniform3fv(location, v.len() as i32, v.as_ptr().cast())
+    },
+    [[f32; 4]] => |v, location| {
+        gl::Uniform4fv(location, v.len() as i32, v.as_ptr().cast())
+    },
+    [[[f32; 3]; 3]] => |v, location| {
+        gl::UniformMatrix3fv(location, v.len() as i32, gl::FALSE, v.as_ptr().cast())
+    },
+    [[[f32; 4]; 4]] => |v, location| {
+        gl::UniformMatrix4fv(location, v.len() as i32, gl::FALSE, v.as_ptr().cast())
+    },
+}
+
+#[cfg(feature = "glam")]
+impl_uniform_value! {
+    glam::Vec2 => |v, location| gl::Uniform2f(location, v.x, v.y),
+    glam::Vec3 => |v, location| gl::Uniform3f(location, v.x, v.y, v.z),
+    glam::Vec4 => |v, location| gl::Uniform4f(location, v.x, v.y, v.z, v.w),
+    glam::IVec2 => |v, location| gl::Uniform2i(location, v.x, v.y),
+    glam::IVec3 => |v, location| gl::Uniform3i(location, v.x, v.y, v.z),
+    glam::IVec4 => |v, location| gl::Uniform4i(location, v.x, v.y, v.z, v.w),
+    glam::Mat3 => |v, location| {
+        gl::UniformMatrix3fv(location, 1, gl::FALSE, v.to_cols_array().as_ptr())
+    },
+    glam::Mat4 => |v, location| {
+        gl::UniformMatrix4fv(location, 1, gl::FALSE, v.to_cols_array().as_ptr())
+    },
+    [glam::Vec2] => |v, location| {
+        gl::Uniform2fv(location, v.len() as i32, v.as_ptr().cast())
+    },
+    [glam::Vec3] => |v, location| {
+        gl::Uniform3fv(location, v.len() as i32, v.as_ptr().cast())
+    },
+    [glam::Vec4] => |v, location| {
+        gl::Uniform4fv(location, v.len() as i32, v.as_ptr().cast())
+    },
+    [glam::Mat4] => |v, location| {
+        gl::UniformMatrix4fv(location, v.len() as i32, gl::FALSE, v.as_ptr().cast())
+    },
+}
+
+/// A uniform looked up once and tied to the type uploaded to it, e.g.
+/// `Uniform<Matrix4<f32>>`, so the `glUniform*` call is picked at compile
+/// time.
+pub struct Uniform<T: ?Sized> {
+    location: UniformLocation,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T: ?Sized> Clone for Uniform<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Uniform<T> {}
+
+impl<T: UniformValue + ?Sized> Uniform<T> {
+    pub fn location(&self) -> UniformLocation {
+        self.location
+    }
+
+    /// Binds `program` and uploads `value`, see
+    /// [`ShaderProgram::set_at`]. Fails if `program` isn't the one the
+    /// uniform was looked up in, or has been reloaded since.
+    pub fn set(&self, program: &ShaderProgram, value: &T) -> Result<(), GLWError> {
+        program.set_at(self.location, value)
+    }
+}
+
+impl ShaderProgram {
+    pub fn uniform<T: UniformValue + ?Sized>(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Uniform<T>, GLWError> {
+        Ok(Uniform {
+            location: self.uniform_location(name)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// A group of uniforms uploaded together, usually implemented with
+/// `#[derive(Uniforms)]` (`derive` feature), which uploads each field to
+/// the uniform of the same name.
+pub trait Uniforms {
+    fn set_uniforms(&self, program: &ShaderProgram) -> Result<(), GLWError>;
+}
+
+#[cfg(feature = "derive")]
+pub use learngl_derive::Uniforms;
+
+impl ShaderProgram {
+    pub fn set<T: UniformValue + ?Sized>(
+        &self,
+        name: impl AsRef<str>,
+        value: &T,
+    ) -> Result<(), GLWError> {
+        value.set_uniform(self, name.as_ref())
+    }
+
+    pub fn set_uniforms(&self, uniforms: &impl Uniforms) -> Result<(), GLWError> {
+        uniforms.set_uniforms(self)
+    }
+}
@@ -0,0 +1,72 @@
+use std::ffi::CString;
+
+use crate::{
+    buffer::{Buffer, BufferTarget, BufferUsage},
+    errors::{GLWError, GLWErrorKind},
+    shader::ShaderProgram,
+};
+
+impl ShaderProgram {
+    pub fn uniform_block_index(&self, name: impl AsRef<str>) -> Result<u32, GLWError> {
+        let c_name = CString::new(name.as_ref())?;
+
+        let index = unsafe { gl::GetUniformBlockIndex(self.shader_program_id, c_name.as_ptr()) };
+        if index == gl::INVALID_INDEX {
+            Err(GLWErrorKind::UniformBlockNotFound(
+                name.as_ref().to_string(),
+            ))?;
+        }
+
+        Ok(index)
+    }
+
+    /// Makes the uniform block `name` source its data from whatever buffer is
+    /// bound to `binding_point`, see [`UniformBuffer::bind_base`].
+    pub fn bind_uniform_block(
+        &self,
+        name: impl AsRef<str>,
+        binding_point: u32,
+    ) -> Result<(), GLWError> {
+        let index = self.uniform_block_index(name)?;
+        unsafe {
+            gl::UniformBlockBinding(self.shader_program_id, index, binding_point);
+        }
+        Ok(())
+    }
+}
+
+/// Buffer backing a uniform block. `T` has to match the std140 layout of the
+/// block, so it should be `#[repr(C)]` with explicit padding where needed.
+pub struct UniformBuffer<T> {
+    buffer: Buffer<T>,
+}
+
+impl<T: Copy> UniformBuffer<T> {
+    pub fn new(value: &T) -> Self {
+        Self {
+            buffer: Buffer::new(
+                BufferTarget::Uniform,
+                std::slice::from_ref(value),
+                BufferUsage::Dynamic,
+            ),
+        }
+    }
+
+    pub fn update(&mut self, value: &T) {
+        self.buffer.update(0, std::slice::from_ref(value));
+    }
+}
+
+impl<T> UniformBuffer<T> {
+    pub fn bind_base(&self, binding_point: u32) {
+        self.buffer.bind_base(binding_point);
+    }
+
+    pub fn buffer(&self) -> &Buffer<T> {
+        &self.buffer
+    }
+
+    pub fn id(&self) -> u32 {
+        self.buffer.id()
+    }
+}
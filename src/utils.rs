@@ -1,43 +1,121 @@
-use gl::types::GLsizei;
+use std::ffi::CStr;
 
+/// Checks `pname` of the shader, e.g. `COMPILE_STATUS`, failing with the
+/// info log. On success the log is returned if the driver left warnings.
+///
 /// # Safety
 /// shader_id should be valid
-pub unsafe fn check_shader_succes(shader_id: u32, pname: gl::types::GLenum) -> Result<(), String> {
-    let mut success = 42;
-    let mut info_log = vec![0; 512];
-    let mut length: i32 = 42;
-
+pub unsafe fn check_shader_succes(
+    shader_id: u32,
+    pname: gl::types::GLenum,
+) -> Result<Option<String>, String> {
+    let mut success = 0;
     gl::GetShaderiv(shader_id, pname, &mut success);
-    if success != gl::TRUE.into() {
-        gl::GetShaderInfoLog(
-            shader_id,
-            512,
-            &mut length as *mut i32 as *mut GLsizei,
-            info_log.as_mut_ptr() as *mut gl::types::GLchar,
-        );
-        return Err(String::from_utf8_lossy(&info_log[..length as usize]).to_string());
+    let info_log = shader_info_log(shader_id);
+    if success != gl::TRUE as i32 {
+        return Err(info_log);
+    }
+    Ok(Some(info_log).filter(|log| !log.trim().is_empty()))
+}
+
+/// Like [`check_shader_succes`] for programs, e.g. with `LINK_STATUS`.
+///
+/// # Safety
+/// pid should be valid
+pub unsafe fn check_program_success(
+    pid: u32,
+    pname: gl::types::GLenum,
+) -> Result<Option<String>, String> {
+    let mut success = 0;
+    gl::GetProgramiv(pid, pname, &mut success);
+    let info_log = program_info_log(pid);
+    if success != gl::TRUE as i32 {
+        return Err(info_log);
+    }
+    Ok(Some(info_log).filter(|log| !log.trim().is_empty()))
+}
+
+/// The whole info log of the shader's last compile.
+///
+/// # Safety
+/// shader_id should be valid
+pub unsafe fn shader_info_log(shader_id: u32) -> String {
+    let mut length = 0;
+    gl::GetShaderiv(shader_id, gl::INFO_LOG_LENGTH, &mut length);
+    if length == 0 {
+        return String::new();
     }
 
-    Ok(())
+    let mut info_log = vec![0u8; length as usize];
+    gl::GetShaderInfoLog(
+        shader_id,
+        length,
+        &mut length,
+        info_log.as_mut_ptr() as *mut gl::types::GLchar,
+    );
+    String::from_utf8_lossy(&info_log[..length as usize]).to_string()
 }
 
+/// The whole info log of the program's last link or validation.
+///
 /// # Safety
 /// pid should be valid
-pub unsafe fn check_program_success(pid: u32, pname: gl::types::GLenum) -> Result<(), String> {
-    let mut success = 42;
-    let mut info_log = vec![0; 512];
-    let mut length: i32 = 42;
+pub unsafe fn program_info_log(pid: u32) -> String {
+    let mut length = 0;
+    gl::GetProgramiv(pid, gl::INFO_LOG_LENGTH, &mut length);
+    if length == 0 {
+        return String::new();
+    }
 
-    gl::GetProgramiv(pid, pname, &mut success);
-    if success != gl::TRUE.into() {
-        gl::GetProgramInfoLog(
-            pid,
-            512,
-            &mut length as *mut i32 as *mut GLsizei,
-            info_log.as_mut_ptr() as *mut gl::types::GLchar,
-        );
-        return Err(String::from_utf8_lossy(&info_log[..length as usize]).to_string());
+    let mut info_log = vec![0u8; length as usize];
+    gl::GetProgramInfoLog(
+        pid,
+        length,
+        &mut length,
+        info_log.as_mut_ptr() as *mut gl::types::GLchar,
+    );
+    String::from_utf8_lossy(&info_log[..length as usize]).to_string()
+}
+
+/// # Safety
+/// shader_id should be valid
+pub unsafe fn shader_source(shader_id: u32) -> String {
+    let mut length = 0;
+    gl::GetShaderiv(shader_id, gl::SHADER_SOURCE_LENGTH, &mut length);
+
+    let mut source = vec![0u8; length.max(1) as usize];
+    gl::GetShaderSource(
+        shader_id,
+        length,
+        &mut length,
+        source.as_mut_ptr() as *mut gl::types::GLchar,
+    );
+    String::from_utf8_lossy(&source[..length as usize]).to_string()
+}
+
+/// Reads one of the `glGetString` strings, e.g. `gl::VENDOR`.
+pub fn gl_string(name: gl::types::GLenum) -> String {
+    unsafe {
+        let ptr = gl::GetString(name);
+        if ptr.is_null() {
+            return String::new();
+        }
+        CStr::from_ptr(ptr as *const _)
+            .to_string_lossy()
+            .to_string()
     }
+}
+
+/// Whether the current context advertises the extension `name`, e.g.
+/// `"GL_KHR_debug"`.
+pub fn has_extension(name: &str) -> bool {
+    unsafe {
+        let mut count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
 
-    Ok(())
+        (0..count as u32).any(|index| {
+            let ptr = gl::GetStringi(gl::EXTENSIONS, index);
+            !ptr.is_null() && CStr::from_ptr(ptr as *const _).to_bytes() == name.as_bytes()
+        })
+    }
 }
@@ -0,0 +1,358 @@
+use cgmath::{Matrix2, Matrix3, Matrix4, Point2, Point3, Vector2, Vector3, Vector4};
+use gl::types::GLenum;
+
+use crate::errors::{GLWError, GLWErrorKind};
+
+/// How the vertex attributes of a vertex buffer are laid out.
+///
+/// ```ignore
+/// // aPos and aColor, 24 bytes per vertex
+/// let layout = VertexLayout::new().push::<f32>(3).push::<f32>(3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>,
+    stride: usize,
+    next_location: u32,
+    divisor: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub location: u32,
+    pub components: i32,
+    pub gl_type: GLenum,
+    pub normalized: bool,
+    /// Read as an integer attribute (`ivec`/`uvec`) rather than converted to
+    /// float, see [`VertexLayout::push`].
+    pub integer: bool,
+    pub offset: usize,
+}
+
+impl VertexAttribute {
+    /// Size of the attribute in bytes.
+    pub fn size(&self) -> usize {
+        let component_size = match self.gl_type {
+            gl::DOUBLE => 8,
+            gl::FLOAT | gl::INT | gl::UNSIGNED_INT => 4,
+            gl::SHORT | gl::UNSIGNED_SHORT => 2,
+            _ => 1,
+        };
+        self.components as usize * component_size
+    }
+}
+
+impl VertexLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an attribute of `components` values of `T` at the next free
+    /// location, right after the previous attribute.
+    ///
+    /// Integer types are passed to the shader as integers, use
+    /// [`push_normalized`](Self::push_normalized) to read them as floats.
+    pub fn push<T: AttribComponent>(self, components: i32) -> Self {
+        let offset = self.stride;
+        self.push_at::<T>(components, offset, false)
+    }
+
+    /// Like [`push`](Self::push), with integer values mapped to `[0, 1]`
+    /// (or `[-1, 1]` for signed types).
+    pub fn push_normalized<T: AttribComponent>(self, components: i32) -> Self {
+        let offset = self.stride;
+        self.push_at::<T>(components, offset, true)
+    }
+
+    /// Appends the attribute for a struct field of type `F` at `offset`,
+    /// used by `#[derive(Vertex)]`.
+    /// Matrices take one location per column.
+    pub fn push_field<F: VertexField>(mut self, offset: usize, normalized: bool) -> Self {
+        let column_size = F::COMPONENTS as usize * std::mem::size_of::<F::Component>();
+        for column in 0..F::COLUMNS {
+            self = self.push_at::<F::Component>(
+                F::COMPONENTS,
+                offset + column * column_size,
+                normalized,
+            );
+        }
+        self
+    }
+
+    /// Makes the attributes pushed afterwards start at `location`, e.g. to
+    /// put per-instance attributes after the per-vertex ones.
+    pub fn starting_at(mut self, location: u32) -> Self {
+        self.next_location = location;
+        self
+    }
+
+    /// Advances the attributes once every `divisor` instances instead of
+    /// once per vertex, see [`InstanceBuffer`](crate::buffer::InstanceBuffer).
+    pub fn per_instance(mut self, divisor: u32) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
+    /// Overrides the distance between consecutive vertices, e.g. to account
+    /// for trailing padding.
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    fn push_at<T: AttribComponent>(
+        mut self,
+        components: i32,
+        offset: usize,
+        normalized: bool,
+    ) -> Self {
+        self.attributes.push(VertexAttribute {
+            location: self.next_location,
+            components,
+            gl_type: T::GL_TYPE,
+            normalized,
+            integer: T::INTEGER && !normalized,
+            offset,
+        });
+        self.next_location += 1;
+        self.stride = self
+            .stride
+            .max(offset + components as usize * std::mem::size_of::<T>());
+        self
+    }
+
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn divisor(&self) -> u32 {
+        self.divisor
+    }
+
+    /// Checks every attribute has 1 to 4 components, fits in the stride and
+    /// has a location of its own below `GL_MAX_VERTEX_ATTRIBS`.
+    pub fn validate(&self) -> Result<(), GLWError> {
+        let mut max_attributes = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_VERTEX_ATTRIBS, &mut max_attributes);
+        }
+        for (index, attribute) in self.attributes.iter().enumerate() {
+            let location = attribute.location;
+            if !(1..=4).contains(&attribute.components) {
+                Err(GLWErrorKind::InvalidVertexLayout(format!(
+                    "attribute {location} has {} components",
+                    attribute.components
+                )))?;
+            }
+            let end = attribute.offset + attribute.size();
+            if end > self.stride {
+                Err(GLWErrorKind::InvalidVertexLayout(format!(
+                    "attribute {location} ends at byte {end}, past the stride of {}",
+                    self.stride
+                )))?;
+            }
+            if location >= max_attributes as u32 {
+                Err(GLWErrorKind::InvalidVertexLayout(format!(
+                    "location {location} is past the {max_attributes} supported attributes"
+                )))?;
+            }
+            if self.attributes[..index]
+                .iter()
+                .any(|other| other.location == location)
+            {
+                Err(GLWErrorKind::InvalidVertexLayout(format!(
+                    "location {location} is used twice"
+                )))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Points the attributes at the bound `ARRAY_BUFFER` and enables them,
+    /// recording both in the bound vertex array.
+    pub fn apply(&self) {
+        for attribute in &self.attributes {
+            unsafe {
+                if attribute.integer {
+                    gl::VertexAttribIPointer(
+                        attribute.location,
+                        attribute.components,
+                        attribute.gl_type,
+                        self.stride as i32,
+                        attribute.offset as *const _,
+                    );
+                } else {
+                    gl::VertexAttribPointer(
+                        attribute.location,
+                        attribute.components,
+                        attribute.gl_type,
+                        attribute.normalized as u8,
+                        self.stride as i32,
+                        attribute.offset as *const _,
+                    );
+                }
+                gl::VertexAttribDivisor(attribute.location, self.divisor);
+                gl::EnableVertexAttribArray(attribute.location);
+            }
+        }
+    }
+}
+
+impl VertexLayout {
+    /// Describes the attributes with the separate attribute format API
+    /// (GL 4.3) and sources them from vertex buffer binding `binding`,
+    /// recording both in the bound vertex array.
+    pub(crate) fn apply_format(&self, binding: u32) {
+        for attribute in &self.attributes {
+            unsafe {
+                if attribute.integer {
+                    gl::VertexAttribIFormat(
+                        attribute.location,
+                        attribute.components,
+                        attribute.gl_type,
+                        attribute.offset as u32,
+                    );
+                } else {
+                    gl::VertexAttribFormat(
+                        attribute.location,
+                        attribute.components,
+                        attribute.gl_type,
+                        attribute.normalized as u8,
+                        attribute.offset as u32,
+                    );
+                }
+                gl::VertexAttribBinding(attribute.location, binding);
+                gl::EnableVertexAttribArray(attribute.location);
+            }
+        }
+        unsafe {
+            gl::VertexBindingDivisor(binding, self.divisor);
+        }
+    }
+}
+
+/// Scalar types vertex attributes can be made of.
+pub trait AttribComponent: Copy {
+    const GL_TYPE: GLenum;
+    const INTEGER: bool;
+}
+
+macro_rules! impl_attrib_component {
+    ($($ty:ty => $gl_type:ident, $integer:literal),* $(,)?) => {
+        $(
+            impl AttribComponent for $ty {
+                const GL_TYPE: GLenum = gl::$gl_type;
+                const INTEGER: bool = $integer;
+            }
+        )*
+    };
+}
+
+impl_attrib_component! {
+    f32 => FLOAT, false,
+    f64 => DOUBLE, false,
+    i8 => BYTE, true,
+    u8 => UNSIGNED_BYTE, true,
+    i16 => SHORT, true,
+    u16 => UNSIGNED_SHORT, true,
+    i32 => INT, true,
+    u32 => UNSIGNED_INT, true,
+}
+
+/// Types a vertex struct field can have, a scalar, a vector of up to four
+/// components or a matrix of such column vectors.
+pub trait VertexField {
+    type Component: AttribComponent;
+    const COMPONENTS: i32;
+    const COLUMNS: usize = 1;
+}
+
+impl<T: AttribComponent> VertexField for T {
+    type Component = T;
+    const COMPONENTS: i32 = 1;
+}
+
+impl<T: AttribComponent, const N: usize> VertexField for [T; N] {
+    type Component = T;
+    const COMPONENTS: i32 = N as i32;
+}
+
+macro_rules! impl_vertex_field {
+    ($($ty:ident => $components:literal),* $(,)?) => {
+        $(
+            impl<T: AttribComponent> VertexField for $ty<T> {
+                type Component = T;
+                const COMPONENTS: i32 = $components;
+            }
+        )*
+    };
+}
+
+impl_vertex_field! {
+    Vector2 => 2,
+    Vector3 => 3,
+    Vector4 => 4,
+    Point2 => 2,
+    Point3 => 3,
+}
+
+macro_rules! impl_vertex_field_matrix {
+    ($($ty:ident => $size:literal),* $(,)?) => {
+        $(
+            impl<T: AttribComponent> VertexField for $ty<T> {
+                type Component = T;
+                const COMPONENTS: i32 = $size;
+                const COLUMNS: usize = $size;
+            }
+        )*
+    };
+}
+
+impl_vertex_field_matrix! {
+    Matrix2 => 2,
+    Matrix3 => 3,
+    Matrix4 => 4,
+}
+
+/// Vertex types that know their layout, usually implemented with
+/// `#[derive(Vertex)]` (`derive` feature) on a `#[repr(C)]` struct, which
+/// assigns one attribute location per field in declaration order.
+///
+/// Lone vectors are vertices with a single attribute at location 0.
+pub trait Vertex: Copy {
+    fn layout() -> VertexLayout;
+
+    fn stride() -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+macro_rules! impl_vertex {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Vertex for $ty {
+                fn layout() -> VertexLayout {
+                    VertexLayout::new().push_field::<Self>(0, false)
+                }
+            }
+        )*
+    };
+}
+
+impl_vertex! {
+    [f32; 2],
+    [f32; 3],
+    [f32; 4],
+    Vector2<f32>,
+    Vector3<f32>,
+    Vector4<f32>,
+    Point2<f32>,
+    Point3<f32>,
+}
+
+#[cfg(feature = "derive")]
+pub use learngl_derive::Vertex;
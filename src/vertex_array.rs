@@ -0,0 +1,113 @@
+use crate::{
+    buffer::{Buffer, Index, IndexBuffer, InstanceBuffer},
+    trace,
+    vertex::{Vertex, VertexLayout},
+};
+
+/// Vertex array object, recording which buffers the vertex attributes are
+/// read from and which index buffer is used.
+pub struct VertexArray {
+    vertex_array_id: u32,
+}
+
+impl VertexArray {
+    pub fn new() -> Self {
+        let mut vertex_array_id = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vertex_array_id);
+        }
+        trace::event!(TRACE, vertex_array_id, "created vertex array");
+        Self { vertex_array_id }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vertex_array_id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindVertexArray(0);
+        }
+    }
+
+    /// Sources the attributes described by `layout` from `buffer`.
+    ///
+    /// The vertex array only references the buffer, which must outlive any
+    /// draw call made through it.
+    pub fn attach_buffer<T>(&self, buffer: &Buffer<T>, layout: &VertexLayout) {
+        self.bind();
+        buffer.bind();
+        layout.apply();
+        self.unbind();
+    }
+
+    /// Sources the attributes of `layout` from `buffer` through vertex buffer
+    /// binding `binding`, so attributes can come from several buffers, e.g.
+    /// positions and normals in separate arrays:
+    ///
+    /// ```ignore
+    /// vao.attach_buffer_at(0, &positions, &VertexLayout::new().push::<f32>(3));
+    /// vao.attach_buffer_at(1, &normals, &VertexLayout::new().starting_at(1).push::<f32>(3));
+    /// ```
+    ///
+    /// Uses `glBindVertexBuffer` where available (GL 4.3) and falls back to
+    /// [`attach_buffer`](Self::attach_buffer) otherwise. Either way the
+    /// layouts of the buffers must not share attribute locations.
+    pub fn attach_buffer_at<T>(&self, binding: u32, buffer: &Buffer<T>, layout: &VertexLayout) {
+        if !gl::BindVertexBuffer::is_loaded() {
+            return self.attach_buffer(buffer, layout);
+        }
+
+        self.bind();
+        unsafe {
+            gl::BindVertexBuffer(binding, buffer.id(), 0, layout.stride() as i32);
+        }
+        layout.apply_format(binding);
+        self.unbind();
+    }
+
+    /// Sources the attributes of `V` from `vertices`, using
+    /// [`Vertex::layout`].
+    pub fn attach_vertices<V: Vertex>(&self, vertices: &Buffer<V>) {
+        self.attach_buffer(vertices, &V::layout());
+    }
+
+    /// Sources per-instance attributes from `instances`, which must outlive
+    /// the draw calls as well.
+    pub fn attach_instance_buffer<T>(&self, instances: &InstanceBuffer<T>) {
+        self.attach_buffer(instances.buffer(), instances.layout());
+    }
+
+    /// Makes indexed draw calls read from `indices`. Like vertex buffers,
+    /// the index buffer must outlive the draw calls.
+    pub fn attach_index_buffer<I: Index>(&self, indices: &IndexBuffer<I>) {
+        self.bind();
+        indices.bind();
+        self.unbind();
+    }
+
+    pub fn id(&self) -> u32 {
+        self.vertex_array_id
+    }
+}
+
+impl Default for VertexArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        trace::event!(
+            TRACE,
+            vertex_array_id = self.vertex_array_id,
+            "deleting vertex array"
+        );
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vertex_array_id);
+        }
+    }
+}
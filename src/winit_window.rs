@@ -0,0 +1,160 @@
+use std::{ffi::CString, num::NonZeroU32};
+
+use glutin::{
+    config::{Config, ConfigTemplateBuilder, GlConfig},
+    context::{
+        ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentGlContext,
+        PossiblyCurrentContext, PossiblyCurrentGlContext, Version,
+    },
+    display::{GetGlDisplay, GlDisplay},
+    surface::{GlSurface, Surface, SwapInterval, WindowSurface},
+};
+use glutin_winit::{DisplayBuilder, GlWindow as _};
+use raw_window_handle::HasWindowHandle;
+use winit::{
+    dpi::PhysicalSize,
+    event_loop::ActiveEventLoop,
+    window::{Window, WindowAttributes},
+};
+
+use crate::{
+    context::GlApi,
+    errors::{GLWError, GLWErrorKind},
+};
+
+/// Context settings for a [`GlWindow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlWindowConfig {
+    pub api: GlApi,
+    /// Major and minor version of the context, a core profile one for
+    /// desktop GL.
+    pub gl_version: (u8, u8),
+    /// Samples of the default framebuffer, `None` for no multisampling.
+    pub samples: Option<u8>,
+    pub vsync: bool,
+}
+
+impl Default for GlWindowConfig {
+    fn default() -> Self {
+        Self {
+            api: GlApi::OpenGl,
+            gl_version: (3, 3),
+            samples: None,
+            vsync: true,
+        }
+    }
+}
+
+/// A winit window with a current glutin context, the `winit` feature's
+/// counterpart of the GLFW window `run` creates. Everything
+/// but windowing and input works the same with it.
+///
+/// winit drives the event loop, so create it in `ApplicationHandler::resumed`
+/// and forward `WindowEvent::Resized` to [`resize`](Self::resize).
+pub struct GlWindow {
+    // the surface and context have to go before the window
+    surface: Surface<WindowSurface>,
+    context: PossiblyCurrentContext,
+    pub window: Window,
+}
+
+impl GlWindow {
+    /// Opens a window with `attributes`, makes a context as described by
+    /// `config` current on it and loads GL.
+    pub fn new(
+        event_loop: &ActiveEventLoop,
+        attributes: WindowAttributes,
+        config: &GlWindowConfig,
+    ) -> Result<Self, GLWError> {
+        let mut template = ConfigTemplateBuilder::new();
+        if let Some(samples) = config.samples {
+            template = template.with_multisampling(samples);
+        }
+        let (window, gl_config) = DisplayBuilder::new()
+            .with_window_attributes(Some(attributes))
+            .build(event_loop, template, |configs| {
+                pick_config(configs, config.samples.is_some())
+            })
+            .map_err(|err| GLWError::new(GLWErrorKind::WindowCreationFailed, err.to_string()))?;
+        let window = window.ok_or(GLWErrorKind::WindowCreationFailed)?;
+
+        let (major, minor) = config.gl_version;
+        let raw_window_handle = window.window_handle().ok().map(|handle| handle.as_raw());
+        let version = Some(Version::new(major, minor));
+        let context_attributes = match config.api {
+            GlApi::OpenGl => ContextAttributesBuilder::new()
+                .with_context_api(ContextApi::OpenGl(version))
+                .with_profile(GlProfile::Core),
+            GlApi::OpenGlEs => {
+                ContextAttributesBuilder::new().with_context_api(ContextApi::Gles(version))
+            }
+        }
+        .build(raw_window_handle);
+        let display = gl_config.display();
+        let context = unsafe { display.create_context(&gl_config, &context_attributes)? };
+
+        let surface_attributes = window
+            .build_surface_attributes(Default::default())
+            .map_err(|err| GLWError::new(GLWErrorKind::WindowCreationFailed, err.to_string()))?;
+        let surface = unsafe { display.create_window_surface(&gl_config, &surface_attributes)? };
+        let context = context.make_current(&surface)?;
+
+        crate::context::load_with(|symbol| {
+            let symbol = CString::new(symbol).unwrap();
+            display.get_proc_address(&symbol) as *const _
+        });
+        let interval = match config.vsync {
+            true => SwapInterval::Wait(NonZeroU32::MIN),
+            false => SwapInterval::DontWait,
+        };
+        // not every platform lets the interval be changed, which is harmless
+        let _ = surface.set_swap_interval(&context, interval);
+        if config.samples.is_some() {
+            crate::context::enable_multisample();
+        }
+
+        let gl_window = Self {
+            surface,
+            context,
+            window,
+        };
+        gl_window.resize(gl_window.window.inner_size());
+        Ok(gl_window)
+    }
+
+    /// Resizes the surface and viewport, call it on `WindowEvent::Resized`.
+    pub fn resize(&self, size: PhysicalSize<u32>) {
+        let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        else {
+            // minimized
+            return;
+        };
+        self.surface.resize(&self.context, width, height);
+        unsafe {
+            gl::Viewport(0, 0, size.width as i32, size.height as i32);
+        }
+    }
+
+    pub fn swap_buffers(&self) -> Result<(), GLWError> {
+        Ok(self.surface.swap_buffers(&self.context)?)
+    }
+
+    /// Makes the context current again, e.g. after using another window.
+    pub fn make_current(&self) -> Result<(), GLWError> {
+        Ok(self.context.make_current(&self.surface)?)
+    }
+}
+
+/// The config with the most samples when multisampling, with the fewest
+/// otherwise.
+fn pick_config(configs: Box<dyn Iterator<Item = Config> + '_>, multisampled: bool) -> Config {
+    configs
+        .reduce(
+            |best, config| match (config.num_samples() > best.num_samples()) == multisampled {
+                true => config,
+                false => best,
+            },
+        )
+        .expect("no GL config matches the template")
+}